@@ -1,14 +1,154 @@
-use crate::aws::sqs::SqsClient;
-use crate::types::{QueueDetails, QueueInfo};
+use crate::archive::{
+    ArchivedMessage, ExportOptions, Exporter, read_archive, timestamped_path, write_archive,
+};
+use crate::audit::{AuditLog, RecentEntries};
+use crate::aws::profile::{ConnectionSettings, known_profiles};
+use crate::backend::{
+    BackendError, PURGE_COOLDOWN, QueueBackend, approximate_total, attribute_count,
+    queue_name_from_url,
+};
+use crate::body::{DecodedBody, decode};
+use crate::config::{Config, ProtectedQueue, Protection};
+use crate::filter::MessageFilter;
+use crate::format::{Formatter, duration};
+use crate::lint::{Finding, LintReport, lint_queues};
+use crate::metrics::{MetricWindow, QueueMetrics};
+use crate::peek::{
+    ActionReport, MAX_VISIBILITY_SECS, MessageAction, PEEK_VISIBILITY_SECS, PeekView,
+    describe_failure,
+};
+use crate::receives::{ReceiveAnalysis, max_receive_count};
+use crate::replay::{ReplayOptions, ReplayStatus, Replayer};
+use crate::retention::{OldestMessageAge, cloudwatch_age, oldest_sent, sampled_age};
+use crate::send::{MessageSource, SendOptions, Sender, looks_like_path};
+use crate::tail::{Tail, TailMode, TailView, redrive_warning};
+use crate::types::{OutgoingMessage, QueueDetails, QueueInfo};
 use anyhow::Result;
 use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::sync::Arc;
 use std::time::Duration;
+use tokio::sync::oneshot;
+use tokio::time::Instant;
+
+const DETAILS_SCROLL_STEP: u16 = 5;
+const AUDIT_ENTRIES_SHOWN: usize = 500;
+const PURGE_POLL_INTERVAL: Duration = Duration::from_secs(2);
+/// How long a CloudWatch oldest-message age is reused before asking again.
+const AGE_REFRESH: Duration = Duration::from_secs(60);
+/// How often every queue's settings are linted; they rarely change and each
+/// queue costs a GetQueueAttributes call.
+const LINT_REFRESH: Duration = Duration::from_secs(300);
+/// How long the message an edit-and-resend replaces stays hidden while the
+/// editor is open, well past the peek's own visibility timeout.
+const EDIT_HOLD_SECS: i32 = 900;
+
+/// An export running in the background of the UI loop, one batch per frame.
+pub struct ExportJob {
+    pub queue_name: String,
+    pub queue_url: String,
+    pub exporter: Exporter,
+    /// Purge the queue once the export has drained it (backup-then-purge).
+    pub purge_after: bool,
+}
+
+/// A replay running in the background of the UI loop, one batch per frame.
+pub struct ReplayJob {
+    pub queue_name: String,
+    pub replayer: Replayer,
+    pub first_failure: Option<String>,
+}
+
+/// Messages from a file or template being sent, one batch per frame.
+pub struct SendJob {
+    pub queue_name: String,
+    pub sender: Sender,
+    pub first_failure: Option<String>,
+}
+
+/// A body to open in `$EDITOR`. The UI loop hands the terminal to the
+/// editor and passes what was saved to `App::send_edited`.
+pub struct EditRequest {
+    /// Queue name or URL to send to.
+    pub target: String,
+    /// The editor starts from its body; attributes and group go out with
+    /// the edited body.
+    pub message: OutgoingMessage,
+    /// The peeked message being edited, deleted once the copy is sent.
+    pub replaces: Option<String>,
+}
+
+/// A purge SQS has accepted, polled until the queue reports no visible
+/// messages.
+pub struct PurgeWatch {
+    pub queue_name: String,
+    pub queue_url: String,
+    started: Instant,
+    next_check: Instant,
+    /// What backup-then-purge wrote first, for the final message.
+    backup: Option<String>,
+}
+
+/// What a submitted text prompt is for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputPurpose {
+    ReplayArchive,
+    /// Typed-name confirmation for purging a protected queue.
+    ConfirmPurge,
+    /// Typed-name confirmation for export-and-delete on a protected queue.
+    ConfirmExportDelete,
+    Profile,
+    Region,
+    /// Seconds for ChangeMessageVisibility on the peeked messages.
+    VisibilityTimeout,
+    /// Queue to move the peeked messages to.
+    MoveTarget,
+    /// Filter expression for the peeked messages.
+    MessageFilter,
+    /// Typed-name confirmation for tail-and-delete on a protected queue.
+    ConfirmTailDelete,
+    /// File or body template to send messages from.
+    SendSource,
+    /// How many messages to send from a template.
+    SendCount,
+    /// Queue to send an edited copy of the peeked message to.
+    ResendTarget,
+}
+
+pub struct TextInput {
+    pub purpose: InputPurpose,
+    pub prompt: String,
+    pub value: String,
+}
 
 pub struct App {
     pub queues: Vec<QueueInfo>,
     all_queues: Vec<QueueInfo>,
     pub selected_index: usize,
     pub selected_details: Option<QueueDetails>,
+    /// The latest CloudWatch oldest-message age and when it was fetched,
+    /// for the queue named.
+    cloudwatch_age: Option<(String, Instant, Option<OldestMessageAge>)>,
+    /// Why CloudWatch could not be asked, shown when falling back to
+    /// peeked messages.
+    pub cloudwatch_error: Option<String>,
+    /// Oldest SentTimestamp seen in the last peek of each queue.
+    sampled_oldest: HashMap<String, DateTime<Utc>>,
+    /// The CloudWatch window charted in the details pane; `None` hides the
+    /// panel and fetches no series. The oldest message age is asked for
+    /// either way, as the retention warning depends on it.
+    pub metrics_window: Option<MetricWindow>,
+    /// The charted metrics and when they were fetched.
+    pub metrics: Option<(QueueMetrics, Instant)>,
+    pub metrics_error: Option<String>,
+    /// Configuration findings per queue name, from the last lint.
+    pub lint_findings: BTreeMap<String, Vec<Finding>>,
+    /// Why a queue's settings could not be read in the last lint.
+    pub lint_errors: BTreeMap<String, String>,
+    lint_checked: Option<Instant>,
+    /// A lint running in the background, read by `step_lint`.
+    lint_task: Option<oneshot::Receiver<LintReport>>,
     pub last_refresh: Option<DateTime<Utc>>,
     pub refresh_interval: Duration,
     pub status_message: String,
@@ -16,17 +156,76 @@ pub struct App {
     pub filter_non_empty: bool,
     pub awaiting_purge_confirmation: bool,
     pub purge_in_progress: bool,
-    sqs_client: SqsClient,
+    /// Set once a purge has been confirmed; the UI loop runs it next.
+    pub purge_confirmed: bool,
+    pub purge_watch: Option<PurgeWatch>,
+    /// When each queue (by URL) was last purged from here, for the cooldown.
+    last_purged: HashMap<String, Instant>,
+    pub show_raw_attributes: bool,
+    /// Recent audit log entries, newest first, while the audit view is open.
+    pub audit_entries: Option<RecentEntries>,
+    /// Messages held in flight for inspection, while the message view is
+    /// open.
+    pub peek: Option<PeekView>,
+    /// The peeked message under the cursor, decoded, while the body viewer
+    /// is open.
+    pub body_view: Option<DecodedBody>,
+    /// Show the payload inside SNS and EventBridge envelopes.
+    pub unwrap_envelopes: bool,
+    /// An action on peeked messages waiting for Y/N.
+    pub pending_message_action: Option<MessageAction>,
+    /// Set once a message action is confirmed; the UI loop runs it next.
+    pub message_action: Option<MessageAction>,
+    /// Set by compose and edit-and-resend; the UI loop opens the editor
+    /// next.
+    pub edit_request: Option<EditRequest>,
+    pub details_scroll: u16,
+    pub human_readable: bool,
+    pub use_utc: bool,
+    pub export_dir: PathBuf,
+    pub export: Option<ExportJob>,
+    pub awaiting_export_confirmation: bool,
+    pub replay: Option<ReplayJob>,
+    pub send: Option<SendJob>,
+    /// The template waiting for its count.
+    send_template: Option<String>,
+    /// A long poll running in the background while the tail pane is open.
+    pub tail: Option<TailView>,
+    pub awaiting_tail_confirmation: bool,
+    pub input: Option<TextInput>,
+    pub config: Config,
+    pub connection: ConnectionSettings,
+    /// The region the backend resolved, for display.
+    pub region: Option<String>,
+    /// Set when listing queues fails for a reason that affects everything
+    /// (credentials, permissions, network); the UI shows a connection
+    /// screen until a refresh succeeds.
+    pub connection_error: Option<BackendError>,
+    /// Set when the profile or region changed; the UI loop rebuilds the
+    /// backend and refreshes.
+    pub reconnect_requested: bool,
+    /// Refuse every action that deletes or sends messages.
+    pub read_only: bool,
+    backend: Arc<dyn QueueBackend>,
 }
 
 impl App {
-    pub async fn new() -> Result<Self> {
-        let sqs_client = SqsClient::new().await?;
-        Ok(Self {
+    pub fn new(backend: Box<dyn QueueBackend>) -> Self {
+        Self {
             queues: Vec::new(),
             all_queues: Vec::new(),
             selected_index: 0,
             selected_details: None,
+            cloudwatch_age: None,
+            cloudwatch_error: None,
+            sampled_oldest: HashMap::new(),
+            metrics_window: None,
+            metrics: None,
+            metrics_error: None,
+            lint_findings: BTreeMap::new(),
+            lint_errors: BTreeMap::new(),
+            lint_checked: None,
+            lint_task: None,
             last_refresh: None,
             refresh_interval: Duration::from_secs(30),
             status_message: "Initializing...".to_string(),
@@ -34,18 +233,48 @@ impl App {
             filter_non_empty: false,
             awaiting_purge_confirmation: false,
             purge_in_progress: false,
-            sqs_client,
-        })
+            purge_confirmed: false,
+            purge_watch: None,
+            last_purged: HashMap::new(),
+            show_raw_attributes: false,
+            audit_entries: None,
+            peek: None,
+            body_view: None,
+            unwrap_envelopes: false,
+            pending_message_action: None,
+            message_action: None,
+            edit_request: None,
+            details_scroll: 0,
+            human_readable: true,
+            use_utc: false,
+            export_dir: PathBuf::from("."),
+            export: None,
+            awaiting_export_confirmation: false,
+            replay: None,
+            send: None,
+            send_template: None,
+            tail: None,
+            awaiting_tail_confirmation: false,
+            input: None,
+            config: Config::default(),
+            connection: ConnectionSettings::default(),
+            region: None,
+            connection_error: None,
+            reconnect_requested: false,
+            read_only: false,
+            backend: backend.into(),
+        }
     }
 
     pub async fn refresh_queues(&mut self) -> Result<()> {
         self.status_message = "Refreshing queues...".to_string();
 
-        match self.sqs_client.list_queues().await {
+        match self.backend.list_queues().await {
             Ok(mut queues) => {
                 // Sort queues by message count in descending order
-                queues.sort_by(|a, b| b.approximate_messages.cmp(&a.approximate_messages));
+                queues.sort_by_key(|q| std::cmp::Reverse(q.approximate_messages));
 
+                self.connection_error = None;
                 self.all_queues = queues;
                 self.apply_filter();
                 self.last_refresh = Some(Utc::now());
@@ -66,6 +295,8 @@ impl App {
                     self.selected_index = 0;
                 }
 
+                self.start_lint();
+
                 // Refresh details for selected queue
                 if !self.queues.is_empty() && self.selected_index < self.queues.len() {
                     self.refresh_selected_details().await?;
@@ -73,15 +304,56 @@ impl App {
             }
             Err(e) => {
                 self.status_message = format!("Error: {}", e);
+                if e.is_connection_problem() {
+                    self.connection_error = Some(e);
+                }
             }
         }
 
         Ok(())
     }
 
+    /// Swaps in a backend built for new connection settings.
+    pub fn set_backend(&mut self, backend: Box<dyn QueueBackend>, region: Option<String>) {
+        self.backend = backend.into();
+        self.tail = None;
+        self.region = region;
+        self.selected_details = None;
+        self.cloudwatch_age = None;
+        self.cloudwatch_error = None;
+        self.metrics = None;
+        self.metrics_error = None;
+        self.lint_findings.clear();
+        self.lint_errors.clear();
+        self.lint_checked = None;
+        self.lint_task = None;
+    }
+
+    pub fn request_profile(&mut self) {
+        let profiles = known_profiles();
+        let known = if profiles.is_empty() {
+            String::new()
+        } else {
+            format!(" [{}]", profiles.join(", "))
+        };
+        self.input = Some(TextInput {
+            purpose: InputPurpose::Profile,
+            prompt: format!("AWS profile{} (empty for default chain): ", known),
+            value: String::new(),
+        });
+    }
+
+    pub fn request_region(&mut self) {
+        self.input = Some(TextInput {
+            purpose: InputPurpose::Region,
+            prompt: "AWS region, e.g. eu-west-1 (empty for default): ".to_string(),
+            value: String::new(),
+        });
+    }
+
     pub async fn refresh_selected_details(&mut self) -> Result<()> {
         if let Some(queue) = self.queues.get(self.selected_index) {
-            match self.sqs_client.get_queue_details(&queue.url).await {
+            match self.backend.get_queue_details(&queue.url).await {
                 Ok(details) => {
                     self.selected_details = Some(details);
                 }
@@ -89,13 +361,153 @@ impl App {
                     self.status_message = format!("Error fetching details: {}", e);
                 }
             }
+            let (name, url) = (queue.name.clone(), queue.url.clone());
+            self.refresh_cloudwatch_age(&name, &url).await;
+            self.refresh_metrics(&name, &url, false).await;
         }
         Ok(())
     }
 
+    /// Lints every queue in the background when the last lint is older
+    /// than `LINT_REFRESH`, one GetQueueAttributes call per queue.
+    fn start_lint(&mut self) {
+        if self.lint_task.is_some()
+            || self
+                .lint_checked
+                .is_some_and(|checked| checked.elapsed() < LINT_REFRESH)
+        {
+            return;
+        }
+        self.lint_checked = Some(Instant::now());
+        let (sender, receiver) = oneshot::channel();
+        let backend = Arc::clone(&self.backend);
+        let queues = self.all_queues.clone();
+        let config = self.config.lint.clone();
+        tokio::spawn(async move {
+            let _ = sender.send(lint_queues(backend.as_ref(), &queues, &config).await);
+        });
+        self.lint_task = Some(receiver);
+    }
+
+    pub fn is_linting(&self) -> bool {
+        self.lint_task.is_some()
+    }
+
+    /// Takes in the findings once the background lint has finished.
+    pub fn step_lint(&mut self) {
+        let Some(task) = self.lint_task.as_mut() else {
+            return;
+        };
+        match task.try_recv() {
+            Ok(report) => {
+                self.lint_findings = report.findings;
+                self.lint_errors = report.errors;
+            }
+            Err(oneshot::error::TryRecvError::Empty) => return,
+            Err(oneshot::error::TryRecvError::Closed) => {}
+        }
+        self.lint_task = None;
+    }
+
+    /// Configuration findings for `queue_name`; empty until it has been
+    /// linted.
+    pub fn findings_for(&self, queue_name: &str) -> &[Finding] {
+        self.lint_findings
+            .get(queue_name)
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Opens the metrics panel, widens its window, or closes it after the
+    /// widest.
+    pub async fn cycle_metrics_window(&mut self) {
+        self.metrics_window = match self.metrics_window {
+            None => Some(MetricWindow::Hour),
+            Some(window) => window.next(),
+        };
+        let Some(window) = self.metrics_window else {
+            self.metrics = None;
+            self.metrics_error = None;
+            self.status_message = "Closed CloudWatch metrics".to_string();
+            return;
+        };
+        if let Some(queue) = self.selected_queue() {
+            let (name, url) = (queue.name.clone(), queue.url.clone());
+            self.refresh_metrics(&name, &url, true).await;
+            self.status_message = match &self.metrics_error {
+                Some(e) => format!("Cannot read CloudWatch metrics for '{}': {}", name, e),
+                None => format!("CloudWatch metrics for '{}' over the last {}", name, window),
+            };
+        }
+    }
+
+    /// Fetches the charted metrics for the selected queue when the panel is
+    /// open and they are older than `AGE_REFRESH` or for another queue.
+    async fn refresh_metrics(&mut self, name: &str, url: &str, force: bool) {
+        let Some(window) = self.metrics_window else {
+            return;
+        };
+        if !force
+            && let Some((metrics, fetched)) = &self.metrics
+            && metrics.queue_name == name
+            && fetched.elapsed() < AGE_REFRESH
+        {
+            return;
+        }
+        match QueueMetrics::fetch(self.backend.as_ref(), name, url, window, Utc::now()).await {
+            Ok(metrics) => {
+                self.metrics = Some((metrics, Instant::now()));
+                self.metrics_error = None;
+            }
+            Err(e) => {
+                self.metrics = None;
+                self.metrics_error = Some(e.to_string());
+            }
+        }
+    }
+
+    /// Asks CloudWatch for the oldest message age of the selected queue,
+    /// at most once per `AGE_REFRESH` since SQS publishes it every minute.
+    async fn refresh_cloudwatch_age(&mut self, name: &str, url: &str) {
+        if let Some((queue, fetched, _)) = &self.cloudwatch_age
+            && queue == name
+            && fetched.elapsed() < AGE_REFRESH
+        {
+            return;
+        }
+        let age = match cloudwatch_age(self.backend.as_ref(), url, Utc::now()).await {
+            Ok(age) => {
+                self.cloudwatch_error = None;
+                age
+            }
+            Err(e) => {
+                self.cloudwatch_error = Some(e.to_string());
+                None
+            }
+        };
+        self.cloudwatch_age = Some((name.to_string(), Instant::now(), age));
+    }
+
+    /// The best estimate of how old the selected queue's oldest message is:
+    /// CloudWatch when it has a datapoint, otherwise the oldest message the
+    /// last peek saw. `None` for an empty queue.
+    pub fn oldest_message_age(&self, now: DateTime<Utc>) -> Option<OldestMessageAge> {
+        let queue = self.selected_queue()?;
+        if queue.approximate_messages + queue.approximate_messages_not_visible == 0 {
+            return None;
+        }
+        match &self.cloudwatch_age {
+            Some((name, _, Some(age))) if *name == queue.name => Some(*age),
+            _ => self
+                .sampled_oldest
+                .get(&queue.name)
+                .map(|&sent| sampled_age(sent, now)),
+        }
+    }
+
     pub fn next_queue(&mut self) {
         if !self.queues.is_empty() {
             self.selected_index = (self.selected_index + 1) % self.queues.len();
+            self.details_scroll = 0;
         }
     }
 
@@ -106,9 +518,78 @@ impl App {
             } else {
                 self.selected_index = self.queues.len() - 1;
             }
+            self.details_scroll = 0;
+        }
+    }
+
+    pub fn toggle_raw_attributes(&mut self) {
+        self.show_raw_attributes = !self.show_raw_attributes;
+        self.details_scroll = 0;
+    }
+
+    /// Opens the audit view with the latest entries from the configured
+    /// log, or closes it.
+    pub fn toggle_audit_log(&mut self) {
+        self.details_scroll = 0;
+        if self.audit_entries.take().is_some() {
+            return;
+        }
+        let log = AuditLog::new(self.config.audit_log());
+        match log.recent(AUDIT_ENTRIES_SHOWN) {
+            Ok(recent) => {
+                self.status_message = format!(
+                    "{} audit entries from {}",
+                    recent.entries.len(),
+                    log.path().display()
+                );
+                if recent.unreadable > 0 {
+                    self.status_message
+                        .push_str(&format!(", {} unreadable lines skipped", recent.unreadable));
+                }
+                self.audit_entries = Some(recent);
+            }
+            Err(e) => self.status_message = format!("Failed to read audit log: {:#}", e),
+        }
+    }
+
+    pub fn formatter(&self) -> Formatter {
+        Formatter {
+            human: self.human_readable,
+            utc: self.use_utc,
         }
     }
 
+    pub fn toggle_human_readable(&mut self) {
+        self.human_readable = !self.human_readable;
+        self.status_message = if self.human_readable {
+            "Showing human-readable values".to_string()
+        } else {
+            "Showing raw values".to_string()
+        };
+    }
+
+    pub fn toggle_timezone(&mut self) {
+        self.use_utc = !self.use_utc;
+        self.status_message = if self.use_utc {
+            "Timestamps in UTC".to_string()
+        } else {
+            "Timestamps in local time".to_string()
+        };
+    }
+
+    pub fn scroll_details_down(&mut self) {
+        self.details_scroll = self.details_scroll.saturating_add(DETAILS_SCROLL_STEP);
+    }
+
+    pub fn scroll_details_up(&mut self) {
+        self.details_scroll = self.details_scroll.saturating_sub(DETAILS_SCROLL_STEP);
+    }
+
+    /// Shows a failure from the UI loop in the status bar instead of exiting.
+    pub fn report_error(&mut self, error: &anyhow::Error) {
+        self.status_message = format!("Error: {:#}", error);
+    }
+
     pub fn quit(&mut self) {
         self.should_quit = true;
     }
@@ -151,18 +632,174 @@ impl App {
         }
     }
 
+    /// The protection entry matching the selected queue, if any.
+    pub fn selected_protection(&self) -> Option<&ProtectedQueue> {
+        self.selected_queue()
+            .and_then(|q| self.config.protection_for(&q.name))
+    }
+
+    /// Reports why `action` may not touch the selected queue, either because
+    /// of `--read-only` or a `block` protection, and returns true if so.
+    /// Without a selection there is nothing to act on, which also counts.
+    fn action_refused(&mut self, action: &str) -> bool {
+        match self.selected_queue().map(|q| q.name.clone()) {
+            Some(queue_name) => self.queue_action_refused(&queue_name, action),
+            None => true,
+        }
+    }
+
+    fn queue_action_refused(&mut self, queue_name: &str, action: &str) -> bool {
+        if self.read_only {
+            self.status_message = format!("Read-only mode: cannot {} '{}'", action, queue_name);
+            return true;
+        }
+        if let Some(protection) = self.config.protection_for(queue_name)
+            && protection.protection == Protection::Block
+        {
+            self.status_message = format!(
+                "Cannot {} '{}': {}",
+                action,
+                queue_name,
+                protection.reason()
+            );
+            return true;
+        }
+        false
+    }
+
+    /// Opens a typed-name prompt if the selected queue's protection asks for
+    /// one, returning true if it did.
+    fn request_typed_name(&mut self, purpose: InputPurpose, action: &str) -> bool {
+        let Some(protection) = self.selected_protection() else {
+            return false;
+        };
+        if protection.protection != Protection::TypeName {
+            return false;
+        }
+        let reason = protection.reason().to_string();
+        let queue_name = self.queues[self.selected_index].name.clone();
+        self.input = Some(TextInput {
+            purpose,
+            prompt: format!(
+                "'{}' is protected ({}). Type its name to {}, Esc to cancel: ",
+                queue_name, reason, action
+            ),
+            value: String::new(),
+        });
+        true
+    }
+
+    /// Time left before SQS accepts another purge of `queue_url`.
+    pub fn purge_cooldown(&self, queue_url: &str) -> Option<Duration> {
+        let last = self.last_purged.get(queue_url)?;
+        PURGE_COOLDOWN
+            .checked_sub(last.elapsed())
+            .filter(|left| !left.is_zero())
+    }
+
     pub fn request_purge_confirmation(&mut self) {
-        if let Some(queue_name) = self.selected_queue().map(|q| q.name.clone()) {
-            self.awaiting_purge_confirmation = true;
+        if self.action_refused("purge") {
+            return;
+        }
+        if let Some(queue) = self.selected_queue()
+            && let Some(left) = self.purge_cooldown(&queue.url)
+        {
             self.status_message = format!(
-                "Purge queue '{}'? Press Y to confirm, N to cancel",
-                queue_name
+                "Queue '{}' was purged recently; SQS allows one purge every 60 seconds. Try again in {}",
+                queue.name,
+                duration(left.as_secs_f64().ceil() as i64)
             );
+            return;
+        }
+        let action = if self.purge_requires_backup() {
+            "back up and purge it"
+        } else {
+            "purge it"
+        };
+        if self.request_typed_name(InputPurpose::ConfirmPurge, action) {
+            return;
+        }
+        if let Some(queue_name) = self.selected_queue().map(|q| q.name.clone()) {
+            self.awaiting_purge_confirmation = true;
+            self.status_message = if self.purge_requires_backup() {
+                format!(
+                    "Purge queue '{}'? Press Y to back up then purge, N to cancel",
+                    queue_name
+                )
+            } else {
+                format!(
+                    "Purge queue '{}'? Press Y to confirm, B to back up first, N to cancel",
+                    queue_name
+                )
+            };
+        }
+    }
+
+    /// Whether the config makes backup-then-purge the default for the
+    /// selected queue.
+    pub fn purge_requires_backup(&self) -> bool {
+        self.selected_protection()
+            .is_some_and(|p| p.backup_before_purge)
+    }
+
+    /// Starts the confirmed purge: a backup export for queues configured
+    /// for it, otherwise flags the plain purge for the UI loop.
+    pub fn confirm_purge(&mut self) {
+        if self.purge_requires_backup() {
+            self.begin_backup_then_purge();
+        } else {
+            self.awaiting_purge_confirmation = false;
+            self.purge_confirmed = true;
+        }
+    }
+
+    /// Drains the selected queue into a timestamped archive in the backup
+    /// directory; the purge runs only once that export completes.
+    pub fn begin_backup_then_purge(&mut self) {
+        self.awaiting_purge_confirmation = false;
+
+        let Some(queue) = self.selected_queue() else {
+            return;
+        };
+        let queue_name = queue.name.clone();
+        let queue_url = queue.url.clone();
+        let dir = self.config.backup_dir();
+
+        let exporter = std::fs::create_dir_all(&dir)
+            .map_err(anyhow::Error::from)
+            .and_then(|()| {
+                Exporter::create(
+                    &timestamped_path(&dir, &queue_name),
+                    &queue_url,
+                    ExportOptions::default(),
+                )
+            });
+        match exporter {
+            Ok(exporter) => {
+                self.status_message = format!(
+                    "Backing up '{}' to {} before purge...",
+                    queue_name,
+                    exporter.path().display()
+                );
+                self.export = Some(ExportJob {
+                    queue_name,
+                    queue_url,
+                    exporter,
+                    purge_after: true,
+                });
+            }
+            Err(e) => {
+                self.status_message = format!(
+                    "Backup of '{}' failed, queue not purged: {:#}",
+                    queue_name, e
+                );
+            }
         }
     }
 
     pub fn begin_purge(&mut self) -> Option<(String, String)> {
         self.awaiting_purge_confirmation = false;
+        self.purge_confirmed = false;
 
         if let Some(queue) = self.selected_queue() {
             let queue_name = queue.name.clone();
@@ -177,23 +814,2083 @@ impl App {
     }
 
     pub async fn execute_purge(&mut self, queue_url: &str, queue_name: &str) -> Result<()> {
-        match self.sqs_client.purge_queue(queue_url).await {
-            Ok(_) => {
-                self.status_message = format!("Queue '{}' purged successfully", queue_name);
-                // Refresh queues to show updated counts
-                self.refresh_queues().await?;
+        self.purge(queue_url, queue_name, None).await;
+        self.purge_in_progress = false;
+        Ok(())
+    }
+
+    /// Issues PurgeQueue and, once SQS accepts it, starts watching the queue
+    /// empty. Failures are reported in the status bar.
+    async fn purge(&mut self, queue_url: &str, queue_name: &str, backup: Option<String>) {
+        let started = Instant::now();
+        match self.backend.purge_queue(queue_url).await {
+            Ok(()) => {
+                self.last_purged.insert(queue_url.to_string(), started);
+                self.status_message = format!(
+                    "Purge of '{}' accepted, waiting for the queue to empty... (N to stop waiting)",
+                    queue_name
+                );
+                self.purge_watch = Some(PurgeWatch {
+                    queue_name: queue_name.to_string(),
+                    queue_url: queue_url.to_string(),
+                    started,
+                    next_check: started,
+                    backup,
+                });
+            }
+            Err(BackendError::PurgeInProgress) => {
+                // Someone purged it elsewhere; when exactly is unknown, so
+                // assume just now.
+                self.last_purged.insert(queue_url.to_string(), started);
+                self.status_message = format!(
+                    "Queue '{}' is already being purged; SQS allows one purge every 60 seconds. Try again in {}",
+                    queue_name,
+                    duration(PURGE_COOLDOWN.as_secs() as i64)
+                );
             }
             Err(e) => {
                 self.status_message = format!("Failed to purge queue '{}': {}", queue_name, e);
             }
         }
+    }
 
-        self.purge_in_progress = false;
-        Ok(())
+    /// Checks a watched purge every couple of seconds until the queue
+    /// reports no visible messages, or gives up after the cooldown.
+    pub async fn step_purge_watch(&mut self) {
+        let Some(watch) = self.purge_watch.as_mut() else {
+            return;
+        };
+        let now = Instant::now();
+        if now < watch.next_check {
+            return;
+        }
+        watch.next_check = now + PURGE_POLL_INTERVAL;
+        let waited = now.duration_since(watch.started);
+        let queue_url = watch.queue_url.clone();
+
+        let visible = self
+            .backend
+            .get_queue_details(&queue_url)
+            .await
+            .ok()
+            .and_then(|details| attribute_count(&details, "ApproximateNumberOfMessages"));
+        let visible_text = visible.map_or_else(|| "?".to_string(), |n| self.formatter().count(n));
+        let elapsed = duration(waited.as_secs() as i64);
+
+        if visible != Some(0) && waited < PURGE_COOLDOWN {
+            if let Some(watch) = &self.purge_watch {
+                self.status_message = format!(
+                    "Purging '{}': {} visible after {} (N to stop waiting)",
+                    watch.queue_name, visible_text, elapsed
+                );
+            }
+            return;
+        }
+
+        let Some(watch) = self.purge_watch.take() else {
+            return;
+        };
+        let what = match watch.backup {
+            Some(backup) => format!("{} and purged", backup),
+            None => "purged".to_string(),
+        };
+        self.refresh_queues().await.ok();
+        self.status_message = if visible == Some(0) {
+            format!("Queue '{}' {} in {}", watch.queue_name, what, elapsed)
+        } else {
+            format!(
+                "Queue '{}' {}, but still reports {} visible messages after {}",
+                watch.queue_name, what, visible_text, elapsed
+            )
+        };
+    }
+
+    pub fn stop_purge_watch(&mut self) {
+        if let Some(watch) = self.purge_watch.take() {
+            self.status_message = format!(
+                "Stopped waiting for '{}'; SQS finishes the purge on its own",
+                watch.queue_name
+            );
+        }
     }
 
     pub fn cancel_purge(&mut self) {
         self.awaiting_purge_confirmation = false;
         self.status_message = "Purge cancelled".to_string();
     }
+
+    /// Whether a Y/N or text prompt currently owns the status bar.
+    pub fn is_prompting(&self) -> bool {
+        self.awaiting_purge_confirmation
+            || self.awaiting_export_confirmation
+            || self.awaiting_tail_confirmation
+            || self.pending_message_action.is_some()
+            || self.input.is_some()
+    }
+
+    /// Whether a multi-step export, replay, send, purge watch or tail is
+    /// running.
+    pub fn is_busy(&self) -> bool {
+        self.export.is_some()
+            || self.replay.is_some()
+            || self.send.is_some()
+            || self.purge_watch.is_some()
+            || self.tail.is_some()
+    }
+
+    pub fn input_char(&mut self, c: char) {
+        if let Some(input) = self.input.as_mut() {
+            input.value.push(c);
+        }
+    }
+
+    pub fn input_backspace(&mut self) {
+        if let Some(input) = self.input.as_mut() {
+            input.value.pop();
+        }
+    }
+
+    pub fn cancel_input(&mut self) {
+        if self.input.take().is_some() {
+            self.status_message = "Cancelled".to_string();
+        }
+    }
+
+    pub fn submit_input(&mut self) {
+        let Some(input) = self.input.take() else {
+            return;
+        };
+        let value = input.value.trim().to_string();
+        match input.purpose {
+            InputPurpose::ReplayArchive => self.start_replay(&value),
+            InputPurpose::ConfirmPurge
+            | InputPurpose::ConfirmExportDelete
+            | InputPurpose::ConfirmTailDelete
+                if self.selected_queue().is_none_or(|q| q.name != value) =>
+            {
+                self.status_message = "Queue name did not match, nothing changed".to_string();
+            }
+            InputPurpose::ConfirmPurge => self.confirm_purge(),
+            InputPurpose::ConfirmExportDelete => self.start_export(true),
+            InputPurpose::ConfirmTailDelete => self.start_tail(TailMode::Delete),
+            InputPurpose::Profile => {
+                self.connection.profile = (!value.is_empty()).then_some(value);
+                self.status_message =
+                    format!("Connecting with {}...", self.connection.credential_source());
+                self.reconnect_requested = true;
+            }
+            InputPurpose::Region => {
+                self.status_message = if value.is_empty() {
+                    "Connecting with the default region...".to_string()
+                } else {
+                    format!("Connecting to {}...", value)
+                };
+                self.connection.region = (!value.is_empty()).then_some(value);
+                self.reconnect_requested = true;
+            }
+            InputPurpose::VisibilityTimeout => match value.parse::<i32>() {
+                Ok(secs) if (0..=MAX_VISIBILITY_SECS).contains(&secs) => {
+                    self.message_action = Some(MessageAction::ChangeVisibility(secs));
+                }
+                _ => {
+                    self.status_message = format!(
+                        "Visibility timeout must be 0 to {} seconds, nothing changed",
+                        MAX_VISIBILITY_SECS
+                    );
+                }
+            },
+            InputPurpose::MoveTarget => self.confirm_message_move(value),
+            InputPurpose::MessageFilter => self.set_message_filter(&value),
+            InputPurpose::SendSource => self.choose_send_source(value),
+            InputPurpose::ResendTarget => self.confirm_message_resend(value),
+            InputPurpose::SendCount => match (value.parse::<usize>(), self.send_template.take()) {
+                (Ok(count), Some(template)) if count > 0 => {
+                    self.start_send(MessageSource::Template(template), Some(count));
+                }
+                _ => {
+                    self.status_message =
+                        "Count must be a positive number, nothing sent".to_string()
+                }
+            },
+        }
+    }
+
+    /// Opens the message view on the selected queue, or closes it. Closed
+    /// messages are not released; they reappear once their visibility
+    /// timeout runs out.
+    pub async fn toggle_peek(&mut self) {
+        if self.peek.take().is_some() {
+            self.body_view = None;
+            self.pending_message_action = None;
+            self.status_message = "Closed message view".to_string();
+            return;
+        }
+        if let Some(queue) = self.selected_queue() {
+            let (name, url) = (queue.name.clone(), queue.url.clone());
+            self.fetch_peek(&name, &url, None).await;
+        }
+    }
+
+    /// Receives a fresh batch for the open message view.
+    pub async fn refetch_peek(&mut self) {
+        if let Some(peek) = self.peek.take() {
+            self.fetch_peek(&peek.queue_name, &peek.queue_url, peek.filter)
+                .await;
+            if let Some(new) = self.peek.as_mut()
+                && peek.grouped
+            {
+                new.toggle_grouped();
+            }
+        }
+    }
+
+    async fn fetch_peek(
+        &mut self,
+        queue_name: &str,
+        queue_url: &str,
+        filter: Option<MessageFilter>,
+    ) {
+        match PeekView::fetch(self.backend.as_ref(), queue_name, queue_url, filter).await {
+            Ok(mut peek) => {
+                if let Some(sent) = oldest_sent(peek.messages.iter().map(|m| &m.message)) {
+                    self.sampled_oldest.insert(queue_name.to_string(), sent);
+                }
+                // The details pane shows the selected queue, which is the
+                // one being peeked
+                peek.max_receive_count = self
+                    .selected_details
+                    .as_ref()
+                    .filter(|_| self.selected_queue().is_some_and(|q| q.name == queue_name))
+                    .and_then(max_receive_count);
+                self.status_message = if peek.messages.is_empty() {
+                    format!("No visible messages in '{}'", queue_name)
+                } else if let Some(filter) = &peek.filter {
+                    format!(
+                        "Holding {} messages from '{}' for {}, {} match {}",
+                        peek.messages.len(),
+                        queue_name,
+                        duration(i64::from(PEEK_VISIBILITY_SECS)),
+                        peek.shown.len(),
+                        filter
+                    )
+                } else {
+                    format!(
+                        "Holding {} messages from '{}' for {}",
+                        peek.messages.len(),
+                        queue_name,
+                        duration(i64::from(PEEK_VISIBILITY_SECS))
+                    )
+                };
+                let analysis = ReceiveAnalysis::of(
+                    peek.messages.iter().map(|m| &m.message),
+                    peek.max_receive_count,
+                    Utc::now(),
+                );
+                if let Some(max) = analysis.max_receive_count
+                    && analysis.near_redrive > 0
+                {
+                    self.status_message.push_str(&format!(
+                        ", {} near maxReceiveCount {} ([O] groups by receive count)",
+                        analysis.near_redrive, max
+                    ));
+                }
+                self.details_scroll = 0;
+                self.peek = Some(peek);
+                self.update_body_view();
+            }
+            Err(e) => {
+                self.status_message = format!("Failed to receive from '{}': {}", queue_name, e);
+            }
+        }
+    }
+
+    pub fn next_message(&mut self) {
+        if let Some(peek) = self.peek.as_mut() {
+            peek.next();
+            self.update_body_view();
+        }
+    }
+
+    pub fn previous_message(&mut self) {
+        if let Some(peek) = self.peek.as_mut() {
+            peek.previous();
+            self.update_body_view();
+        }
+    }
+
+    /// Closes the message body if one is shown, otherwise the message
+    /// view.
+    pub fn close_view(&mut self) {
+        if self.body_view.take().is_some() {
+            self.details_scroll = 0;
+        } else if self.peek.take().is_some() {
+            self.pending_message_action = None;
+            self.status_message = "Closed message view".to_string();
+        }
+    }
+
+    /// Opens the body of the message under the cursor full-screen, or
+    /// returns to the message list.
+    pub fn toggle_body_view(&mut self) {
+        if self.body_view.take().is_none() {
+            self.body_view = self.decode_cursor_message();
+        }
+        self.details_scroll = 0;
+    }
+
+    /// Orders the message view by receive count and shows how the counts
+    /// and first-receive ages are spread.
+    pub fn toggle_receive_groups(&mut self) {
+        let Some(peek) = self.peek.as_mut() else {
+            return;
+        };
+        peek.toggle_grouped();
+        self.status_message = if peek.grouped {
+            let analysis = ReceiveAnalysis::of(
+                peek.shown.iter().map(|&i| &peek.messages[i].message),
+                peek.max_receive_count,
+                Utc::now(),
+            );
+            analysis.diagnosis().to_string()
+        } else {
+            "Showing messages in the order received".to_string()
+        };
+        self.update_body_view();
+    }
+
+    pub fn toggle_unwrap_envelopes(&mut self) {
+        self.unwrap_envelopes = !self.unwrap_envelopes;
+        self.status_message = if self.unwrap_envelopes {
+            "Showing the payload inside SNS and EventBridge envelopes".to_string()
+        } else {
+            "Showing message bodies as sent".to_string()
+        };
+        self.update_body_view();
+    }
+
+    /// Re-decodes the open body viewer after the cursor or a setting
+    /// changed.
+    fn update_body_view(&mut self) {
+        if self.body_view.is_some() {
+            self.body_view = self.decode_cursor_message();
+            self.details_scroll = 0;
+        }
+    }
+
+    fn decode_cursor_message(&self) -> Option<DecodedBody> {
+        let peek = self.peek.as_ref()?;
+        let peeked = peek.current()?;
+        Some(decode(&peeked.message.body, self.unwrap_envelopes))
+    }
+
+    pub fn toggle_message_selected(&mut self) {
+        if let Some(peek) = self.peek.as_mut() {
+            peek.toggle_selected();
+            peek.next();
+        }
+    }
+
+    pub fn request_message_filter(&mut self) {
+        let Some(peek) = self.peek.as_ref() else {
+            return;
+        };
+        self.input = Some(TextInput {
+            purpose: InputPurpose::MessageFilter,
+            prompt: "Filter, e.g. .status == \"FAILED\" and receives > 3 (empty clears): "
+                .to_string(),
+            value: peek
+                .filter
+                .as_ref()
+                .map(ToString::to_string)
+                .unwrap_or_default(),
+        });
+    }
+
+    fn set_message_filter(&mut self, expression: &str) {
+        let filter = if expression.is_empty() {
+            None
+        } else {
+            match expression.parse::<MessageFilter>() {
+                Ok(filter) => Some(filter),
+                Err(e) => {
+                    self.status_message = format!("Invalid filter: {:#}", e);
+                    return;
+                }
+            }
+        };
+        let Some(peek) = self.peek.as_mut() else {
+            return;
+        };
+        peek.set_filter(filter);
+        self.status_message = match &peek.filter {
+            Some(filter) => format!(
+                "{} of {} messages match {}",
+                peek.shown.len(),
+                peek.messages.len(),
+                filter
+            ),
+            None => format!("Showing all {} messages", peek.messages.len()),
+        };
+        self.update_body_view();
+    }
+
+    /// Selects every message the filter shows, so batch actions apply to
+    /// exactly the matches.
+    pub fn toggle_all_messages_selected(&mut self) {
+        if let Some(peek) = self.peek.as_mut() {
+            let selected = peek.toggle_all_shown();
+            self.status_message = format!("{} message(s) selected", selected);
+        }
+    }
+
+    /// Writes the selected (or current) peeked messages to a timestamped
+    /// archive in the export directory. Nothing is deleted.
+    pub fn export_peeked(&mut self) {
+        let Some(peek) = self.peek.as_ref() else {
+            return;
+        };
+        let records: Vec<ArchivedMessage> = peek
+            .targets()
+            .into_iter()
+            .map(|i| ArchivedMessage::from_message(&peek.queue_url, &peek.messages[i].message))
+            .collect();
+        if records.is_empty() {
+            return;
+        }
+        let path = timestamped_path(&self.export_dir, &peek.queue_name);
+        self.status_message = match write_archive(&path, &records) {
+            Ok(()) => format!(
+                "Exported {} message(s) from '{}' to {}",
+                records.len(),
+                peek.queue_name,
+                path.display()
+            ),
+            Err(e) => format!("Failed to export messages: {:#}", e),
+        };
+    }
+
+    /// The open message view's queue name and how many messages the next
+    /// action would touch, if there are any.
+    fn message_targets(&self) -> Option<(String, usize)> {
+        let peek = self.peek.as_ref()?;
+        let count = peek.targets().len();
+        (count > 0).then(|| (peek.queue_name.clone(), count))
+    }
+
+    pub fn request_message_delete(&mut self) {
+        let Some((queue_name, count)) = self.message_targets() else {
+            return;
+        };
+        if self.queue_action_refused(&queue_name, "delete messages from") {
+            return;
+        }
+        self.pending_message_action = Some(MessageAction::Delete);
+        self.status_message = format!(
+            "Delete {} message(s) from '{}'? Press Y to confirm, N to cancel",
+            count, queue_name
+        );
+    }
+
+    pub fn request_message_visibility(&mut self) {
+        let Some((queue_name, count)) = self.message_targets() else {
+            return;
+        };
+        if self.queue_action_refused(&queue_name, "change visibility of messages in") {
+            return;
+        }
+        self.input = Some(TextInput {
+            purpose: InputPurpose::VisibilityTimeout,
+            prompt: format!(
+                "Hide {} message(s) for how many seconds (0 releases them now): ",
+                count
+            ),
+            value: String::new(),
+        });
+    }
+
+    pub fn request_message_move(&mut self) {
+        let Some((queue_name, count)) = self.message_targets() else {
+            return;
+        };
+        if self.queue_action_refused(&queue_name, "move messages from") {
+            return;
+        }
+        self.input = Some(TextInput {
+            purpose: InputPurpose::MoveTarget,
+            prompt: format!(
+                "Move {} message(s) from '{}' to queue (name or URL): ",
+                count, queue_name
+            ),
+            value: String::new(),
+        });
+    }
+
+    fn confirm_message_move(&mut self, target: String) {
+        let Some(peek) = self.peek.as_ref() else {
+            return;
+        };
+        if target.is_empty() || target == peek.queue_name || target == peek.queue_url {
+            self.status_message = "Pick a different queue to move to, nothing changed".to_string();
+            return;
+        }
+        let target_name = queue_name_from_url(&target).to_string();
+        if self.queue_action_refused(&target_name, "move messages into") {
+            return;
+        }
+        self.message_action = Some(MessageAction::Move { target });
+    }
+
+    /// Opens the editor on an empty body for the selected queue.
+    pub fn request_compose(&mut self) {
+        if self.action_refused("send to") {
+            return;
+        }
+        if let Some(queue) = self.selected_queue() {
+            self.edit_request = Some(EditRequest {
+                target: queue.name.clone(),
+                message: OutgoingMessage::default(),
+                replaces: None,
+            });
+        }
+    }
+
+    /// Asks where to send an edited copy of the message under the cursor,
+    /// suggesting the source queue when the view is on a dead-letter queue.
+    pub fn request_message_resend(&mut self) {
+        let Some(peek) = self.peek.as_ref() else {
+            return;
+        };
+        if peek.current().is_none() {
+            return;
+        }
+        let suggestion = self
+            .source_queue_of(&peek.queue_name)
+            .unwrap_or_else(|| peek.queue_name.clone());
+        self.input = Some(TextInput {
+            purpose: InputPurpose::ResendTarget,
+            prompt: "Edit and send a copy to queue (name or URL): ".to_string(),
+            value: suggestion,
+        });
+    }
+
+    /// `orders` for `orders-dlq` (or `orders.fifo` for `orders-dlq.fifo`),
+    /// if such a queue is listed.
+    fn source_queue_of(&self, dlq_name: &str) -> Option<String> {
+        let base = dlq_name.trim_end_matches(".fifo");
+        let source = base
+            .strip_suffix("-dlq")
+            .or_else(|| base.strip_suffix("_dlq"))?;
+        let source = format!("{}{}", source, &dlq_name[base.len()..]);
+        self.all_queues
+            .iter()
+            .any(|q| q.name == source)
+            .then_some(source)
+    }
+
+    fn confirm_message_resend(&mut self, target: String) {
+        let Some(peek) = self.peek.as_ref() else {
+            return;
+        };
+        let Some(current) = peek.current() else {
+            return;
+        };
+        if target.is_empty() {
+            self.status_message = "No queue given, nothing sent".to_string();
+            return;
+        }
+        let original = current.message.clone();
+        let source_name = peek.queue_name.clone();
+        if self.queue_action_refused(queue_name_from_url(&target), "send to")
+            || self.queue_action_refused(&source_name, "delete messages from")
+        {
+            return;
+        }
+        self.edit_request = Some(EditRequest {
+            target,
+            message: OutgoingMessage {
+                body: original.body,
+                message_attributes: original.message_attributes,
+                message_group_id: original.attributes.get("MessageGroupId").cloned(),
+                ..OutgoingMessage::default()
+            },
+            replaces: Some(original.message_id),
+        });
+    }
+
+    /// Keeps the message an edit replaces in flight for `EDIT_HOLD_SECS`, so
+    /// its receipt handle still works when the edited copy is sent.
+    pub async fn hold_for_edit(&mut self, request: &EditRequest) {
+        let (Some(original), Some(peek)) = (request.replaces.as_ref(), self.peek.as_mut()) else {
+            return;
+        };
+        let problem = match peek
+            .change_visibility_one(self.backend.as_ref(), original, EDIT_HOLD_SECS)
+            .await
+        {
+            Ok(failures) => failures.first().map(describe_failure),
+            Err(e) => Some(e.to_string()),
+        };
+        if let Some(problem) = problem {
+            self.status_message = format!(
+                "Could not keep the original hidden while editing ({}); \
+                 sending will leave it in '{}' as well",
+                problem, peek.queue_name
+            );
+        }
+    }
+
+    /// Sends what the editor saved. An edited copy of a peeked message
+    /// replaces the original, which is deleted once the copy is sent, or
+    /// released again if nothing was sent.
+    pub async fn send_edited(&mut self, request: EditRequest, edited: Result<Option<String>>) {
+        let replaces = request.replaces.clone();
+        let sent = self.send_edited_copy(request, edited).await;
+        let (Some(original), Some(peek)) = (replaces, self.peek.as_mut()) else {
+            return;
+        };
+        if !sent {
+            // `hold_for_edit` hid it for much longer than a peek does. An
+            // expired handle means it is already back in the queue.
+            let problem = match peek
+                .change_visibility_one(self.backend.as_ref(), &original, 0)
+                .await
+            {
+                Ok(failures) => failures
+                    .iter()
+                    .find(|f| {
+                        !matches!(
+                            f.code.as_str(),
+                            "ReceiptHandleIsInvalid" | "MessageNotInflight"
+                        )
+                    })
+                    .map(describe_failure),
+                Err(e) => Some(e.to_string()),
+            };
+            if let Some(problem) = problem {
+                self.status_message.push_str(&format!(
+                    "; the original could not be released ({}) and stays hidden in '{}' for up to {}",
+                    problem,
+                    peek.queue_name,
+                    duration(i64::from(EDIT_HOLD_SECS))
+                ));
+            }
+            return;
+        }
+
+        let outcome = match peek.delete_one(self.backend.as_ref(), &original).await {
+            Ok(failures) => match failures.first() {
+                Some(failure) => format!(
+                    "; the original was not deleted, so '{}' now holds a duplicate: {}",
+                    peek.queue_name,
+                    describe_failure(failure)
+                ),
+                None => format!(" and deleted the original from '{}'", peek.queue_name),
+            },
+            Err(e) => format!(
+                "; the original was not deleted, so '{}' now holds a duplicate: {}",
+                peek.queue_name, e
+            ),
+        };
+        self.status_message.push_str(&outcome);
+        self.update_body_view();
+    }
+
+    /// Sends the saved body to the request's target, returning whether it
+    /// went out. Every outcome is reported in the status bar.
+    async fn send_edited_copy(
+        &mut self,
+        request: EditRequest,
+        edited: Result<Option<String>>,
+    ) -> bool {
+        let body = match edited {
+            Ok(Some(body)) => body,
+            Ok(None) => {
+                self.status_message =
+                    "Editor closed without a saved body, nothing sent".to_string();
+                return false;
+            }
+            Err(e) => {
+                self.status_message = format!("Cannot open editor: {:#}", e);
+                return false;
+            }
+        };
+        let target_name = queue_name_from_url(&request.target).to_string();
+        let queue_url = match self.backend.queue_url(&request.target).await {
+            Ok(url) => url,
+            Err(e) => {
+                self.status_message = format!("Cannot send to '{}': {}", target_name, e);
+                return false;
+            }
+        };
+
+        let mut message = OutgoingMessage {
+            id: "0".to_string(),
+            body,
+            ..request.message
+        };
+        message.prepare_for_queue(
+            &queue_url,
+            &format!("edited-{}", Utc::now().timestamp_millis()),
+        );
+        let sent = match self.backend.send_messages(&queue_url, &[message]).await {
+            Ok(result) => match (result.sent.first(), result.failed.first()) {
+                (Some(sent), _) => sent.message_id.clone(),
+                (None, Some(failure)) => {
+                    self.status_message = format!(
+                        "Failed to send to '{}': {}: {}",
+                        target_name, failure.code, failure.message
+                    );
+                    return false;
+                }
+                (None, None) => String::new(),
+            },
+            Err(e) => {
+                self.status_message = format!("Failed to send to '{}': {}", target_name, e);
+                return false;
+            }
+        };
+        self.status_message = format!("Sent message {} to '{}'", sent, target_name);
+        true
+    }
+
+    pub fn confirm_message_action(&mut self) {
+        self.message_action = self.pending_message_action.take();
+    }
+
+    pub fn cancel_message_action(&mut self) {
+        if self.pending_message_action.take().is_some() {
+            self.status_message = "Cancelled".to_string();
+        }
+    }
+
+    /// Runs the confirmed message action and reports what happened to each
+    /// target message.
+    pub async fn run_message_action(&mut self) {
+        let Some(action) = self.message_action.take() else {
+            return;
+        };
+        let Some(peek) = self.peek.as_mut() else {
+            return;
+        };
+        let queue_name = peek.queue_name.clone();
+        self.status_message = match peek.apply(self.backend.as_ref(), &action).await {
+            Ok(report) => describe_report(&action, &queue_name, &report),
+            Err(e) => format!("Failed to update messages in '{}': {}", queue_name, e),
+        };
+        self.update_body_view();
+    }
+
+    pub fn request_export_delete_confirmation(&mut self) {
+        if self.action_refused("export and delete")
+            || self.request_typed_name(
+                InputPurpose::ConfirmExportDelete,
+                "export and delete its messages",
+            )
+        {
+            return;
+        }
+        if let Some(queue_name) = self.selected_queue().map(|q| q.name.clone()) {
+            self.awaiting_export_confirmation = true;
+            self.status_message = format!(
+                "Export and DELETE all messages from '{}'? Press Y to confirm, N to cancel",
+                queue_name
+            );
+        }
+    }
+
+    pub fn start_export(&mut self, delete_after_write: bool) {
+        self.awaiting_export_confirmation = false;
+
+        let Some(queue) = self.selected_queue() else {
+            return;
+        };
+        let queue_name = queue.name.clone();
+        let queue_url = queue.url.clone();
+        let path = timestamped_path(&self.export_dir, &queue_name);
+        let options = ExportOptions {
+            delete_after_write,
+            ..ExportOptions::default()
+        };
+
+        match Exporter::create(&path, &queue_url, options) {
+            Ok(exporter) => {
+                self.status_message =
+                    format!("Exporting '{}' to {}...", queue_name, path.display());
+                self.export = Some(ExportJob {
+                    queue_name,
+                    queue_url,
+                    exporter,
+                    purge_after: false,
+                });
+            }
+            Err(e) => {
+                self.status_message = format!("Failed to export queue '{}': {:#}", queue_name, e);
+            }
+        }
+    }
+
+    /// Runs one export batch and updates the progress message.
+    pub async fn step_export(&mut self) {
+        let Some(job) = self.export.as_mut() else {
+            return;
+        };
+
+        let result = job.exporter.step(self.backend.as_ref()).await;
+        let exporter = &job.exporter;
+        let deleted = if exporter.deleted > 0 {
+            format!(", deleted {}", exporter.deleted)
+        } else {
+            String::new()
+        };
+
+        match result {
+            Err(e) if job.purge_after => {
+                self.status_message = format!(
+                    "Backup of '{}' failed after {} messages, queue not purged: {}",
+                    job.queue_name, exporter.exported, e
+                );
+                self.export = None;
+            }
+            Err(e) => {
+                self.status_message = format!(
+                    "Export of '{}' failed after {} messages ({}): {}",
+                    job.queue_name,
+                    exporter.exported,
+                    exporter.path().display(),
+                    e
+                );
+                self.export = None;
+            }
+            Ok(()) if exporter.is_done() && job.purge_after => {
+                // The backup has to hold everything the purge would delete:
+                // visible, in-flight (what the backup itself is hiding) and
+                // delayed messages.
+                let total = match self.backend.get_queue_details(&job.queue_url).await {
+                    Ok(details) => approximate_total(&details),
+                    Err(_) => None,
+                };
+                let complete = !exporter.stopped_on_repeats()
+                    && total.is_some_and(|total| exporter.exported as i64 >= total);
+                if !complete {
+                    let total = total.map_or("an unknown number of".to_string(), |total| {
+                        total.to_string()
+                    });
+                    self.status_message = format!(
+                        "Backup of '{}' holds {} of {} messages, queue not purged ({})",
+                        job.queue_name,
+                        exporter.exported,
+                        total,
+                        exporter.path().display()
+                    );
+                    self.export = None;
+                    return;
+                }
+                let backup = format!(
+                    "backed up {} messages to {}",
+                    exporter.exported,
+                    exporter.path().display()
+                );
+                let (queue_url, queue_name) = (job.queue_url.clone(), job.queue_name.clone());
+                self.export = None;
+                self.purge_in_progress = true;
+                self.purge(&queue_url, &queue_name, Some(backup)).await;
+                self.purge_in_progress = false;
+            }
+            Ok(()) if exporter.is_done() => {
+                let incomplete = if exporter.stopped_on_repeats() {
+                    " (stopped early: only already-exported messages were coming back)"
+                } else {
+                    ""
+                };
+                self.status_message = format!(
+                    "Exported {} messages from '{}' to {}{}{}",
+                    exporter.exported,
+                    job.queue_name,
+                    exporter.path().display(),
+                    deleted,
+                    incomplete
+                );
+                self.export = None;
+            }
+            Ok(()) => {
+                let verb = if job.purge_after {
+                    "Backing up"
+                } else {
+                    "Exporting"
+                };
+                self.status_message = format!(
+                    "{} '{}': {} messages written{} (N to stop)",
+                    verb, job.queue_name, exporter.exported, deleted
+                );
+            }
+        }
+    }
+
+    pub fn cancel_export(&mut self) {
+        self.awaiting_export_confirmation = false;
+        self.status_message = match self.export.take() {
+            Some(job) if job.purge_after => format!(
+                "Backup of '{}' stopped after {} messages, queue not purged",
+                job.queue_name, job.exporter.exported
+            ),
+            Some(job) => format!(
+                "Export of '{}' stopped after {} messages ({})",
+                job.queue_name,
+                job.exporter.exported,
+                job.exporter.path().display()
+            ),
+            None => "Export cancelled".to_string(),
+        };
+    }
+
+    pub fn request_replay_path(&mut self) {
+        if self.action_refused("replay into") {
+            return;
+        }
+        if let Some(queue_name) = self.selected_queue().map(|q| q.name.clone()) {
+            self.input = Some(TextInput {
+                purpose: InputPurpose::ReplayArchive,
+                prompt: format!(
+                    "Replay archive into '{}' (Enter to start, Esc to cancel): ",
+                    queue_name
+                ),
+                value: String::new(),
+            });
+        }
+    }
+
+    pub fn start_replay(&mut self, archive: &str) {
+        let Some(queue) = self.selected_queue() else {
+            return;
+        };
+        let queue_name = queue.name.clone();
+        let queue_url = queue.url.clone();
+
+        match read_archive(std::path::Path::new(archive)) {
+            Ok(records) => {
+                self.status_message = format!(
+                    "Replaying {} messages into '{}'...",
+                    records.len(),
+                    queue_name
+                );
+                self.replay = Some(ReplayJob {
+                    queue_name,
+                    replayer: Replayer::new(&queue_url, records, ReplayOptions::default()),
+                    first_failure: None,
+                });
+            }
+            Err(e) => {
+                self.status_message = format!("Failed to replay into '{}': {:#}", queue_name, e);
+            }
+        }
+    }
+
+    /// Sends one replay batch and updates the progress message.
+    pub async fn step_replay(&mut self) {
+        let Some(job) = self.replay.as_mut() else {
+            return;
+        };
+
+        match job.replayer.step(self.backend.as_ref()).await {
+            Ok(outcomes) => {
+                if job.first_failure.is_none() {
+                    job.first_failure = outcomes.into_iter().find_map(|o| match o.status {
+                        ReplayStatus::Failed { reason } => {
+                            Some(format!("{}: {}", o.source_message_id, reason))
+                        }
+                        _ => None,
+                    });
+                }
+            }
+            Err(e) => {
+                self.status_message = format!("Replay into '{}' failed: {}", job.queue_name, e);
+                self.replay = None;
+                return;
+            }
+        }
+
+        let replayer = &job.replayer;
+        let failures = match &job.first_failure {
+            Some(first) => format!(", {} failed (first: {})", replayer.failed, first),
+            None => String::new(),
+        };
+        if replayer.is_done() {
+            self.status_message = format!(
+                "Replayed {} of {} messages into '{}'{}",
+                replayer.sent,
+                replayer.total(),
+                job.queue_name,
+                failures
+            );
+            self.replay = None;
+        } else {
+            self.status_message = format!(
+                "Replaying into '{}': {}/{} sent{} (N to stop)",
+                job.queue_name,
+                replayer.sent,
+                replayer.total(),
+                failures
+            );
+        }
+    }
+
+    /// Reports an audit entry that could not be written. The action itself
+    /// went through.
+    pub fn check_audit_log(&mut self) {
+        if let Some(e) = self.backend.take_audit_failure() {
+            self.status_message = format!("Audit log not written: {}", e);
+        }
+    }
+
+    pub fn cancel_replay(&mut self) {
+        if let Some(job) = self.replay.take() {
+            self.status_message = format!(
+                "Replay into '{}' stopped after {} of {} messages",
+                job.queue_name,
+                job.replayer.processed(),
+                job.replayer.total()
+            );
+        }
+    }
+
+    pub fn request_send_source(&mut self) {
+        if self.action_refused("send to") {
+            return;
+        }
+        if let Some(queue_name) = self.selected_queue().map(|q| q.name.clone()) {
+            self.input = Some(TextInput {
+                purpose: InputPurpose::SendSource,
+                prompt: format!(
+                    "Send to '{}' from a JSONL/CSV file, or a body template using {{n}}: ",
+                    queue_name
+                ),
+                value: String::new(),
+            });
+        }
+    }
+
+    /// Sends a file's messages once, or asks how many times to send a
+    /// template. Anything that looks like a path but is not a file is an
+    /// error, so a typo is not sent as a literal body.
+    fn choose_send_source(&mut self, value: String) {
+        let path = std::path::Path::new(&value);
+        if value.is_empty() {
+            self.status_message = "Nothing to send".to_string();
+        } else if path.is_file() || looks_like_path(&value) {
+            match MessageSource::read(path) {
+                Ok(source) => self.start_send(source, None),
+                Err(e) => self.status_message = format!("Cannot send {}: {:#}", value, e),
+            }
+        } else {
+            self.send_template = Some(value);
+            self.input = Some(TextInput {
+                purpose: InputPurpose::SendCount,
+                prompt: "How many messages? ".to_string(),
+                value: String::new(),
+            });
+        }
+    }
+
+    pub fn start_send(&mut self, source: MessageSource, count: Option<usize>) {
+        let Some(queue) = self.selected_queue() else {
+            return;
+        };
+        let queue_name = queue.name.clone();
+        let options = SendOptions {
+            count,
+            ..SendOptions::default()
+        };
+        let sender = Sender::new(&queue.url, source, options);
+        self.status_message = format!("Sending {} messages to '{}'...", sender.total(), queue_name);
+        self.send = Some(SendJob {
+            queue_name,
+            sender,
+            first_failure: None,
+        });
+    }
+
+    /// Sends one batch and updates the progress message.
+    pub async fn step_send(&mut self) {
+        let Some(job) = self.send.as_mut() else {
+            return;
+        };
+
+        let failures = job.sender.step(self.backend.as_ref()).await;
+        if job.first_failure.is_none()
+            && let Some(failure) = failures.first()
+        {
+            job.first_failure = Some(format!("{}: {}", failure.code, failure.message));
+        }
+
+        let sender = &job.sender;
+        let failures = match &job.first_failure {
+            Some(first) => format!(", {} failed (first: {})", sender.failed, first),
+            None => String::new(),
+        };
+        if sender.is_done() {
+            self.status_message = format!(
+                "Sent {} of {} messages to '{}' at {:.1}/s{}",
+                sender.sent,
+                sender.total(),
+                job.queue_name,
+                sender.throughput(),
+                failures
+            );
+            self.send = None;
+        } else {
+            self.status_message = format!(
+                "Sending to '{}': {}/{} sent, {:.1}/s{} (N to stop)",
+                job.queue_name,
+                sender.sent,
+                sender.total(),
+                sender.throughput(),
+                failures
+            );
+        }
+    }
+
+    pub fn cancel_send(&mut self) {
+        if let Some(job) = self.send.take() {
+            self.status_message = format!(
+                "Stopped sending to '{}' after {} of {} messages",
+                job.queue_name,
+                job.sender.sent,
+                job.sender.total()
+            );
+        }
+    }
+
+    /// Starts tailing the selected queue, releasing what arrives, or stops
+    /// a running tail.
+    pub fn toggle_tail(&mut self) {
+        if self.tail.is_some() {
+            self.stop_tail();
+        } else {
+            self.start_tail(TailMode::Release);
+        }
+    }
+
+    pub fn request_tail_delete_confirmation(&mut self) {
+        if self.action_refused("consume")
+            || self.request_typed_name(
+                InputPurpose::ConfirmTailDelete,
+                "consume and delete its messages",
+            )
+        {
+            return;
+        }
+        if let Some(queue_name) = self.selected_queue().map(|q| q.name.clone()) {
+            self.awaiting_tail_confirmation = true;
+            self.status_message = format!(
+                "Tail '{}' and DELETE every message that arrives? Press Y to confirm, N to cancel",
+                queue_name
+            );
+        }
+    }
+
+    pub fn start_tail(&mut self, mode: TailMode) {
+        self.awaiting_tail_confirmation = false;
+        let Some(queue) = self.selected_queue() else {
+            return;
+        };
+        let (name, url) = (queue.name.clone(), queue.url.clone());
+        self.tail = Some(TailView::start(
+            self.backend.clone(),
+            &name,
+            Tail::new(&url, mode),
+        ));
+        self.status_message = format!("Tailing '{}', {} messages as they arrive", name, mode);
+        if mode == TailMode::Release
+            && let Some(warning) = self
+                .selected_details
+                .as_ref()
+                .and_then(|details| redrive_warning(details, false))
+        {
+            self.status_message = format!("{}. Careful: {}", self.status_message, warning);
+        }
+    }
+
+    /// Takes in the messages the background poll has received.
+    pub fn step_tail(&mut self) {
+        if let Some(tail) = self.tail.as_mut() {
+            tail.drain();
+        }
+    }
+
+    /// Stops a running tail and waits for it to release what it holds, so
+    /// nothing stays hidden after the program exits.
+    pub async fn finish_tail(&mut self) {
+        if let Some(tail) = self.tail.take() {
+            tail.stop().await;
+        }
+    }
+
+    pub fn stop_tail(&mut self) {
+        self.awaiting_tail_confirmation = false;
+        self.status_message = match self.tail.take() {
+            Some(tail) => format!(
+                "Stopped tailing '{}' after {} messages",
+                tail.queue_name, tail.received
+            ),
+            None => "Tail cancelled".to_string(),
+        };
+    }
+}
+
+fn describe_report(action: &MessageAction, queue_name: &str, report: &ActionReport) -> String {
+    let mut text = match action {
+        MessageAction::Delete => {
+            format!("Deleted {} message(s) from '{}'", report.done, queue_name)
+        }
+        MessageAction::ChangeVisibility(0) => format!("Released {} message(s)", report.done),
+        MessageAction::ChangeVisibility(secs) => format!(
+            "Hid {} message(s) for {}",
+            report.done,
+            duration(i64::from(*secs))
+        ),
+        MessageAction::Move { target } => format!(
+            "Moved {} message(s) from '{}' to '{}'",
+            report.done, queue_name, target
+        ),
+    };
+    if report.expired > 0 {
+        text.push_str(&format!(
+            ", skipped {} with expired receipt handles (R to fetch again)",
+            report.expired
+        ));
+    }
+    if let Some(first) = report.failed.first() {
+        text.push_str(&format!(
+            ", {} failed (first: {})",
+            report.failed.len(),
+            describe_failure(first)
+        ));
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::memory::{FakeMessage, FakeQueue, InMemoryBackend, Operation};
+    use crate::retention::AgeSource;
+    use crate::types::QueueMetric;
+
+    fn app_with(queues: &[(&str, i64)]) -> (App, InMemoryBackend) {
+        let backend = queues
+            .iter()
+            .fold(InMemoryBackend::new(), |backend, (name, count)| {
+                backend.with_queue(FakeQueue::new(name, *count))
+            });
+        (App::new(Box::new(backend.clone())), backend)
+    }
+
+    fn names(app: &App) -> Vec<&str> {
+        app.queues.iter().map(|q| q.name.as_str()).collect()
+    }
+
+    #[tokio::test]
+    async fn refresh_sorts_queues_by_message_count() {
+        let (mut app, _) = app_with(&[("a", 1), ("b", 10), ("c", 0)]);
+        app.refresh_queues().await.unwrap();
+
+        assert_eq!(names(&app), ["b", "a", "c"]);
+        assert_eq!(app.status_message, "Connected to AWS | 3 queues found");
+        assert!(app.last_refresh.is_some());
+        assert_eq!(
+            app.selected_details.as_ref().and_then(|d| d.arn.as_deref()),
+            Some("arn:aws:sqs:us-east-1:000000000000:b")
+        );
+    }
+
+    #[tokio::test]
+    async fn refresh_failure_is_reported_in_status() {
+        let (mut app, backend) = app_with(&[("a", 1)]);
+        backend.fail_next(Operation::ListQueues, "boom");
+        app.refresh_queues().await.unwrap();
+
+        assert!(app.queues.is_empty());
+        assert_eq!(app.status_message, "Error: boom");
+    }
+
+    #[tokio::test]
+    async fn error_classes_get_their_own_message() {
+        let (mut app, backend) = app_with(&[("a", 1)]);
+        backend.fail_next(Operation::ListQueues, "ExpiredToken");
+        app.refresh_queues().await.unwrap();
+        assert_eq!(
+            app.status_message,
+            "Error: credentials expired — renew them and press R, or press P to switch profile (ExpiredToken)"
+        );
+
+        backend.fail_next(Operation::ListQueues, "AccessDenied");
+        app.refresh_queues().await.unwrap();
+        assert_eq!(
+            app.status_message,
+            "Error: access denied — the current identity lacks permission (AccessDenied)"
+        );
+    }
+
+    #[tokio::test]
+    async fn connection_errors_open_the_connection_screen_until_a_refresh_works() {
+        let (mut app, backend) = app_with(&[("a", 1)]);
+        backend.fail_next(Operation::ListQueues, "QueueDoesNotExist");
+        app.refresh_queues().await.unwrap();
+        assert_eq!(app.connection_error, None);
+
+        backend.fail_next(Operation::ListQueues, "InvalidClientTokenId");
+        app.refresh_queues().await.unwrap();
+        assert!(matches!(
+            app.connection_error,
+            Some(BackendError::InvalidCredentials(_))
+        ));
+
+        app.refresh_queues().await.unwrap();
+        assert_eq!(app.connection_error, None);
+        assert_eq!(app.queues.len(), 1);
+    }
+
+    #[test]
+    fn profile_and_region_prompts_request_a_reconnect() {
+        let (mut app, _backend) = app_with(&[]);
+        app.request_profile();
+        for c in "dev".chars() {
+            app.input_char(c);
+        }
+        app.submit_input();
+        assert_eq!(app.connection.profile.as_deref(), Some("dev"));
+        assert!(app.reconnect_requested);
+        assert_eq!(app.status_message, "Connecting with profile 'dev'...");
+
+        app.reconnect_requested = false;
+        app.request_region();
+        app.submit_input();
+        assert_eq!(app.connection.region, None);
+        assert!(app.reconnect_requested);
+    }
+
+    #[tokio::test]
+    async fn filter_hides_empty_queues_and_resets_selection() {
+        let (mut app, _) = app_with(&[("a", 5), ("b", 0), ("c", 0)]);
+        app.refresh_queues().await.unwrap();
+        app.selected_index = 2;
+
+        app.toggle_filter();
+        assert_eq!(names(&app), ["a"]);
+        assert_eq!(app.selected_index, 0);
+        assert_eq!(
+            app.status_message,
+            "Filter: ON | 1 of 3 queues (non-empty only)"
+        );
+
+        app.toggle_filter();
+        assert_eq!(names(&app).len(), 3);
+        assert_eq!(app.status_message, "Filter: OFF | 3 queues shown");
+    }
+
+    #[tokio::test]
+    async fn navigation_wraps_around() {
+        let (mut app, _) = app_with(&[("a", 3), ("b", 2), ("c", 1)]);
+        app.refresh_queues().await.unwrap();
+
+        app.previous_queue();
+        assert_eq!(app.selected_queue().unwrap().name, "c");
+        app.next_queue();
+        assert_eq!(app.selected_queue().unwrap().name, "a");
+        app.next_queue();
+        assert_eq!(app.selected_queue().unwrap().name, "b");
+    }
+
+    #[test]
+    fn navigation_on_empty_list_is_a_no_op() {
+        let (mut app, _) = app_with(&[]);
+        app.next_queue();
+        app.previous_queue();
+        assert_eq!(app.selected_index, 0);
+        assert!(app.selected_queue().is_none());
+    }
+
+    #[tokio::test]
+    async fn purge_flow_purges_selected_queue() {
+        let (mut app, backend) = app_with(&[("a", 3), ("b", 2)]);
+        app.refresh_queues().await.unwrap();
+
+        app.request_purge_confirmation();
+        assert!(app.awaiting_purge_confirmation);
+        assert_eq!(
+            app.status_message,
+            "Purge queue 'a'? Press Y to confirm, B to back up first, N to cancel"
+        );
+
+        let (url, name) = app.begin_purge().unwrap();
+        assert!(!app.awaiting_purge_confirmation);
+        assert!(app.purge_in_progress);
+
+        app.execute_purge(&url, &name).await.unwrap();
+        assert!(!app.purge_in_progress);
+        assert!(app.is_busy());
+        assert_eq!(backend.calls(Operation::PurgeQueue), 1);
+
+        app.step_purge_watch().await;
+        assert!(!app.is_busy());
+        assert_eq!(names(&app), ["b", "a"]);
+        assert_eq!(app.status_message, "Queue 'a' purged in 0s");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn purge_cooldown_blocks_retries() {
+        let (mut app, backend) = app_with(&[("a", 3)]);
+        app.refresh_queues().await.unwrap();
+        let (url, name) = app.begin_purge().unwrap();
+        app.execute_purge(&url, &name).await.unwrap();
+        app.step_purge_watch().await;
+
+        tokio::time::advance(Duration::from_secs(15)).await;
+        assert_eq!(app.purge_cooldown(&url), Some(Duration::from_secs(45)));
+        app.request_purge_confirmation();
+        assert!(!app.awaiting_purge_confirmation);
+        assert_eq!(
+            app.status_message,
+            "Queue 'a' was purged recently; SQS allows one purge every 60 seconds. Try again in 45s"
+        );
+
+        tokio::time::advance(Duration::from_secs(45)).await;
+        assert_eq!(app.purge_cooldown(&url), None);
+        app.request_purge_confirmation();
+        assert!(app.awaiting_purge_confirmation);
+        assert_eq!(backend.calls(Operation::PurgeQueue), 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn purge_in_progress_elsewhere_starts_the_countdown() {
+        let (mut app, backend) = app_with(&[("a", 3)]);
+        app.refresh_queues().await.unwrap();
+        let url = app.queues[0].url.clone();
+        // Another client purged the queue a moment ago.
+        backend.purge_queue(&url).await.unwrap();
+
+        app.execute_purge(&url, "a").await.unwrap();
+        assert!(app.purge_watch.is_none());
+        assert_eq!(
+            app.status_message,
+            "Queue 'a' is already being purged; SQS allows one purge every 60 seconds. Try again in 1m"
+        );
+        assert_eq!(app.purge_cooldown(&url), Some(Duration::from_secs(60)));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn purge_watch_polls_until_the_queue_is_empty() {
+        let (mut app, backend) = app_with(&[("a", 3)]);
+        app.refresh_queues().await.unwrap();
+        let url = app.queues[0].url.clone();
+        app.execute_purge(&url, "a").await.unwrap();
+        // Messages that arrive right after the purge keep the count up.
+        let outgoing = OutgoingMessage {
+            body: "late".to_string(),
+            ..OutgoingMessage::default()
+        };
+        backend.send_messages(&url, &[outgoing]).await.unwrap();
+
+        app.step_purge_watch().await;
+        assert_eq!(
+            app.status_message,
+            "Purging 'a': 1 visible after 0s (N to stop waiting)"
+        );
+        // Nothing is checked again before the poll interval.
+        let checks = backend.calls(Operation::GetQueueDetails);
+        app.step_purge_watch().await;
+        assert_eq!(backend.calls(Operation::GetQueueDetails), checks);
+
+        tokio::time::advance(Duration::from_secs(60)).await;
+        app.step_purge_watch().await;
+        assert!(app.purge_watch.is_none());
+        assert_eq!(
+            app.status_message,
+            "Queue 'a' purged, but still reports 1 visible messages after 1m"
+        );
+    }
+
+    #[tokio::test]
+    async fn purge_failure_is_reported_and_clears_progress() {
+        let (mut app, backend) = app_with(&[("a", 3)]);
+        app.refresh_queues().await.unwrap();
+        backend.fail_next(Operation::PurgeQueue, "access denied");
+
+        app.request_purge_confirmation();
+        let (url, name) = app.begin_purge().unwrap();
+        app.execute_purge(&url, &name).await.unwrap();
+
+        assert!(!app.purge_in_progress);
+        assert_eq!(
+            app.status_message,
+            "Failed to purge queue 'a': access denied"
+        );
+        assert_eq!(app.queues[0].approximate_messages, 3);
+    }
+
+    #[tokio::test]
+    async fn cancelled_purge_does_not_call_backend() {
+        let (mut app, backend) = app_with(&[("a", 3)]);
+        app.refresh_queues().await.unwrap();
+
+        app.request_purge_confirmation();
+        app.cancel_purge();
+
+        assert!(!app.awaiting_purge_confirmation);
+        assert_eq!(app.status_message, "Purge cancelled");
+        assert_eq!(backend.calls(Operation::PurgeQueue), 0);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn protected_queues_back_up_before_purge() {
+        let (mut app, backend) = app_with(&[("prod-orders", 23)]);
+        let dir = tempfile::tempdir().unwrap();
+        app.config = toml::from_str(&format!(
+            "backup_dir = {:?}\n[[protected_queues]]\npattern = \"prod-*\"\nprotection = \"confirm\"\nbackup_before_purge = true",
+            dir.path()
+        ))
+        .unwrap();
+        app.refresh_queues().await.unwrap();
+
+        app.request_purge_confirmation();
+        assert!(app.purge_requires_backup());
+        assert!(app.status_message.contains("Y to back up then purge"));
+
+        app.begin_backup_then_purge();
+        let mut steps = 0;
+        while app.export.is_some() {
+            assert_eq!(backend.calls(Operation::PurgeQueue), 0);
+            app.step_export().await;
+            steps += 1;
+        }
+
+        assert_eq!(steps, 5);
+        assert_eq!(backend.calls(Operation::PurgeQueue), 1);
+        app.step_purge_watch().await;
+        assert_eq!(app.queues[0].approximate_messages, 0);
+        let backups: Vec<_> = std::fs::read_dir(dir.path()).unwrap().collect();
+        assert_eq!(backups.len(), 1);
+        let backup = std::fs::read_to_string(backups[0].as_ref().unwrap().path()).unwrap();
+        assert_eq!(backup.lines().count(), 23);
+        assert!(app.status_message.contains("backed up 23 messages"));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn incomplete_backup_skips_purge() {
+        let mut orders = FakeQueue::new("orders", 6);
+        // Delayed messages cannot be received, so the backup misses them
+        orders.delayed = 4;
+        let backend = InMemoryBackend::new().with_queue(orders);
+        let mut app = App::new(Box::new(backend.clone()));
+        let dir = tempfile::tempdir().unwrap();
+        app.config.backup_dir = Some(dir.path().to_path_buf());
+        app.refresh_queues().await.unwrap();
+
+        app.request_purge_confirmation();
+        app.begin_backup_then_purge();
+        while app.export.is_some() {
+            app.step_export().await;
+        }
+
+        assert_eq!(backend.calls(Operation::PurgeQueue), 0);
+        assert!(
+            app.status_message
+                .starts_with("Backup of 'orders' holds 6 of 10 messages, queue not purged (")
+        );
+    }
+
+    #[tokio::test]
+    async fn failed_backup_skips_purge() {
+        let (mut app, backend) = app_with(&[("orders", 5)]);
+        let dir = tempfile::tempdir().unwrap();
+        app.config.backup_dir = Some(dir.path().to_path_buf());
+        app.refresh_queues().await.unwrap();
+        backend.fail_next(Operation::ReceiveMessages, "access denied");
+
+        app.request_purge_confirmation();
+        assert!(!app.purge_requires_backup());
+        app.begin_backup_then_purge();
+        app.step_export().await;
+
+        assert!(app.export.is_none());
+        assert_eq!(backend.calls(Operation::PurgeQueue), 0);
+        assert_eq!(
+            app.status_message,
+            "Backup of 'orders' failed after 0 messages, queue not purged: access denied"
+        );
+    }
+
+    #[tokio::test]
+    async fn read_only_mode_refuses_mutating_actions() {
+        let (mut app, backend) = app_with(&[("orders", 5)]);
+        app.read_only = true;
+        app.refresh_queues().await.unwrap();
+
+        app.request_purge_confirmation();
+        assert!(!app.is_prompting());
+        assert_eq!(app.status_message, "Read-only mode: cannot purge 'orders'");
+        app.request_export_delete_confirmation();
+        assert!(!app.is_prompting());
+        app.request_tail_delete_confirmation();
+        assert!(!app.is_prompting());
+        app.request_send_source();
+        assert!(app.input.is_none());
+        app.request_compose();
+        assert!(app.edit_request.is_none());
+        app.request_replay_path();
+        assert!(app.input.is_none());
+        assert_eq!(
+            app.status_message,
+            "Read-only mode: cannot replay into 'orders'"
+        );
+        assert_eq!(backend.calls(Operation::PurgeQueue), 0);
+    }
+
+    #[tokio::test]
+    async fn blocked_queues_report_the_reason() {
+        let (mut app, _) = app_with(&[("billing-audit", 5)]);
+        app.config = toml::from_str(
+            "[[protected_queues]]\npattern = \"*-audit\"\nprotection = \"block\"\nreason = \"compliance\"",
+        )
+        .unwrap();
+        app.refresh_queues().await.unwrap();
+
+        app.request_purge_confirmation();
+        assert!(!app.is_prompting());
+        assert_eq!(
+            app.status_message,
+            "Cannot purge 'billing-audit': compliance"
+        );
+    }
+
+    #[tokio::test]
+    async fn protected_queues_need_the_typed_name() {
+        let (mut app, backend) = app_with(&[("prod-orders", 5)]);
+        app.config = toml::from_str("[[protected_queues]]\npattern = \"prod-*\"").unwrap();
+        app.refresh_queues().await.unwrap();
+
+        app.request_purge_confirmation();
+        assert!(!app.awaiting_purge_confirmation);
+        assert_eq!(
+            app.input.as_ref().unwrap().purpose,
+            InputPurpose::ConfirmPurge
+        );
+        "prod".chars().for_each(|c| app.input_char(c));
+        app.submit_input();
+        assert!(!app.purge_confirmed);
+        assert_eq!(
+            app.status_message,
+            "Queue name did not match, nothing changed"
+        );
+
+        app.request_purge_confirmation();
+        "prod-orders".chars().for_each(|c| app.input_char(c));
+        app.submit_input();
+        assert!(app.purge_confirmed);
+        let (url, name) = app.begin_purge().unwrap();
+        app.execute_purge(&url, &name).await.unwrap();
+        assert_eq!(backend.calls(Operation::PurgeQueue), 1);
+    }
+
+    #[test]
+    fn purge_confirmation_requires_a_selection() {
+        let (mut app, _) = app_with(&[]);
+        app.request_purge_confirmation();
+        assert!(!app.awaiting_purge_confirmation);
+        assert!(app.begin_purge().is_none());
+    }
+
+    #[tokio::test]
+    async fn peeked_messages_are_deleted_after_confirmation() {
+        let (mut app, backend) = app_with(&[("orders", 3)]);
+        app.refresh_queues().await.unwrap();
+        app.toggle_peek().await;
+        assert_eq!(
+            app.status_message,
+            "Holding 3 messages from 'orders' for 30s"
+        );
+
+        app.toggle_message_selected();
+        app.toggle_message_selected();
+        app.request_message_delete();
+        assert_eq!(
+            app.status_message,
+            "Delete 2 message(s) from 'orders'? Press Y to confirm, N to cancel"
+        );
+        assert!(app.is_prompting());
+        app.confirm_message_action();
+        app.run_message_action().await;
+
+        assert_eq!(app.status_message, "Deleted 2 message(s) from 'orders'");
+        assert_eq!(app.peek.as_ref().unwrap().messages.len(), 1);
+        assert_eq!(backend.calls(Operation::DeleteMessages), 1);
+    }
+
+    #[tokio::test]
+    async fn closing_leaves_the_body_before_the_message_view() {
+        let (mut app, _backend) = app_with(&[("orders", 3)]);
+        app.refresh_queues().await.unwrap();
+        app.toggle_peek().await;
+        app.toggle_body_view();
+        assert!(app.body_view.is_some());
+
+        app.close_view();
+        assert!(app.body_view.is_none());
+        assert!(app.peek.is_some());
+
+        app.close_view();
+        assert!(app.peek.is_none());
+        assert_eq!(app.status_message, "Closed message view");
+        assert!(!app.should_quit);
+    }
+
+    #[tokio::test]
+    async fn message_actions_respect_read_only_and_move_targets() {
+        let (mut app, backend) = app_with(&[("orders", 1), ("orders-dlq", 0)]);
+        app.refresh_queues().await.unwrap();
+        app.toggle_peek().await;
+
+        app.request_message_move();
+        for c in "orders".chars() {
+            app.input_char(c);
+        }
+        app.submit_input();
+        assert_eq!(
+            app.status_message,
+            "Pick a different queue to move to, nothing changed"
+        );
+        assert!(app.message_action.is_none());
+
+        app.read_only = true;
+        app.request_message_delete();
+        assert_eq!(
+            app.status_message,
+            "Read-only mode: cannot delete messages from 'orders'"
+        );
+        assert!(app.pending_message_action.is_none());
+
+        // Hiding messages keeps them from consumers for up to 12 hours
+        app.request_message_visibility();
+        assert_eq!(
+            app.status_message,
+            "Read-only mode: cannot change visibility of messages in 'orders'"
+        );
+        assert!(app.input.is_none());
+        assert_eq!(backend.calls(Operation::ChangeMessageVisibility), 0);
+    }
+
+    #[tokio::test]
+    async fn filter_selects_messages_for_batch_actions() {
+        let (mut app, backend) = app_with(&[("orders", 30)]);
+        app.refresh_queues().await.unwrap();
+        app.toggle_peek().await;
+        assert_eq!(app.peek.as_ref().unwrap().messages.len(), 30);
+
+        app.request_message_filter();
+        for c in ".seq < 4 or".chars() {
+            app.input_char(c);
+        }
+        app.submit_input();
+        assert!(app.status_message.starts_with("Invalid filter: "));
+
+        app.request_message_filter();
+        for c in ".seq < 4".chars() {
+            app.input_char(c);
+        }
+        app.submit_input();
+        assert_eq!(app.status_message, "4 of 30 messages match .seq < 4");
+
+        app.toggle_all_messages_selected();
+        app.request_message_delete();
+        app.confirm_message_action();
+        app.run_message_action().await;
+        assert_eq!(app.status_message, "Deleted 4 message(s) from 'orders'");
+        let peek = app.peek.as_ref().unwrap();
+        assert_eq!((peek.shown.len(), peek.messages.len()), (0, 26));
+        assert_eq!(backend.calls(Operation::DeleteMessages), 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn releasing_tail_warns_about_redrive_and_leaves_messages_visible() {
+        let mut orders = FakeQueue::new("orders", 3);
+        orders.details.attributes.insert(
+            "RedrivePolicy".to_string(),
+            r#"{"deadLetterTargetArn":"arn:aws:sqs:us-east-1:000000000000:orders-dlq","maxReceiveCount":5}"#
+                .to_string(),
+        );
+        let backend = InMemoryBackend::new().with_queue(orders);
+        let mut app = App::new(Box::new(backend.clone()));
+        app.refresh_queues().await.unwrap();
+
+        app.toggle_tail();
+        assert!(
+            app.status_message
+                .contains("after 5 receives, and tailing receives each one again every 5s")
+        );
+        // Long enough for the messages to come round again
+        tokio::time::sleep(Duration::from_secs(30)).await;
+        app.step_tail();
+        assert_eq!(app.tail.as_ref().unwrap().received, 3);
+
+        app.finish_tail().await;
+        let info = &backend.list_queues().await.unwrap()[0];
+        assert_eq!(info.approximate_messages, 3);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn tail_consumes_only_after_confirmation() {
+        let (mut app, backend) = app_with(&[("orders", 3)]);
+        app.refresh_queues().await.unwrap();
+
+        app.toggle_tail();
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        app.step_tail();
+        assert_eq!(app.tail.as_ref().unwrap().received, 3);
+        app.toggle_tail();
+        assert_eq!(
+            app.status_message,
+            "Stopped tailing 'orders' after 3 messages"
+        );
+        assert_eq!(backend.calls(Operation::DeleteMessages), 0);
+
+        app.request_tail_delete_confirmation();
+        assert!(app.awaiting_tail_confirmation);
+        app.start_tail(TailMode::Delete);
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        app.step_tail();
+        assert_eq!(app.tail.as_ref().unwrap().received, 3);
+        app.stop_tail();
+        app.refresh_queues().await.unwrap();
+        assert_eq!(app.queues[0].approximate_messages, 0);
+    }
+
+    #[tokio::test]
+    async fn sends_a_template_after_asking_for_a_count() {
+        let (mut app, _backend) = app_with(&[("orders", 0)]);
+        app.refresh_queues().await.unwrap();
+
+        app.request_send_source();
+        for c in r#"{"seq":{n}}"#.chars() {
+            app.input_char(c);
+        }
+        app.submit_input();
+        assert_eq!(app.input.as_ref().unwrap().purpose, InputPurpose::SendCount);
+        for c in "25".chars() {
+            app.input_char(c);
+        }
+        app.submit_input();
+        assert!(app.is_busy());
+
+        while app.send.is_some() {
+            app.step_send().await;
+        }
+        assert!(
+            app.status_message
+                .starts_with("Sent 25 of 25 messages to 'orders' at ")
+        );
+        app.refresh_queues().await.unwrap();
+        assert_eq!(app.queues[0].approximate_messages, 25);
+
+        app.request_send_source();
+        for c in "ordrs.jsonl".chars() {
+            app.input_char(c);
+        }
+        app.submit_input();
+        assert!(app.input.is_none() && !app.is_busy());
+        assert!(app.status_message.starts_with("Cannot send ordrs.jsonl: "));
+    }
+
+    #[tokio::test]
+    async fn composes_a_message_in_the_editor() {
+        let (mut app, _backend) = app_with(&[("orders", 0)]);
+        app.refresh_queues().await.unwrap();
+
+        app.request_compose();
+        let request = app.edit_request.take().unwrap();
+        app.send_edited(request, Ok(None)).await;
+        assert_eq!(
+            app.status_message,
+            "Editor closed without a saved body, nothing sent"
+        );
+
+        app.request_compose();
+        let request = app.edit_request.take().unwrap();
+        app.send_edited(request, Ok(Some("{}".to_string()))).await;
+        assert!(app.status_message.starts_with("Sent message "));
+        assert!(app.status_message.ends_with(" to 'orders'"));
+        app.refresh_queues().await.unwrap();
+        assert_eq!(app.queues[0].approximate_messages, 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn resending_from_a_dlq_replaces_the_original() {
+        let (mut app, backend) = app_with(&[("orders-dlq", 2), ("orders", 0)]);
+        app.refresh_queues().await.unwrap();
+        app.toggle_peek().await;
+
+        app.request_message_resend();
+        let input = app.input.as_ref().unwrap();
+        assert_eq!(input.purpose, InputPurpose::ResendTarget);
+        assert_eq!(input.value, "orders");
+        app.submit_input();
+
+        let request = app.edit_request.take().unwrap();
+        let original = request.replaces.clone().unwrap();
+        assert_eq!(
+            Some(request.message.body.as_str()),
+            app.peek
+                .as_ref()
+                .unwrap()
+                .current()
+                .map(|m| m.message.body.as_str())
+        );
+        app.hold_for_edit(&request).await;
+        assert_eq!(backend.calls(Operation::ChangeMessageVisibility), 1);
+        // Editing takes longer than the peek's own visibility timeout
+        tokio::time::advance(Duration::from_secs(120)).await;
+        app.send_edited(request, Ok(Some(r#"{"fixed":true}"#.to_string())))
+            .await;
+        assert!(
+            app.status_message
+                .ends_with(" to 'orders' and deleted the original from 'orders-dlq'")
+        );
+        let peek = app.peek.as_ref().unwrap();
+        assert_eq!(peek.messages.len(), 1);
+        assert_ne!(peek.messages[0].message.message_id, original);
+        assert_eq!(backend.calls(Operation::DeleteMessages), 1);
+
+        app.request_message_resend();
+        app.submit_input();
+        let request = app.edit_request.take().unwrap();
+        backend.fail_next(Operation::DeleteMessages, "ReceiptHandleIsInvalid");
+        app.send_edited(request, Ok(Some("{}".to_string()))).await;
+        assert!(
+            app.status_message.contains(
+                "; the original was not deleted, so 'orders-dlq' now holds a duplicate: "
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn cancelled_resend_releases_the_original() {
+        let (mut app, backend) = app_with(&[("orders-dlq", 2), ("orders", 0)]);
+        app.refresh_queues().await.unwrap();
+        app.toggle_peek().await;
+
+        app.request_message_resend();
+        app.submit_input();
+        let request = app.edit_request.take().unwrap();
+        app.hold_for_edit(&request).await;
+        app.send_edited(request, Ok(None)).await;
+        assert_eq!(
+            app.status_message,
+            "Editor closed without a saved body, nothing sent"
+        );
+        assert_eq!(backend.calls(Operation::ChangeMessageVisibility), 2);
+        // Back in the queue at once; the other peeked message is still hidden
+        let queues = backend.list_queues().await.unwrap();
+        let dlq = queues.iter().find(|q| q.name == "orders-dlq").unwrap();
+        assert_eq!(
+            (
+                dlq.approximate_messages,
+                dlq.approximate_messages_not_visible
+            ),
+            (1, 1)
+        );
+        assert_eq!(backend.calls(Operation::SendMessages), 0);
+    }
+
+    #[tokio::test]
+    async fn oldest_message_age_falls_back_to_peeked_messages() {
+        let mut dlq = FakeQueue::new("orders-dlq", 0);
+        let mut old = FakeMessage::new("m-1", "{}");
+        old.sent_timestamp = (Utc::now() - chrono::Duration::hours(90)).timestamp_millis();
+        dlq = dlq.with_message(old);
+        let backend = InMemoryBackend::new().with_queue(dlq);
+        let mut app = App::new(Box::new(backend.clone()));
+
+        app.refresh_queues().await.unwrap();
+        let age = app.oldest_message_age(Utc::now()).unwrap();
+        assert_eq!(age.source, AgeSource::CloudWatch);
+        assert!(age.near_expiry(345_600, Utc::now()));
+        assert_eq!(backend.calls(Operation::QueueMetric), 1);
+        // Reused until CloudWatch has a newer datapoint
+        app.refresh_queues().await.unwrap();
+        assert_eq!(backend.calls(Operation::QueueMetric), 1);
+
+        let mut app = App::new(Box::new(backend.clone()));
+        backend.fail_next(Operation::QueueMetric, "AccessDenied");
+        app.refresh_queues().await.unwrap();
+        assert!(app.cloudwatch_error.is_some());
+        assert!(app.oldest_message_age(Utc::now()).is_none());
+
+        app.toggle_peek().await;
+        let age = app.oldest_message_age(Utc::now()).unwrap();
+        assert_eq!(age.source, AgeSource::Sampled);
+        assert!(age.secs >= 90 * 3_600);
+    }
+
+    #[tokio::test]
+    async fn metrics_window_cycles_and_reports_cloudwatch_errors() {
+        let (mut app, backend) = app_with(&[("orders", 5)]);
+        app.refresh_queues().await.unwrap();
+        let calls = backend.calls(Operation::QueueMetric);
+
+        backend.fail_next(Operation::QueueMetric, "AccessDenied");
+        app.cycle_metrics_window().await;
+        assert_eq!(app.metrics_window, Some(MetricWindow::Hour));
+        assert!(app.metrics.is_none());
+        assert!(
+            app.status_message
+                .starts_with("Cannot read CloudWatch metrics for 'orders': ")
+        );
+
+        for window in [
+            MetricWindow::SixHours,
+            MetricWindow::Day,
+            MetricWindow::Week,
+        ] {
+            app.cycle_metrics_window().await;
+            assert_eq!(app.metrics.as_ref().unwrap().0.window, window);
+        }
+        app.cycle_metrics_window().await;
+        assert!(app.metrics_window.is_none() && app.metrics.is_none());
+        let calls = backend.calls(Operation::QueueMetric) - calls;
+        // One failed call, then every metric for three windows
+        assert_eq!(calls, 1 + 3 * QueueMetric::ALL.len());
+    }
+
+    async fn finish_lint(app: &mut App) {
+        while app.is_linting() {
+            tokio::task::yield_now().await;
+            app.step_lint();
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn lints_every_queue_at_most_every_few_minutes() {
+        let (mut app, backend) = app_with(&[("orders", 5), ("orders-dlq", 0)]);
+        app.refresh_queues().await.unwrap();
+        // The lint runs in the background while the queue list is shown
+        assert!(app.is_linting());
+        assert!(app.findings_for("orders").is_empty());
+        finish_lint(&mut app).await;
+        assert_eq!(app.findings_for("orders").len(), 1);
+
+        // A queue that cannot be read is skipped, the others still linted
+        tokio::time::advance(LINT_REFRESH).await;
+        backend.fail_next(Operation::GetQueueDetails, "AccessDenied");
+        app.start_lint();
+        finish_lint(&mut app).await;
+        assert!(app.lint_errors.contains_key("orders"));
+        assert!(app.findings_for("orders").is_empty());
+        assert!(app.findings_for("orders-dlq").is_empty());
+        assert!(app.lint_findings.contains_key("orders-dlq"));
+
+        // Until LINT_REFRESH has passed only the selected queue is read
+        let calls = backend.calls(Operation::GetQueueDetails);
+        app.refresh_queues().await.unwrap();
+        finish_lint(&mut app).await;
+        assert_eq!(backend.calls(Operation::GetQueueDetails), calls + 1);
+    }
 }
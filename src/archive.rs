@@ -0,0 +1,411 @@
+use crate::backend::QueueBackend;
+use crate::filter::MessageFilter;
+use crate::types::{BatchFailure, DeleteEntry, MessageAttribute, QueueMessage, ReceiveOptions};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// One line of a JSONL message archive.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedMessage {
+    pub message_id: String,
+    pub queue_url: String,
+    pub exported_at: DateTime<Utc>,
+    pub body: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub md5_of_body: Option<String>,
+    /// System attributes (SentTimestamp, ApproximateReceiveCount,
+    /// MessageGroupId, AWSTraceHeader, ...).
+    #[serde(default)]
+    pub attributes: BTreeMap<String, String>,
+    #[serde(default)]
+    pub message_attributes: BTreeMap<String, MessageAttribute>,
+}
+
+impl ArchivedMessage {
+    pub fn from_message(queue_url: &str, message: &QueueMessage) -> Self {
+        Self {
+            message_id: message.message_id.clone(),
+            queue_url: queue_url.to_string(),
+            exported_at: Utc::now(),
+            body: message.body.clone(),
+            md5_of_body: message.md5_of_body.clone(),
+            attributes: message.attributes.clone(),
+            message_attributes: message.message_attributes.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ExportOptions {
+    /// How long received messages stay hidden while the export runs. Messages
+    /// that are not deleted become visible again once it expires.
+    pub visibility_timeout: i32,
+    pub delete_after_write: bool,
+    pub max_messages: Option<usize>,
+    /// Only matching messages are written (and deleted); the rest stay
+    /// hidden until the visibility timeout runs out, so the export does not
+    /// receive them again.
+    pub filter: Option<MessageFilter>,
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        Self {
+            visibility_timeout: 300,
+            delete_after_write: false,
+            max_messages: None,
+            filter: None,
+        }
+    }
+}
+
+/// Number of consecutive empty long-poll receives after which a queue is
+/// considered drained.
+const EMPTY_RECEIVES_BEFORE_DONE: u32 = 2;
+/// Number of consecutive batches holding only already-written messages
+/// after which the export gives up. They come back once the visibility
+/// timeout runs out, so the queue is not drained, only cycling.
+const REPEATED_BATCHES_BEFORE_DONE: u32 = 10;
+
+/// Drains a queue into a JSONL file one receive batch at a time, so callers
+/// can report progress between batches.
+pub struct Exporter {
+    queue_url: String,
+    path: PathBuf,
+    writer: BufWriter<File>,
+    options: ExportOptions,
+    seen: HashSet<String>,
+    empty_receives: u32,
+    repeated_batches: u32,
+    pub exported: usize,
+    /// Received but left alone because they did not match the filter.
+    pub skipped: usize,
+    pub deleted: usize,
+    pub delete_failures: Vec<BatchFailure>,
+}
+
+impl Exporter {
+    pub fn create(path: &Path, queue_url: &str, options: ExportOptions) -> Result<Self> {
+        let file = File::create_new(path)
+            .with_context(|| format!("Cannot create archive {}", path.display()))?;
+        Ok(Self {
+            queue_url: queue_url.to_string(),
+            path: path.to_path_buf(),
+            writer: BufWriter::new(file),
+            options,
+            seen: HashSet::new(),
+            empty_receives: 0,
+            repeated_batches: 0,
+            exported: 0,
+            skipped: 0,
+            deleted: 0,
+            delete_failures: Vec::new(),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.empty_receives >= EMPTY_RECEIVES_BEFORE_DONE
+            || self.stopped_on_repeats()
+            || self
+                .options
+                .max_messages
+                .is_some_and(|max| self.exported >= max)
+    }
+
+    /// Whether the export stopped because only messages it had already
+    /// written kept arriving, leaving others possibly unread.
+    pub fn stopped_on_repeats(&self) -> bool {
+        self.repeated_batches >= REPEATED_BATCHES_BEFORE_DONE
+    }
+
+    /// Receives, writes and optionally deletes one batch. Each batch is
+    /// flushed to disk before any of its messages are deleted.
+    pub async fn step(&mut self, backend: &dyn QueueBackend) -> Result<()> {
+        if self.is_done() {
+            return Ok(());
+        }
+
+        let remaining = self
+            .options
+            .max_messages
+            .map_or(10, |max| max.saturating_sub(self.exported).min(10));
+        let messages = backend
+            .receive_messages(
+                &self.queue_url,
+                ReceiveOptions {
+                    max_messages: remaining as i32,
+                    visibility_timeout: Some(self.options.visibility_timeout),
+                    wait_time_seconds: 1,
+                },
+            )
+            .await?;
+
+        if messages.is_empty() {
+            self.empty_receives += 1;
+            return Ok(());
+        }
+        self.empty_receives = 0;
+
+        // Messages can come back a second time if the export outlives the
+        // visibility timeout; only the first copy is written. A batch of
+        // repeats says nothing about whether the queue is drained.
+        let fresh: Vec<&QueueMessage> = messages
+            .iter()
+            .filter(|m| self.seen.insert(m.message_id.clone()))
+            .collect();
+        if fresh.is_empty() {
+            self.repeated_batches += 1;
+            return Ok(());
+        }
+        self.repeated_batches = 0;
+
+        let now = Utc::now();
+        let (fresh, skipped): (Vec<&QueueMessage>, Vec<&QueueMessage>) =
+            fresh.into_iter().partition(|m| {
+                self.options
+                    .filter
+                    .as_ref()
+                    .is_none_or(|filter| filter.matches(m, now))
+            });
+        self.skipped += skipped.len();
+        if fresh.is_empty() {
+            return Ok(());
+        }
+
+        for message in &fresh {
+            let record = ArchivedMessage::from_message(&self.queue_url, message);
+            serde_json::to_writer(&mut self.writer, &record)?;
+            self.writer.write_all(b"\n")?;
+        }
+        self.writer.flush()?;
+        self.writer.get_ref().sync_data()?;
+        self.exported += fresh.len();
+
+        if self.options.delete_after_write {
+            let entries: Vec<DeleteEntry> = fresh
+                .iter()
+                .enumerate()
+                .map(|(i, m)| DeleteEntry {
+                    id: i.to_string(),
+                    receipt_handle: m.receipt_handle.clone(),
+                })
+                .collect();
+            let failures = backend.delete_messages(&self.queue_url, &entries).await?;
+            self.deleted += entries.len() - failures.len();
+            self.delete_failures.extend(failures);
+        }
+
+        Ok(())
+    }
+}
+
+/// Reads every record from a JSONL archive, skipping blank lines.
+pub fn read_archive(path: &Path) -> Result<Vec<ArchivedMessage>> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Cannot read archive {}", path.display()))?;
+    let mut records = Vec::new();
+    for (index, line) in content.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let record = serde_json::from_str(line)
+            .with_context(|| format!("{}:{}: invalid archive record", path.display(), index + 1))?;
+        records.push(record);
+    }
+    Ok(records)
+}
+
+/// Writes `records` to a new JSONL archive.
+pub fn write_archive(path: &Path, records: &[ArchivedMessage]) -> Result<()> {
+    let file = File::create_new(path)
+        .with_context(|| format!("Cannot create archive {}", path.display()))?;
+    let mut writer = BufWriter::new(file);
+    for record in records {
+        serde_json::to_writer(&mut writer, record)?;
+        writer.write_all(b"\n")?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// `<dir>/<queue>-<UTC timestamp>.jsonl`
+pub fn timestamped_path(dir: &Path, queue_name: &str) -> PathBuf {
+    dir.join(format!(
+        "{}-{}.jsonl",
+        queue_name,
+        Utc::now().format("%Y%m%dT%H%M%SZ")
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::memory::{FakeMessage, FakeQueue, InMemoryBackend};
+    use crate::types::OutgoingMessage;
+
+    async fn export(
+        backend: &InMemoryBackend,
+        queue: &str,
+        options: ExportOptions,
+    ) -> (Exporter, Vec<ArchivedMessage>) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("out.jsonl");
+        let url = backend.queue_url(queue).await.unwrap();
+        let mut exporter = Exporter::create(&path, &url, options).unwrap();
+        while !exporter.is_done() {
+            exporter.step(backend).await.unwrap();
+        }
+        let records = std::fs::read_to_string(&path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        (exporter, records)
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn export_keeps_messages_by_default() {
+        let backend = InMemoryBackend::new().with_queue(FakeQueue::new("orders", 25));
+        let (exporter, records) = export(&backend, "orders", ExportOptions::default()).await;
+
+        assert_eq!(exporter.exported, 25);
+        assert_eq!(exporter.deleted, 0);
+        assert_eq!(records.len(), 25);
+        let info = &backend.list_queues().await.unwrap()[0];
+        assert_eq!(info.approximate_messages_not_visible, 25);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn export_can_delete_written_batches() {
+        let backend = InMemoryBackend::new().with_queue(FakeQueue::new("orders", 12));
+        let options = ExportOptions {
+            delete_after_write: true,
+            ..ExportOptions::default()
+        };
+        let (exporter, records) = export(&backend, "orders", options).await;
+
+        assert_eq!(records.len(), 12);
+        assert_eq!(exporter.deleted, 12);
+        assert!(exporter.delete_failures.is_empty());
+        let info = &backend.list_queues().await.unwrap()[0];
+        assert_eq!(
+            info.approximate_messages + info.approximate_messages_not_visible,
+            0
+        );
+    }
+
+    #[tokio::test]
+    async fn export_stops_at_max_messages() {
+        let backend = InMemoryBackend::new().with_queue(FakeQueue::new("orders", 30));
+        let options = ExportOptions {
+            max_messages: Some(13),
+            ..ExportOptions::default()
+        };
+        let (exporter, records) = export(&backend, "orders", options).await;
+
+        assert_eq!(exporter.exported, 13);
+        assert_eq!(records.len(), 13);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn export_filter_selects_what_is_written_and_deleted() {
+        // FakeQueue bodies are {"seq":N,"queue":"orders"}.
+        let backend = InMemoryBackend::new().with_queue(FakeQueue::new("orders", 20));
+        let options = ExportOptions {
+            delete_after_write: true,
+            filter: Some(".seq >= 15".parse().unwrap()),
+            ..ExportOptions::default()
+        };
+        let (exporter, records) = export(&backend, "orders", options).await;
+
+        assert_eq!(records.len(), 5);
+        assert_eq!(
+            (exporter.exported, exporter.skipped, exporter.deleted),
+            (5, 15, 5)
+        );
+        let info = &backend.list_queues().await.unwrap()[0];
+        assert_eq!(info.approximate_messages_not_visible, 15);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn export_records_attributes() {
+        let mut message = FakeMessage::new("m-1", r#"{"order":1}"#);
+        message
+            .attributes
+            .insert("MessageGroupId".to_string(), "g1".to_string());
+        message.message_attributes.insert(
+            "Type".to_string(),
+            MessageAttribute {
+                data_type: "String".to_string(),
+                string_value: Some("created".to_string()),
+                binary_value: None,
+            },
+        );
+        let backend = InMemoryBackend::new()
+            .with_queue(FakeQueue::new("orders.fifo", 0).with_message(message));
+        let (_, records) = export(&backend, "orders.fifo", ExportOptions::default()).await;
+
+        let record = &records[0];
+        assert_eq!(record.message_id, "m-1");
+        assert_eq!(record.body, r#"{"order":1}"#);
+        assert_eq!(record.attributes["MessageGroupId"], "g1");
+        assert_eq!(record.attributes["ApproximateReceiveCount"], "1");
+        assert_eq!(
+            record.message_attributes["Type"].string_value.as_deref(),
+            Some("created")
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn redelivered_messages_do_not_end_the_export() {
+        let backend = InMemoryBackend::new().with_queue(FakeQueue::new("orders", 3));
+        let url = backend.queue_url("orders").await.unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        // Received messages are visible again at once, so every receive
+        // returns the ones already written.
+        let options = ExportOptions {
+            visibility_timeout: 0,
+            ..ExportOptions::default()
+        };
+        let mut exporter = Exporter::create(&dir.path().join("out.jsonl"), &url, options).unwrap();
+
+        for _ in 0..3 {
+            exporter.step(&backend).await.unwrap();
+        }
+        assert_eq!(exporter.exported, 3);
+        assert!(!exporter.is_done());
+
+        let late: Vec<OutgoingMessage> = (0..2)
+            .map(|i| OutgoingMessage {
+                body: format!("late-{}", i),
+                ..OutgoingMessage::default()
+            })
+            .collect();
+        backend.send_messages(&url, &late).await.unwrap();
+        exporter.step(&backend).await.unwrap();
+        assert_eq!(exporter.exported, 5);
+
+        while !exporter.is_done() {
+            exporter.step(&backend).await.unwrap();
+        }
+        assert_eq!(exporter.exported, 5);
+        assert!(exporter.stopped_on_repeats());
+    }
+
+    #[test]
+    fn create_refuses_to_overwrite() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("out.jsonl");
+        std::fs::write(&path, "existing").unwrap();
+        assert!(Exporter::create(&path, "url", ExportOptions::default()).is_err());
+    }
+}
@@ -0,0 +1,167 @@
+use crate::types::CallerIdentity;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// One line of the audit log: a single mutating call and what came of it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub timestamp: DateTime<Utc>,
+    pub os_user: Option<String>,
+    #[serde(flatten)]
+    pub identity: CallerIdentity,
+    pub queue_url: String,
+    /// `purge`, `delete`, `send`, ...
+    pub action: String,
+    #[serde(default)]
+    pub parameters: serde_json::Value,
+    /// Visible, in-flight and delayed messages just before the call, when
+    /// they could be read.
+    pub messages_before: Option<i64>,
+    #[serde(flatten)]
+    pub outcome: AuditOutcome,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "outcome", rename_all = "snake_case")]
+pub enum AuditOutcome {
+    Ok,
+    /// Some entries of a batch call failed.
+    Partial {
+        failed: usize,
+    },
+    Error {
+        error: String,
+    },
+}
+
+impl AuditOutcome {
+    pub fn from_result<T, E: std::fmt::Display>(result: &std::result::Result<T, E>) -> Self {
+        match result {
+            Ok(_) => AuditOutcome::Ok,
+            Err(e) => AuditOutcome::Error {
+                error: e.to_string(),
+            },
+        }
+    }
+}
+
+/// The newest entries of an audit log.
+#[derive(Debug, Default)]
+pub struct RecentEntries {
+    /// Newest first.
+    pub entries: Vec<AuditEntry>,
+    /// Lines among them that are not valid entries, such as one cut short
+    /// by a crash mid-append; they are skipped.
+    pub unreadable: usize,
+}
+
+/// Append-only JSONL file of audit entries.
+#[derive(Debug, Clone)]
+pub struct AuditLog {
+    path: PathBuf,
+}
+
+impl AuditLog {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Writes `entry` as one line, creating the file and its directory if
+    /// needed.
+    pub fn append(&self, entry: &AuditEntry) -> Result<()> {
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let mut line = serde_json::to_vec(entry)?;
+        line.push(b'\n');
+        // A single write on an O_APPEND file keeps concurrent writers from
+        // interleaving within a line.
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .and_then(|mut file| file.write_all(&line))
+            .with_context(|| format!("Cannot write audit log {}", self.path.display()))
+    }
+
+    /// The last `limit` entries, newest first. A missing log has none.
+    pub fn recent(&self, limit: usize) -> Result<RecentEntries> {
+        let content = match std::fs::read_to_string(&self.path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Ok(RecentEntries::default());
+            }
+            Err(e) => {
+                return Err(e)
+                    .with_context(|| format!("Cannot read audit log {}", self.path.display()));
+            }
+        };
+        let mut recent = RecentEntries::default();
+        for line in content.lines().rev() {
+            if recent.entries.len() == limit {
+                break;
+            }
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str(line) {
+                Ok(entry) => recent.entries.push(entry),
+                Err(_) => recent.unreadable += 1,
+            }
+        }
+        Ok(recent)
+    }
+}
+
+/// The login name of whoever runs the tool.
+pub fn os_user() -> Option<String> {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn skips_lines_that_are_not_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = AuditLog::new(dir.path().join("audit.jsonl"));
+        assert!(log.recent(10).unwrap().entries.is_empty());
+
+        let entry = |action: &str| AuditEntry {
+            timestamp: Utc::now(),
+            os_user: None,
+            identity: CallerIdentity::default(),
+            queue_url: "https://sqs.us-east-1.amazonaws.com/1/orders".to_string(),
+            action: action.to_string(),
+            parameters: serde_json::Value::Null,
+            messages_before: Some(3),
+            outcome: AuditOutcome::Ok,
+        };
+        log.append(&entry("purge")).unwrap();
+        // A crash part-way through an append
+        std::fs::OpenOptions::new()
+            .append(true)
+            .open(log.path())
+            .unwrap()
+            .write_all(b"{\"timestamp\":\"2024-06-01T12:00\n")
+            .unwrap();
+        log.append(&entry("send")).unwrap();
+
+        let recent = log.recent(10).unwrap();
+        let actions: Vec<&str> = recent.entries.iter().map(|e| e.action.as_str()).collect();
+        assert_eq!(actions, ["send", "purge"]);
+        assert_eq!(recent.unreadable, 1);
+        assert_eq!(log.recent(1).unwrap().unreadable, 0);
+    }
+}
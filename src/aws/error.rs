@@ -0,0 +1,43 @@
+use crate::backend::BackendError;
+use aws_sdk_sqs::error::{BuildError, DisplayErrorContext, ProvideErrorMetadata, SdkError};
+
+/// Classifies SDK failures for any AWS service client (SQS, STS).
+impl<E, R> From<SdkError<E, R>> for BackendError
+where
+    E: ProvideErrorMetadata + std::error::Error + Send + Sync + 'static,
+    R: std::fmt::Debug,
+{
+    fn from(error: SdkError<E, R>) -> Self {
+        if let Some(code) = error.code() {
+            return BackendError::from_code(code, error.message().unwrap_or_default());
+        }
+
+        let detail = DisplayErrorContext(&error).to_string();
+        match &error {
+            // The credentials chain fails before anything is sent, and only
+            // says so in its error text.
+            _ if detail.to_lowercase().contains("credentials") => {
+                BackendError::InvalidCredentials(detail)
+            }
+            SdkError::TimeoutError(_) | SdkError::ResponseError(_) => {
+                BackendError::Transient(detail)
+            }
+            SdkError::DispatchFailure(failure) if failure.is_io() || failure.is_timeout() => {
+                BackendError::Transient(detail)
+            }
+            _ => BackendError::Other(detail),
+        }
+    }
+}
+
+impl From<BuildError> for BackendError {
+    fn from(error: BuildError) -> Self {
+        BackendError::Other(error.to_string())
+    }
+}
+
+impl From<base64::DecodeError> for BackendError {
+    fn from(error: base64::DecodeError) -> Self {
+        BackendError::Other(format!("invalid base64 binary attribute: {}", error))
+    }
+}
@@ -1 +1,3 @@
+mod error;
+pub mod profile;
 pub mod sqs;
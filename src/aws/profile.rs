@@ -0,0 +1,84 @@
+use std::path::PathBuf;
+
+/// Which AWS profile and region to connect with. `None` defers to the usual
+/// environment and config-file lookup.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConnectionSettings {
+    pub profile: Option<String>,
+    pub region: Option<String>,
+}
+
+impl ConnectionSettings {
+    /// Where credentials will come from, as far as can be told without
+    /// resolving them.
+    pub fn credential_source(&self) -> String {
+        let env = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+        if let Some(profile) = &self.profile {
+            format!("profile '{}'", profile)
+        } else if env("AWS_ACCESS_KEY_ID").is_some() {
+            "environment variables (AWS_ACCESS_KEY_ID)".to_string()
+        } else if let Some(profile) = env("AWS_PROFILE") {
+            format!("profile '{}' (AWS_PROFILE)", profile)
+        } else if env("AWS_WEB_IDENTITY_TOKEN_FILE").is_some() {
+            "web identity token (AWS_WEB_IDENTITY_TOKEN_FILE)".to_string()
+        } else {
+            "default chain: profile 'default', SSO, container or instance role".to_string()
+        }
+    }
+}
+
+/// Profile names from the shared config and credentials files, sorted.
+pub fn known_profiles() -> Vec<String> {
+    let home = dirs::home_dir().unwrap_or_default().join(".aws");
+    let file = |var: &str, default: &str| {
+        std::env::var_os(var)
+            .map(PathBuf::from)
+            .unwrap_or_else(|| home.join(default))
+    };
+
+    let mut profiles = Vec::new();
+    for (path, is_config) in [
+        (file("AWS_CONFIG_FILE", "config"), true),
+        (file("AWS_SHARED_CREDENTIALS_FILE", "credentials"), false),
+    ] {
+        if let Ok(content) = std::fs::read_to_string(path) {
+            profiles.extend(profile_names(&content, is_config));
+        }
+    }
+    profiles.sort();
+    profiles.dedup();
+    profiles
+}
+
+/// Section names that are profiles: `[profile x]` (or `[default]`) in the
+/// config file, any `[x]` in the credentials file.
+fn profile_names(content: &str, is_config: bool) -> Vec<String> {
+    content
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix('[')?.strip_suffix(']'))
+        .filter_map(|section| {
+            let section = section.trim();
+            if !is_config || section == "default" {
+                Some(section.to_string())
+            } else {
+                section
+                    .strip_prefix("profile ")
+                    .map(|name| name.trim().to_string())
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_profile_sections() {
+        let config = "[default]\nregion = us-east-1\n\n[profile dev]\nsso_start_url = x\n[sso-session corp]\n[ profile prod ]\n";
+        assert_eq!(profile_names(config, true), ["default", "dev", "prod"]);
+
+        let credentials = "[default]\naws_access_key_id = x\n[ci]\n";
+        assert_eq!(profile_names(credentials, false), ["default", "ci"]);
+    }
+}
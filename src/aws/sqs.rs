@@ -1,34 +1,81 @@
-use anyhow::Result;
+use aws_sdk_cloudwatch::primitives::DateTime as SmithyDateTime;
+use aws_sdk_cloudwatch::types::{Dimension, Statistic};
 use aws_sdk_sqs::Client;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 
-use crate::types::{QueueDetails, QueueInfo};
+use super::profile::ConnectionSettings;
+use crate::backend::{
+    BackendError, BackendResult, QueueBackend, is_queue_url, queue_name_from_url,
+};
+use crate::types::{
+    BatchFailure, CallerIdentity, DeleteEntry, MessageAttribute, MetricPoint, MetricQuery,
+    OutgoingMessage, QueueDetails, QueueInfo, QueueMessage, ReceiveOptions, SendBatchResult,
+    SentMessage, VisibilityEntry,
+};
+use async_trait::async_trait;
+use aws_config::Region;
+use aws_config::profile::ProfileFileCredentialsProvider;
+use aws_config::retry::RetryConfig;
+use aws_sdk_sqs::primitives::Blob;
+use aws_sdk_sqs::types::{
+    BatchResultErrorEntry, ChangeMessageVisibilityBatchRequestEntry,
+    DeleteMessageBatchRequestEntry, Message, MessageAttributeValue, MessageSystemAttributeName,
+    SendMessageBatchRequestEntry,
+};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
 
 pub struct SqsClient {
     client: Client,
+    sts: aws_sdk_sts::Client,
+    cloudwatch: aws_sdk_cloudwatch::Client,
+    profile: Option<String>,
+    region: Option<String>,
 }
 
 impl SqsClient {
-    pub async fn new() -> Result<Self> {
-        let config = aws_config::load_from_env().await;
-        let client = Client::new(&config);
-        Ok(Self { client })
-    }
-
-    pub async fn list_queues(&self) -> Result<Vec<QueueInfo>> {
-        let resp = self.client.list_queues().send().await?;
+    /// Builds clients for `settings`. Nothing is resolved or checked until
+    /// the first call, so this cannot fail; bad credentials or an unknown
+    /// profile surface as errors from the calls themselves.
+    ///
+    /// Endpoints follow `AWS_ENDPOINT_URL` and its per-service variants such
+    /// as `AWS_ENDPOINT_URL_CLOUDWATCH`, so LocalStack can stand in for SQS
+    /// and CloudWatch.
+    pub async fn connect(settings: &ConnectionSettings) -> Self {
+        // Retries happen in `RetryingBackend`, with backoff that knows which
+        // calls are safe to repeat.
+        let mut loader = aws_config::from_env().retry_config(RetryConfig::disabled());
+        if let Some(profile) = &settings.profile {
+            // An explicit profile also has to win over credentials in the
+            // environment, which the default chain would try first.
+            loader = loader.profile_name(profile).credentials_provider(
+                ProfileFileCredentialsProvider::builder()
+                    .profile_name(profile)
+                    .build(),
+            );
+        }
+        if let Some(region) = &settings.region {
+            loader = loader.region(Region::new(region.clone()));
+        }
+        let config = loader.load().await;
 
-        let mut queues = Vec::new();
-        let urls = resp.queue_urls();
-        for url in urls {
-            let queue_info = self.get_queue_info(url).await?;
-            queues.push(queue_info);
+        Self {
+            client: Client::new(&config),
+            sts: aws_sdk_sts::Client::new(&config),
+            cloudwatch: aws_sdk_cloudwatch::Client::new(&config),
+            profile: settings
+                .profile
+                .clone()
+                .or_else(|| std::env::var("AWS_PROFILE").ok()),
+            region: config.region().map(|r| r.to_string()),
         }
+    }
 
-        Ok(queues)
+    pub fn region(&self) -> Option<&str> {
+        self.region.as_deref()
     }
 
-    async fn get_queue_info(&self, url: &str) -> Result<QueueInfo> {
+    async fn get_queue_info(&self, url: &str) -> BackendResult<QueueInfo> {
         let resp = self
             .client
             .get_queue_attributes()
@@ -61,7 +108,7 @@ impl SqsClient {
             .and_then(|v| v.parse::<i64>().ok())
             .unwrap_or(0);
 
-        let name = url.rsplit('/').next().unwrap_or("unknown").to_string();
+        let name = queue_name_from_url(url).to_string();
 
         Ok(QueueInfo {
             url: url.to_string(),
@@ -72,8 +119,24 @@ impl SqsClient {
             last_updated: Utc::now(),
         })
     }
+}
+
+#[async_trait]
+impl QueueBackend for SqsClient {
+    async fn list_queues(&self) -> BackendResult<Vec<QueueInfo>> {
+        let resp = self.client.list_queues().send().await?;
 
-    pub async fn get_queue_details(&self, url: &str) -> Result<QueueDetails> {
+        let mut queues = Vec::new();
+        let urls = resp.queue_urls();
+        for url in urls {
+            let queue_info = self.get_queue_info(url).await?;
+            queues.push(queue_info);
+        }
+
+        Ok(queues)
+    }
+
+    async fn get_queue_details(&self, url: &str) -> BackendResult<QueueDetails> {
         let resp = self
             .client
             .get_queue_attributes()
@@ -107,11 +170,278 @@ impl SqsClient {
             delay_seconds: attributes
                 .get(&aws_sdk_sqs::types::QueueAttributeName::DelaySeconds)
                 .and_then(|v| v.parse::<i32>().ok()),
+            fifo_queue: attributes
+                .get(&aws_sdk_sqs::types::QueueAttributeName::FifoQueue)
+                .is_some_and(|v| v == "true"),
+            content_based_deduplication: attributes
+                .get(&aws_sdk_sqs::types::QueueAttributeName::ContentBasedDeduplication)
+                .is_some_and(|v| v == "true"),
+            deduplication_scope: attributes
+                .get(&aws_sdk_sqs::types::QueueAttributeName::DeduplicationScope)
+                .cloned(),
+            fifo_throughput_limit: attributes
+                .get(&aws_sdk_sqs::types::QueueAttributeName::FifoThroughputLimit)
+                .cloned(),
+            attributes: attributes
+                .iter()
+                .map(|(name, value)| (name.as_str().to_string(), value.clone()))
+                .collect(),
+        })
+    }
+
+    async fn caller_identity(&self) -> BackendResult<CallerIdentity> {
+        let resp = self.sts.get_caller_identity().send().await?;
+        Ok(CallerIdentity {
+            account: resp.account().map(str::to_string),
+            arn: resp.arn().map(str::to_string),
+            profile: self.profile.clone(),
+            region: self.region.clone(),
+        })
+    }
+
+    async fn queue_metric(
+        &self,
+        url: &str,
+        query: &MetricQuery,
+    ) -> BackendResult<Vec<MetricPoint>> {
+        let statistic = if query.metric.is_count() {
+            Statistic::Sum
+        } else {
+            Statistic::Maximum
+        };
+        let resp = self
+            .cloudwatch
+            .get_metric_statistics()
+            .namespace("AWS/SQS")
+            .metric_name(query.metric.name())
+            .dimensions(
+                Dimension::builder()
+                    .name("QueueName")
+                    .value(queue_name_from_url(url))
+                    .build(),
+            )
+            .start_time(SmithyDateTime::from_secs(query.start.timestamp()))
+            .end_time(SmithyDateTime::from_secs(query.end.timestamp()))
+            .period(query.period_secs)
+            .statistics(statistic.clone())
+            .send()
+            .await?;
+
+        let mut points: Vec<MetricPoint> = resp
+            .datapoints()
+            .iter()
+            .filter_map(|point| {
+                let timestamp = DateTime::from_timestamp(point.timestamp()?.secs(), 0)?;
+                let value = match statistic {
+                    Statistic::Sum => point.sum()?,
+                    _ => point.maximum()?,
+                };
+                Some(MetricPoint { timestamp, value })
+            })
+            .collect();
+        // CloudWatch returns datapoints in no particular order
+        points.sort_by_key(|point| point.timestamp);
+        Ok(points)
+    }
+
+    async fn purge_queue(&self, url: &str) -> BackendResult<()> {
+        match self.client.purge_queue().queue_url(url).send().await {
+            Ok(_) => Ok(()),
+            Err(e)
+                if e.as_service_error()
+                    .is_some_and(|e| e.is_purge_queue_in_progress()) =>
+            {
+                Err(BackendError::PurgeInProgress)
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn queue_url(&self, name_or_url: &str) -> BackendResult<String> {
+        if is_queue_url(name_or_url) {
+            return Ok(name_or_url.to_string());
+        }
+        let resp = self
+            .client
+            .get_queue_url()
+            .queue_name(name_or_url)
+            .send()
+            .await?;
+        resp.queue_url().map(str::to_string).ok_or_else(|| {
+            BackendError::Other(format!("No URL returned for queue '{}'", name_or_url))
         })
     }
 
-    pub async fn purge_queue(&self, url: &str) -> Result<()> {
-        self.client.purge_queue().queue_url(url).send().await?;
-        Ok(())
+    async fn receive_messages(
+        &self,
+        url: &str,
+        options: ReceiveOptions,
+    ) -> BackendResult<Vec<QueueMessage>> {
+        let resp = self
+            .client
+            .receive_message()
+            .queue_url(url)
+            .max_number_of_messages(options.max_messages)
+            .set_visibility_timeout(options.visibility_timeout)
+            .wait_time_seconds(options.wait_time_seconds)
+            .message_system_attribute_names(MessageSystemAttributeName::All)
+            .message_attribute_names("All")
+            .send()
+            .await?;
+
+        Ok(resp.messages().iter().map(convert_message).collect())
+    }
+
+    async fn delete_messages(
+        &self,
+        url: &str,
+        entries: &[DeleteEntry],
+    ) -> BackendResult<Vec<BatchFailure>> {
+        let entries = entries
+            .iter()
+            .map(|entry| {
+                DeleteMessageBatchRequestEntry::builder()
+                    .id(&entry.id)
+                    .receipt_handle(&entry.receipt_handle)
+                    .build()
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let resp = self
+            .client
+            .delete_message_batch()
+            .queue_url(url)
+            .set_entries(Some(entries))
+            .send()
+            .await?;
+
+        Ok(resp.failed().iter().map(convert_failure).collect())
+    }
+
+    async fn change_message_visibility(
+        &self,
+        url: &str,
+        entries: &[VisibilityEntry],
+    ) -> BackendResult<Vec<BatchFailure>> {
+        let entries = entries
+            .iter()
+            .map(|entry| {
+                ChangeMessageVisibilityBatchRequestEntry::builder()
+                    .id(&entry.id)
+                    .receipt_handle(&entry.receipt_handle)
+                    .visibility_timeout(entry.visibility_timeout)
+                    .build()
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let resp = self
+            .client
+            .change_message_visibility_batch()
+            .queue_url(url)
+            .set_entries(Some(entries))
+            .send()
+            .await?;
+
+        Ok(resp.failed().iter().map(convert_failure).collect())
+    }
+
+    async fn send_messages(
+        &self,
+        url: &str,
+        messages: &[OutgoingMessage],
+    ) -> BackendResult<SendBatchResult> {
+        let mut entries = Vec::with_capacity(messages.len());
+        for message in messages {
+            let mut attributes = std::collections::HashMap::new();
+            for (name, attribute) in &message.message_attributes {
+                let binary_value = match &attribute.binary_value {
+                    Some(encoded) => Some(Blob::new(BASE64.decode(encoded)?)),
+                    None => None,
+                };
+                let value = MessageAttributeValue::builder()
+                    .data_type(&attribute.data_type)
+                    .set_string_value(attribute.string_value.clone())
+                    .set_binary_value(binary_value)
+                    .build()?;
+                attributes.insert(name.clone(), value);
+            }
+
+            entries.push(
+                SendMessageBatchRequestEntry::builder()
+                    .id(&message.id)
+                    .message_body(&message.body)
+                    .set_message_attributes((!attributes.is_empty()).then_some(attributes))
+                    .set_message_group_id(message.message_group_id.clone())
+                    .set_message_deduplication_id(message.message_deduplication_id.clone())
+                    .set_delay_seconds(message.delay_seconds)
+                    .build()?,
+            );
+        }
+
+        let resp = self
+            .client
+            .send_message_batch()
+            .queue_url(url)
+            .set_entries(Some(entries))
+            .send()
+            .await?;
+
+        Ok(SendBatchResult {
+            sent: resp
+                .successful()
+                .iter()
+                .map(|entry| SentMessage {
+                    id: entry.id().to_string(),
+                    message_id: entry.message_id().to_string(),
+                })
+                .collect(),
+            failed: resp.failed().iter().map(convert_failure).collect(),
+        })
+    }
+}
+
+fn convert_failure(failure: &BatchResultErrorEntry) -> BatchFailure {
+    BatchFailure {
+        id: failure.id().to_string(),
+        code: failure.code().to_string(),
+        message: failure.message().unwrap_or_default().to_string(),
+    }
+}
+
+fn convert_message(message: &Message) -> QueueMessage {
+    QueueMessage {
+        message_id: message.message_id().unwrap_or_default().to_string(),
+        receipt_handle: message.receipt_handle().unwrap_or_default().to_string(),
+        body: message.body().unwrap_or_default().to_string(),
+        md5_of_body: message.md5_of_body().map(str::to_string),
+        attributes: message
+            .attributes()
+            .map(|attributes| {
+                attributes
+                    .iter()
+                    .map(|(name, value)| (name.as_str().to_string(), value.clone()))
+                    .collect()
+            })
+            .unwrap_or_default(),
+        message_attributes: message
+            .message_attributes()
+            .map(|attributes| {
+                attributes
+                    .iter()
+                    .map(|(name, value)| {
+                        (
+                            name.clone(),
+                            MessageAttribute {
+                                data_type: value.data_type().to_string(),
+                                string_value: value.string_value().map(str::to_string),
+                                binary_value: value
+                                    .binary_value()
+                                    .map(|blob| BASE64.encode(blob.as_ref())),
+                            },
+                        )
+                    })
+                    .collect()
+            })
+            .unwrap_or_default(),
     }
 }
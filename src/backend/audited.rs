@@ -0,0 +1,290 @@
+use super::{BackendResult, QueueBackend, approximate_total};
+use crate::audit::{AuditEntry, AuditLog, AuditOutcome, os_user};
+use crate::types::{
+    BatchFailure, CallerIdentity, DeleteEntry, MetricPoint, MetricQuery, OutgoingMessage,
+    QueueDetails, QueueInfo, QueueMessage, ReceiveOptions, SendBatchResult, VisibilityEntry,
+};
+use async_trait::async_trait;
+use chrono::Utc;
+use serde_json::json;
+use std::sync::Mutex;
+use tokio::sync::OnceCell;
+
+/// Wraps another backend and appends an `AuditEntry` for every call that
+/// changes a queue, with the queue's message count looked up just before
+/// it. Reads pass straight through.
+///
+/// The entry is written after the call, whatever its outcome. A call that
+/// went through is never reported as failed because its entry could not be
+/// written; the write error is kept for `take_audit_failure` instead.
+pub struct AuditedBackend {
+    inner: Box<dyn QueueBackend>,
+    log: AuditLog,
+    os_user: Option<String>,
+    identity: OnceCell<CallerIdentity>,
+    write_failure: Mutex<Option<String>>,
+}
+
+impl AuditedBackend {
+    pub fn new(inner: Box<dyn QueueBackend>, log: AuditLog) -> Self {
+        Self {
+            inner,
+            log,
+            os_user: os_user(),
+            identity: OnceCell::new(),
+            write_failure: Mutex::new(None),
+        }
+    }
+
+    /// Looked up once; a failed lookup is retried on the next entry and the
+    /// current one is written without identity.
+    async fn identity(&self) -> CallerIdentity {
+        self.identity
+            .get_or_try_init(|| self.inner.caller_identity())
+            .await
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    async fn messages_before(&self, url: &str) -> Option<i64> {
+        let details = self.inner.get_queue_details(url).await.ok()?;
+        approximate_total(&details)
+    }
+
+    async fn record(
+        &self,
+        url: &str,
+        action: &str,
+        parameters: serde_json::Value,
+        messages_before: Option<i64>,
+        outcome: AuditOutcome,
+    ) {
+        let written = self.log.append(&AuditEntry {
+            timestamp: Utc::now(),
+            os_user: self.os_user.clone(),
+            identity: self.identity().await,
+            queue_url: url.to_string(),
+            action: action.to_string(),
+            parameters,
+            messages_before,
+            outcome,
+        });
+        if let Err(e) = written {
+            *self.write_failure.lock().unwrap() = Some(format!("{:#}", e));
+        }
+    }
+}
+
+#[async_trait]
+impl QueueBackend for AuditedBackend {
+    async fn list_queues(&self) -> BackendResult<Vec<QueueInfo>> {
+        self.inner.list_queues().await
+    }
+
+    async fn get_queue_details(&self, url: &str) -> BackendResult<QueueDetails> {
+        self.inner.get_queue_details(url).await
+    }
+
+    async fn caller_identity(&self) -> BackendResult<CallerIdentity> {
+        self.inner.caller_identity().await
+    }
+
+    async fn purge_queue(&self, url: &str) -> BackendResult<()> {
+        let before = self.messages_before(url).await;
+        let result = self.inner.purge_queue(url).await;
+        self.record(
+            url,
+            "purge",
+            json!({}),
+            before,
+            AuditOutcome::from_result(&result),
+        )
+        .await;
+        result
+    }
+
+    async fn queue_url(&self, name_or_url: &str) -> BackendResult<String> {
+        self.inner.queue_url(name_or_url).await
+    }
+
+    async fn queue_metric(
+        &self,
+        url: &str,
+        query: &MetricQuery,
+    ) -> BackendResult<Vec<MetricPoint>> {
+        self.inner.queue_metric(url, query).await
+    }
+
+    fn take_audit_failure(&self) -> Option<String> {
+        self.write_failure.lock().unwrap().take()
+    }
+
+    async fn receive_messages(
+        &self,
+        url: &str,
+        options: ReceiveOptions,
+    ) -> BackendResult<Vec<QueueMessage>> {
+        self.inner.receive_messages(url, options).await
+    }
+
+    async fn delete_messages(
+        &self,
+        url: &str,
+        entries: &[DeleteEntry],
+    ) -> BackendResult<Vec<BatchFailure>> {
+        let before = self.messages_before(url).await;
+        let result = self.inner.delete_messages(url, entries).await;
+        let outcome = match &result {
+            Ok(failures) if !failures.is_empty() => AuditOutcome::Partial {
+                failed: failures.len(),
+            },
+            _ => AuditOutcome::from_result(&result),
+        };
+        self.record(
+            url,
+            "delete",
+            json!({ "messages": entries.len() }),
+            before,
+            outcome,
+        )
+        .await;
+        result
+    }
+
+    async fn change_message_visibility(
+        &self,
+        url: &str,
+        entries: &[VisibilityEntry],
+    ) -> BackendResult<Vec<BatchFailure>> {
+        let before = self.messages_before(url).await;
+        let result = self.inner.change_message_visibility(url, entries).await;
+        let outcome = match &result {
+            Ok(failures) if !failures.is_empty() => AuditOutcome::Partial {
+                failed: failures.len(),
+            },
+            _ => AuditOutcome::from_result(&result),
+        };
+        let timeouts: Vec<i32> = entries.iter().map(|e| e.visibility_timeout).collect();
+        self.record(
+            url,
+            "change-visibility",
+            json!({ "messages": entries.len(), "visibility_timeouts": timeouts }),
+            before,
+            outcome,
+        )
+        .await;
+        result
+    }
+
+    async fn send_messages(
+        &self,
+        url: &str,
+        messages: &[OutgoingMessage],
+    ) -> BackendResult<SendBatchResult> {
+        let before = self.messages_before(url).await;
+        let result = self.inner.send_messages(url, messages).await;
+        let outcome = match &result {
+            Ok(sent) if !sent.failed.is_empty() => AuditOutcome::Partial {
+                failed: sent.failed.len(),
+            },
+            _ => AuditOutcome::from_result(&result),
+        };
+        self.record(
+            url,
+            "send",
+            json!({ "messages": messages.len() }),
+            before,
+            outcome,
+        )
+        .await;
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::BackendError;
+    use crate::backend::memory::{FakeQueue, InMemoryBackend, Operation};
+
+    fn audited(fake: &InMemoryBackend) -> (AuditedBackend, tempfile::TempDir) {
+        let dir = tempfile::tempdir().unwrap();
+        let log = AuditLog::new(dir.path().join("audit.jsonl"));
+        (AuditedBackend::new(Box::new(fake.clone()), log), dir)
+    }
+
+    #[tokio::test]
+    async fn mutating_calls_are_recorded() {
+        let fake = InMemoryBackend::new().with_queue(FakeQueue::new("orders", 7));
+        let (backend, _dir) = audited(&fake);
+        let url = backend.queue_url("orders").await.unwrap();
+
+        backend.list_queues().await.unwrap();
+        let outgoing = OutgoingMessage {
+            id: "0".to_string(),
+            body: "hello".to_string(),
+            ..OutgoingMessage::default()
+        };
+        backend.send_messages(&url, &[outgoing]).await.unwrap();
+        backend.purge_queue(&url).await.unwrap();
+
+        let entries = backend.log.recent(10).unwrap().entries;
+        assert_eq!(entries.len(), 2);
+        let purge = &entries[0];
+        assert_eq!(purge.action, "purge");
+        assert_eq!(purge.queue_url, url);
+        assert_eq!(purge.messages_before, Some(8));
+        assert_eq!(purge.outcome, AuditOutcome::Ok);
+        assert_eq!(purge.identity.account.as_deref(), Some("000000000000"));
+        assert_eq!(entries[1].action, "send");
+        assert_eq!(entries[1].parameters, json!({ "messages": 1 }));
+        assert_eq!(entries[1].messages_before, Some(7));
+        // Reads are not recorded and cost no count lookup
+        assert_eq!(fake.calls(Operation::GetQueueDetails), 2);
+        assert_eq!(fake.calls(Operation::GetCallerIdentity), 1);
+    }
+
+    #[tokio::test]
+    async fn failures_are_recorded_and_still_returned() {
+        let fake = InMemoryBackend::new().with_queue(FakeQueue::new("orders", 3));
+        fake.fail_next(Operation::PurgeQueue, "PurgeQueueInProgress");
+        fake.fail_next(Operation::GetCallerIdentity, "ExpiredToken");
+        let (backend, _dir) = audited(&fake);
+        let url = backend.queue_url("orders").await.unwrap();
+
+        assert!(backend.purge_queue(&url).await.is_err());
+        let stale = DeleteEntry {
+            id: "0".to_string(),
+            receipt_handle: "stale".to_string(),
+        };
+        let failures = backend.delete_messages(&url, &[stale]).await.unwrap();
+        assert_eq!(failures.len(), 1);
+
+        let entries = backend.log.recent(10).unwrap().entries;
+        assert_eq!(entries[0].outcome, AuditOutcome::Partial { failed: 1 });
+        assert_eq!(entries[0].messages_before, Some(3));
+        assert_eq!(
+            entries[1].outcome,
+            AuditOutcome::Error {
+                error: BackendError::PurgeInProgress.to_string()
+            }
+        );
+        // The identity lookup failed for the first entry and was retried.
+        assert_eq!(entries[1].identity, CallerIdentity::default());
+        assert!(entries[0].identity.arn.is_some());
+    }
+
+    #[tokio::test]
+    async fn unwritable_log_is_reported_without_failing_the_call() {
+        let fake = InMemoryBackend::new().with_queue(FakeQueue::new("orders", 3));
+        let dir = tempfile::tempdir().unwrap();
+        // A directory where the log file should be.
+        let backend = AuditedBackend::new(Box::new(fake.clone()), AuditLog::new(dir.path()));
+        let url = backend.queue_url("orders").await.unwrap();
+
+        backend.purge_queue(&url).await.unwrap();
+        assert_eq!(fake.calls(Operation::PurgeQueue), 1);
+        assert!(backend.take_audit_failure().is_some());
+        assert!(backend.take_audit_failure().is_none());
+    }
+}
@@ -0,0 +1,155 @@
+use std::fmt;
+
+pub type BackendResult<T> = Result<T, BackendError>;
+
+/// Why a backend call failed, classified so the UI can say what to do about
+/// it and `retry::RetryingBackend` knows what is worth another attempt.
+///
+/// Each variant carries the service's `Code: message` text for reference.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BackendError {
+    /// Temporary credentials or a session token ran out.
+    ExpiredCredentials(String),
+    /// No credentials were found, or AWS rejected them.
+    InvalidCredentials(String),
+    AccessDenied(String),
+    Throttled(String),
+    /// Timeouts, dropped connections and 5xx responses.
+    Transient(String),
+    QueueNotFound(String),
+    /// The queue was purged within the last 60 seconds.
+    PurgeInProgress,
+    Other(String),
+}
+
+impl BackendError {
+    /// Classifies an AWS error code such as `ThrottlingException` or
+    /// `AWS.SimpleQueueService.NonExistentQueue`.
+    pub fn from_code(code: &str, message: &str) -> Self {
+        let detail = if message.is_empty() || message == code {
+            code.to_string()
+        } else {
+            format!("{}: {}", code, message)
+        };
+        match code.trim_start_matches("AWS.SimpleQueueService.") {
+            "ExpiredToken" | "ExpiredTokenException" | "RequestExpired" => {
+                BackendError::ExpiredCredentials(detail)
+            }
+            "InvalidClientTokenId"
+            | "UnrecognizedClientException"
+            | "SignatureDoesNotMatch"
+            | "MissingAuthenticationToken"
+            | "IncompleteSignature"
+            | "InvalidSecurity" => BackendError::InvalidCredentials(detail),
+            "AccessDenied" | "AccessDeniedException" | "KmsAccessDenied" => {
+                BackendError::AccessDenied(detail)
+            }
+            "Throttling"
+            | "ThrottlingException"
+            | "RequestThrottled"
+            | "TooManyRequestsException"
+            | "KmsThrottled" => BackendError::Throttled(detail),
+            "InternalError"
+            | "InternalFailure"
+            | "ServiceUnavailable"
+            | "ServiceUnavailableException"
+            | "RequestTimeout" => BackendError::Transient(detail),
+            "NonExistentQueue" | "QueueDoesNotExist" => BackendError::QueueNotFound(detail),
+            "PurgeQueueInProgress" => BackendError::PurgeInProgress,
+            _ => BackendError::Other(detail),
+        }
+    }
+
+    /// Failures that mean nothing can be reached with the current profile
+    /// and region, rather than a problem with one queue or call.
+    pub fn is_connection_problem(&self) -> bool {
+        matches!(
+            self,
+            BackendError::ExpiredCredentials(_)
+                | BackendError::InvalidCredentials(_)
+                | BackendError::AccessDenied(_)
+                | BackendError::Transient(_)
+        )
+    }
+
+    /// Throttling and transient failures may succeed if tried again.
+    pub fn is_transient(&self) -> bool {
+        matches!(
+            self,
+            BackendError::Throttled(_) | BackendError::Transient(_)
+        )
+    }
+}
+
+impl fmt::Display for BackendError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BackendError::ExpiredCredentials(detail) => write!(
+                f,
+                "credentials expired — renew them and press R, or press P to switch profile ({})",
+                detail
+            ),
+            BackendError::InvalidCredentials(detail) => write!(
+                f,
+                "no valid AWS credentials — press P to pick a profile or run `aws configure` ({})",
+                detail
+            ),
+            BackendError::AccessDenied(detail) => write!(
+                f,
+                "access denied — the current identity lacks permission ({})",
+                detail
+            ),
+            BackendError::Throttled(detail) => write!(
+                f,
+                "throttled by AWS even after retrying — wait a moment and try again ({})",
+                detail
+            ),
+            BackendError::Transient(detail) => write!(
+                f,
+                "network or service error — check connectivity and press R ({})",
+                detail
+            ),
+            BackendError::QueueNotFound(detail) => write!(
+                f,
+                "queue does not exist — it may have been deleted, press R to refresh ({})",
+                detail
+            ),
+            BackendError::PurgeInProgress => f.write_str(
+                "PurgeQueueInProgress: only one purge per queue is allowed every 60 seconds",
+            ),
+            BackendError::Other(detail) => f.write_str(detail),
+        }
+    }
+}
+
+impl std::error::Error for BackendError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_aws_error_codes() {
+        assert_eq!(
+            BackendError::from_code(
+                "ExpiredToken",
+                "The security token included in the request is expired"
+            ),
+            BackendError::ExpiredCredentials(
+                "ExpiredToken: The security token included in the request is expired".to_string()
+            )
+        );
+        assert_eq!(
+            BackendError::from_code("AWS.SimpleQueueService.NonExistentQueue", ""),
+            BackendError::QueueNotFound("AWS.SimpleQueueService.NonExistentQueue".to_string())
+        );
+        assert_eq!(
+            BackendError::from_code("AWS.SimpleQueueService.PurgeQueueInProgress", "x"),
+            BackendError::PurgeInProgress
+        );
+        assert!(BackendError::from_code("RequestThrottled", "").is_transient());
+        assert!(BackendError::from_code("InternalError", "").is_transient());
+        assert!(!BackendError::from_code("AccessDenied", "").is_transient());
+        assert_eq!(BackendError::from_code("boom", "boom").to_string(), "boom");
+    }
+}
@@ -0,0 +1,675 @@
+use super::{BackendError, BackendResult, PURGE_COOLDOWN, QueueBackend, is_queue_url};
+use crate::types::{
+    BatchFailure, CallerIdentity, DeleteEntry, MessageAttribute, MetricPoint, MetricQuery,
+    OutgoingMessage, QueueDetails, QueueInfo, QueueMessage, QueueMetric, ReceiveOptions,
+    SendBatchResult, SentMessage, VisibilityEntry,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+const FAKE_ACCOUNT_URL: &str = "https://sqs.us-east-1.amazonaws.com/000000000000";
+/// How often a long poll on an empty queue looks again.
+const LONG_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Backend operations that can be scripted to fail.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Operation {
+    ListQueues,
+    GetQueueDetails,
+    PurgeQueue,
+    GetCallerIdentity,
+    ReceiveMessages,
+    DeleteMessages,
+    ChangeMessageVisibility,
+    SendMessages,
+    QueueMetric,
+}
+
+#[derive(Debug, Clone)]
+pub struct FakeMessage {
+    pub message_id: String,
+    pub body: String,
+    /// Sent time in epoch milliseconds, as SQS reports SentTimestamp.
+    pub sent_timestamp: i64,
+    pub receive_count: u32,
+    pub first_receive_timestamp: Option<i64>,
+    /// Extra system attributes such as MessageGroupId.
+    pub attributes: BTreeMap<String, String>,
+    pub message_attributes: BTreeMap<String, MessageAttribute>,
+    receipt_handle: Option<String>,
+    invisible_until: Option<Instant>,
+}
+
+impl FakeMessage {
+    pub fn new(message_id: &str, body: &str) -> Self {
+        Self {
+            message_id: message_id.to_string(),
+            body: body.to_string(),
+            sent_timestamp: Utc::now().timestamp_millis(),
+            receive_count: 0,
+            first_receive_timestamp: None,
+            attributes: BTreeMap::new(),
+            message_attributes: BTreeMap::new(),
+            receipt_handle: None,
+            invisible_until: None,
+        }
+    }
+
+    fn is_visible(&self, now: Instant) -> bool {
+        self.invisible_until.is_none_or(|until| until <= now)
+    }
+
+    fn to_queue_message(&self) -> QueueMessage {
+        let mut attributes = self.attributes.clone();
+        attributes.insert("SentTimestamp".to_string(), self.sent_timestamp.to_string());
+        attributes.insert(
+            "ApproximateReceiveCount".to_string(),
+            self.receive_count.to_string(),
+        );
+        if let Some(first) = self.first_receive_timestamp {
+            attributes.insert(
+                "ApproximateFirstReceiveTimestamp".to_string(),
+                first.to_string(),
+            );
+        }
+        QueueMessage {
+            message_id: self.message_id.clone(),
+            receipt_handle: self.receipt_handle.clone().unwrap_or_default(),
+            body: self.body.clone(),
+            md5_of_body: None,
+            attributes,
+            message_attributes: self.message_attributes.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FakeQueue {
+    pub name: String,
+    pub delayed: i64,
+    pub details: QueueDetails,
+    pub messages: Vec<FakeMessage>,
+    next_sequence: u64,
+    /// Tokio's clock, so paused-time tests can wait out the cooldown.
+    last_purge: Option<tokio::time::Instant>,
+    /// Counts for the CloudWatch stand-in, with when they happened.
+    activity: Vec<(DateTime<Utc>, QueueMetric, u64)>,
+}
+
+impl FakeQueue {
+    /// A queue seeded with `visible` small JSON messages, oldest first.
+    pub fn new(name: &str, visible: i64) -> Self {
+        let mut details = QueueDetails {
+            arn: Some(format!("arn:aws:sqs:us-east-1:000000000000:{}", name)),
+            created_timestamp: Some(Utc::now().timestamp()),
+            last_modified_timestamp: Some(Utc::now().timestamp()),
+            message_retention_period: Some(345_600),
+            visibility_timeout: Some(30),
+            maximum_message_size: Some(262_144),
+            delay_seconds: Some(0),
+            fifo_queue: name.ends_with(".fifo"),
+            content_based_deduplication: false,
+            deduplication_scope: None,
+            fifo_throughput_limit: None,
+            attributes: BTreeMap::new(),
+        };
+        if details.fifo_queue {
+            details.deduplication_scope = Some("queue".to_string());
+            details.fifo_throughput_limit = Some("perQueue".to_string());
+        }
+        let mut queue = Self {
+            name: name.to_string(),
+            delayed: 0,
+            details,
+            messages: Vec::new(),
+            next_sequence: 0,
+            last_purge: None,
+            activity: Vec::new(),
+        };
+        let now = Utc::now().timestamp_millis();
+        for i in 0..visible {
+            let id = queue.next_message_id();
+            let mut message =
+                FakeMessage::new(&id, &format!(r#"{{"seq":{},"queue":"{}"}}"#, i, name));
+            message.sent_timestamp = now - (visible - i) * 60_000;
+            queue.messages.push(message);
+        }
+        queue
+    }
+
+    /// Adds past activity for the CloudWatch stand-in to report.
+    pub fn with_activity(mut self, at: DateTime<Utc>, metric: QueueMetric, count: u64) -> Self {
+        self.activity.push((at, metric, count));
+        self
+    }
+
+    fn record(&mut self, metric: QueueMetric, count: u64) {
+        if count > 0 {
+            self.activity.push((Utc::now(), metric, count));
+        }
+    }
+
+    pub fn with_message(mut self, message: FakeMessage) -> Self {
+        self.messages.push(message);
+        self
+    }
+
+    /// Marks the first `count` messages as received by some other consumer.
+    pub fn with_in_flight(mut self, count: usize) -> Self {
+        let until = Instant::now() + Duration::from_secs(3_600);
+        for message in self.messages.iter_mut().take(count) {
+            message.invisible_until = Some(until);
+            message.receive_count += 1;
+        }
+        self
+    }
+
+    /// Deterministic UUID-shaped ids, unique per queue.
+    pub fn next_message_id(&mut self) -> String {
+        let name_hash = self.name.bytes().fold(0x811c_9dc5_u32, |hash, byte| {
+            (hash ^ u32::from(byte)).wrapping_mul(0x0100_0193)
+        });
+        self.next_sequence += 1;
+        format!(
+            "{:08x}-0000-4000-8000-{:012x}",
+            name_hash, self.next_sequence
+        )
+    }
+
+    pub fn url(&self) -> String {
+        format!("{}/{}", FAKE_ACCOUNT_URL, self.name)
+    }
+
+    fn info(&self) -> QueueInfo {
+        QueueInfo {
+            url: self.url(),
+            name: self.name.clone(),
+            approximate_messages: self.visible_count(),
+            approximate_messages_not_visible: self.in_flight_count(),
+            approximate_messages_delayed: self.delayed,
+            last_updated: Utc::now(),
+        }
+    }
+
+    fn visible_count(&self) -> i64 {
+        let now = Instant::now();
+        self.messages.iter().filter(|m| m.is_visible(now)).count() as i64
+    }
+
+    fn in_flight_count(&self) -> i64 {
+        self.messages.len() as i64 - self.visible_count()
+    }
+
+    fn details(&self) -> QueueDetails {
+        let mut details = self.details.clone();
+        let mut attributes = BTreeMap::new();
+        let mut insert = |name: &str, value: Option<String>| {
+            if let Some(value) = value {
+                attributes.insert(name.to_string(), value);
+            }
+        };
+        insert("QueueArn", details.arn.clone());
+        insert(
+            "CreatedTimestamp",
+            details.created_timestamp.map(|v| v.to_string()),
+        );
+        insert(
+            "LastModifiedTimestamp",
+            details.last_modified_timestamp.map(|v| v.to_string()),
+        );
+        insert(
+            "MessageRetentionPeriod",
+            details.message_retention_period.map(|v| v.to_string()),
+        );
+        insert(
+            "VisibilityTimeout",
+            details.visibility_timeout.map(|v| v.to_string()),
+        );
+        insert(
+            "MaximumMessageSize",
+            details.maximum_message_size.map(|v| v.to_string()),
+        );
+        insert("DelaySeconds", details.delay_seconds.map(|v| v.to_string()));
+        insert(
+            "ApproximateNumberOfMessages",
+            Some(self.visible_count().to_string()),
+        );
+        insert(
+            "ApproximateNumberOfMessagesNotVisible",
+            Some(self.in_flight_count().to_string()),
+        );
+        insert(
+            "ApproximateNumberOfMessagesDelayed",
+            Some(self.delayed.to_string()),
+        );
+        if details.fifo_queue {
+            insert("FifoQueue", Some("true".to_string()));
+            insert(
+                "ContentBasedDeduplication",
+                Some(details.content_based_deduplication.to_string()),
+            );
+            insert("DeduplicationScope", details.deduplication_scope.clone());
+            insert("FifoThroughputLimit", details.fifo_throughput_limit.clone());
+        }
+        attributes.extend(self.details.attributes.clone());
+        details.attributes = attributes;
+        details
+    }
+}
+
+#[derive(Default)]
+struct State {
+    queues: Vec<FakeQueue>,
+    failures: HashMap<Operation, VecDeque<String>>,
+    calls: HashMap<Operation, usize>,
+    latency: Duration,
+}
+
+/// An in-process stand-in for SQS with scriptable failures and latency.
+///
+/// Clones share state, so a test can keep a handle on the fake after handing
+/// a clone to `App`.
+#[derive(Clone, Default)]
+pub struct InMemoryBackend {
+    state: Arc<Mutex<State>>,
+}
+
+impl InMemoryBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A handful of queues in different states, used by `--demo`.
+    pub fn demo() -> Self {
+        let mut failed_order = FakeMessage::new(
+            "5f1c0a9e-7d2b-4c1e-9a43-2b8e6f0d1c77",
+            r#"{"orderId":"A-1042","status":"FAILED","error":"inventory timeout"}"#,
+        );
+        failed_order.receive_count = 5;
+        failed_order.message_attributes.insert(
+            "EventType".to_string(),
+            MessageAttribute {
+                data_type: "String".to_string(),
+                string_value: Some("OrderPlaced".to_string()),
+                binary_value: None,
+            },
+        );
+
+        // A day of traffic for the metrics panel, busier every third hour
+        let now = Utc::now();
+        let mut orders = FakeQueue::new("orders", 45).with_in_flight(3);
+        for minutes_ago in (0..24 * 60).step_by(5) {
+            let at = now - chrono::Duration::minutes(minutes_ago);
+            let sent = 40 + (minutes_ago % 180) as u64 / 4;
+            orders = orders
+                .with_activity(at, QueueMetric::NumberOfMessagesSent, sent)
+                .with_activity(at, QueueMetric::NumberOfMessagesReceived, sent + 3)
+                .with_activity(at, QueueMetric::NumberOfMessagesDeleted, sent - 1)
+                .with_activity(at, QueueMetric::NumberOfEmptyReceives, 6);
+        }
+
+        let backend = Self::new()
+            .with_queue(orders)
+            .with_queue(FakeQueue::new("orders-dlq", 6).with_message(failed_order))
+            .with_queue(FakeQueue::new("payments.fifo", 1_250))
+            .with_queue(FakeQueue::new("payments-dlq.fifo", 0))
+            .with_queue(FakeQueue::new("notifications", 0));
+        {
+            let mut state = backend.state.lock().unwrap();
+            if let Some(queue) = state.queues.iter_mut().find(|q| q.name == "orders") {
+                queue.details.attributes.insert(
+                    "RedrivePolicy".to_string(),
+                    r#"{"deadLetterTargetArn":"arn:aws:sqs:us-east-1:000000000000:orders-dlq","maxReceiveCount":5}"#
+                        .to_string(),
+                );
+            }
+            if let Some(queue) = state.queues.iter_mut().find(|q| q.name == "notifications") {
+                queue
+                    .details
+                    .attributes
+                    .insert("ReceiveMessageWaitTimeSeconds".to_string(), "0".to_string());
+            }
+        }
+        backend
+    }
+
+    pub fn with_queue(self, queue: FakeQueue) -> Self {
+        self.state.lock().unwrap().queues.push(queue);
+        self
+    }
+
+    pub fn with_latency(self, latency: Duration) -> Self {
+        self.state.lock().unwrap().latency = latency;
+        self
+    }
+
+    /// Makes the next call of `operation` fail with the AWS error `code`,
+    /// classified as `SqsClient` would; unknown codes become
+    /// `BackendError::Other(code)`. Repeated calls queue up failures for
+    /// subsequent calls.
+    #[allow(dead_code)]
+    pub fn fail_next(&self, operation: Operation, code: &str) {
+        self.state
+            .lock()
+            .unwrap()
+            .failures
+            .entry(operation)
+            .or_default()
+            .push_back(code.to_string());
+    }
+
+    /// How many times `operation` has been called, including failed calls.
+    #[allow(dead_code)]
+    pub fn calls(&self, operation: Operation) -> usize {
+        self.state
+            .lock()
+            .unwrap()
+            .calls
+            .get(&operation)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Records the call, waits out the configured latency and returns any
+    /// scripted failure.
+    async fn enter(&self, operation: Operation) -> BackendResult<()> {
+        let (latency, failure) = {
+            let mut state = self.state.lock().unwrap();
+            *state.calls.entry(operation).or_default() += 1;
+            let failure = state
+                .failures
+                .get_mut(&operation)
+                .and_then(|failures| failures.pop_front());
+            (state.latency, failure)
+        };
+
+        if !latency.is_zero() {
+            tokio::time::sleep(latency).await;
+        }
+
+        match failure {
+            Some(code) => Err(BackendError::from_code(&code, &code)),
+            None => Ok(()),
+        }
+    }
+
+    fn with_queue_by_url<T>(
+        &self,
+        url: &str,
+        f: impl FnOnce(&mut FakeQueue) -> T,
+    ) -> BackendResult<T> {
+        let mut state = self.state.lock().unwrap();
+        let queue = state
+            .queues
+            .iter_mut()
+            .find(|q| q.url() == url)
+            .ok_or_else(|| queue_not_found(url))?;
+        Ok(f(queue))
+    }
+
+    /// Receives the currently visible messages, oldest first.
+    fn receive_visible(
+        &self,
+        url: &str,
+        options: &ReceiveOptions,
+    ) -> BackendResult<Vec<QueueMessage>> {
+        self.with_queue_by_url(url, |queue| {
+            let now = Instant::now();
+            let timeout = options
+                .visibility_timeout
+                .or(queue.details.visibility_timeout)
+                .unwrap_or(30);
+            let invisible_until = now + Duration::from_secs(timeout.max(0) as u64);
+            let receive_time = Utc::now().timestamp_millis();
+
+            let mut received = Vec::new();
+            for index in 0..queue.messages.len() {
+                if received.len() >= options.max_messages.max(1) as usize {
+                    break;
+                }
+                if !queue.messages[index].is_visible(now) {
+                    continue;
+                }
+                queue.next_sequence += 1;
+                let handle = format!("{}-handle-{}", queue.name, queue.next_sequence);
+                let message = &mut queue.messages[index];
+                message.receive_count += 1;
+                message.first_receive_timestamp.get_or_insert(receive_time);
+                message.receipt_handle = Some(handle);
+                message.invisible_until = Some(invisible_until);
+                received.push(message.to_queue_message());
+            }
+            received
+        })
+    }
+}
+
+fn queue_not_found(queue: &str) -> BackendError {
+    BackendError::from_code(
+        "AWS.SimpleQueueService.NonExistentQueue",
+        &format!("The specified queue does not exist: {}", queue),
+    )
+}
+
+#[async_trait]
+impl QueueBackend for InMemoryBackend {
+    async fn list_queues(&self) -> BackendResult<Vec<QueueInfo>> {
+        self.enter(Operation::ListQueues).await?;
+        let state = self.state.lock().unwrap();
+        Ok(state.queues.iter().map(FakeQueue::info).collect())
+    }
+
+    async fn get_queue_details(&self, url: &str) -> BackendResult<QueueDetails> {
+        self.enter(Operation::GetQueueDetails).await?;
+        self.with_queue_by_url(url, |queue| queue.details())
+    }
+
+    async fn caller_identity(&self) -> BackendResult<CallerIdentity> {
+        self.enter(Operation::GetCallerIdentity).await?;
+        Ok(CallerIdentity {
+            account: Some("000000000000".to_string()),
+            arn: Some("arn:aws:iam::000000000000:user/demo".to_string()),
+            profile: None,
+            region: Some("us-east-1".to_string()),
+        })
+    }
+
+    /// Stands in for CloudWatch: counts are summed from recorded activity
+    /// per period, and the age is reported as of now in a single datapoint.
+    async fn queue_metric(
+        &self,
+        url: &str,
+        query: &MetricQuery,
+    ) -> BackendResult<Vec<MetricPoint>> {
+        self.enter(Operation::QueueMetric).await?;
+        self.with_queue_by_url(url, |queue| {
+            let period = chrono::Duration::seconds(query.period_secs.max(1).into());
+            if !query.metric.is_count() {
+                let Some(oldest) = queue.messages.iter().map(|m| m.sent_timestamp).min() else {
+                    return Vec::new();
+                };
+                return vec![MetricPoint {
+                    timestamp: query.end - period,
+                    value: (query.end.timestamp_millis() - oldest).max(0) as f64 / 1000.0,
+                }];
+            }
+            let mut sums: BTreeMap<DateTime<Utc>, f64> = BTreeMap::new();
+            for &(at, metric, count) in &queue.activity {
+                if metric != query.metric || at < query.start || at >= query.end {
+                    continue;
+                }
+                let periods = (at - query.start).num_seconds() / period.num_seconds();
+                let timestamp = query.start + period * periods as i32;
+                *sums.entry(timestamp).or_default() += count as f64;
+            }
+            sums.into_iter()
+                .map(|(timestamp, value)| MetricPoint { timestamp, value })
+                .collect()
+        })
+    }
+
+    async fn purge_queue(&self, url: &str) -> BackendResult<()> {
+        self.enter(Operation::PurgeQueue).await?;
+        self.with_queue_by_url(url, |queue| {
+            let now = tokio::time::Instant::now();
+            if queue
+                .last_purge
+                .is_some_and(|last| now < last + PURGE_COOLDOWN)
+            {
+                return Err(BackendError::PurgeInProgress);
+            }
+            queue.last_purge = Some(now);
+            queue.messages.clear();
+            queue.delayed = 0;
+            Ok(())
+        })?
+    }
+
+    async fn queue_url(&self, name_or_url: &str) -> BackendResult<String> {
+        if is_queue_url(name_or_url) {
+            return Ok(name_or_url.to_string());
+        }
+        let state = self.state.lock().unwrap();
+        state
+            .queues
+            .iter()
+            .find(|q| q.name == name_or_url)
+            .map(FakeQueue::url)
+            .ok_or_else(|| queue_not_found(name_or_url))
+    }
+
+    async fn receive_messages(
+        &self,
+        url: &str,
+        options: ReceiveOptions,
+    ) -> BackendResult<Vec<QueueMessage>> {
+        self.enter(Operation::ReceiveMessages).await?;
+        // Long polls wait for a message to become visible, like SQS does
+        let deadline = tokio::time::Instant::now()
+            + Duration::from_secs(options.wait_time_seconds.max(0) as u64);
+        loop {
+            let received = self.receive_visible(url, &options)?;
+            let now = tokio::time::Instant::now();
+            if !received.is_empty() || now >= deadline {
+                self.with_queue_by_url(url, |queue| match received.len() {
+                    0 => queue.record(QueueMetric::NumberOfEmptyReceives, 1),
+                    n => queue.record(QueueMetric::NumberOfMessagesReceived, n as u64),
+                })?;
+                return Ok(received);
+            }
+            tokio::time::sleep(LONG_POLL_INTERVAL.min(deadline - now)).await;
+        }
+    }
+
+    async fn delete_messages(
+        &self,
+        url: &str,
+        entries: &[DeleteEntry],
+    ) -> BackendResult<Vec<BatchFailure>> {
+        self.enter(Operation::DeleteMessages).await?;
+        self.with_queue_by_url(url, |queue| {
+            let mut failures = Vec::new();
+            for entry in entries {
+                let position = queue.messages.iter().position(|m| {
+                    m.receipt_handle.as_deref() == Some(entry.receipt_handle.as_str())
+                });
+                match position {
+                    Some(position) => {
+                        queue.messages.remove(position);
+                    }
+                    None => failures.push(BatchFailure {
+                        id: entry.id.clone(),
+                        code: "ReceiptHandleIsInvalid".to_string(),
+                        message: "The receipt handle is not valid".to_string(),
+                    }),
+                }
+            }
+            queue.record(
+                QueueMetric::NumberOfMessagesDeleted,
+                (entries.len() - failures.len()) as u64,
+            );
+            failures
+        })
+    }
+
+    async fn change_message_visibility(
+        &self,
+        url: &str,
+        entries: &[VisibilityEntry],
+    ) -> BackendResult<Vec<BatchFailure>> {
+        self.enter(Operation::ChangeMessageVisibility).await?;
+        self.with_queue_by_url(url, |queue| {
+            let now = Instant::now();
+            let mut failures = Vec::new();
+            for entry in entries {
+                let message = queue
+                    .messages
+                    .iter_mut()
+                    .find(|m| m.receipt_handle.as_deref() == Some(entry.receipt_handle.as_str()));
+                match message {
+                    Some(message) if !message.is_visible(now) => {
+                        let timeout = Duration::from_secs(entry.visibility_timeout.max(0) as u64);
+                        message.invisible_until = Some(now + timeout);
+                    }
+                    Some(_) => failures.push(BatchFailure {
+                        id: entry.id.clone(),
+                        code: "MessageNotInflight".to_string(),
+                        message: "The message is not in flight".to_string(),
+                    }),
+                    None => failures.push(BatchFailure {
+                        id: entry.id.clone(),
+                        code: "ReceiptHandleIsInvalid".to_string(),
+                        message: "The receipt handle is not valid".to_string(),
+                    }),
+                }
+            }
+            failures
+        })
+    }
+
+    async fn send_messages(
+        &self,
+        url: &str,
+        messages: &[OutgoingMessage],
+    ) -> BackendResult<SendBatchResult> {
+        self.enter(Operation::SendMessages).await?;
+        self.with_queue_by_url(url, |queue| {
+            let mut result = SendBatchResult::default();
+            for outgoing in messages {
+                if queue.details.fifo_queue && outgoing.message_group_id.is_none() {
+                    result.failed.push(BatchFailure {
+                        id: outgoing.id.clone(),
+                        code: "MissingParameter".to_string(),
+                        message: "The request must contain the parameter MessageGroupId."
+                            .to_string(),
+                    });
+                    continue;
+                }
+
+                let message_id = queue.next_message_id();
+                let mut message = FakeMessage::new(&message_id, &outgoing.body);
+                message.message_attributes = outgoing.message_attributes.clone();
+                if let Some(group) = &outgoing.message_group_id {
+                    message
+                        .attributes
+                        .insert("MessageGroupId".to_string(), group.clone());
+                }
+                if let Some(dedup) = &outgoing.message_deduplication_id {
+                    message
+                        .attributes
+                        .insert("MessageDeduplicationId".to_string(), dedup.clone());
+                }
+                queue.messages.push(message);
+                result.sent.push(SentMessage {
+                    id: outgoing.id.clone(),
+                    message_id,
+                });
+            }
+            queue.record(QueueMetric::NumberOfMessagesSent, result.sent.len() as u64);
+            result
+        })
+    }
+}
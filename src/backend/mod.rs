@@ -0,0 +1,109 @@
+pub mod audited;
+pub mod error;
+pub mod memory;
+pub mod retry;
+
+pub use error::{BackendError, BackendResult};
+
+use crate::types::{
+    BatchFailure, CallerIdentity, DeleteEntry, MetricPoint, MetricQuery, OutgoingMessage,
+    QueueDetails, QueueInfo, QueueMessage, ReceiveOptions, SendBatchResult, VisibilityEntry,
+};
+use async_trait::async_trait;
+use std::time::Duration;
+
+/// SQS accepts one PurgeQueue call per queue every 60 seconds.
+pub const PURGE_COOLDOWN: Duration = Duration::from_secs(60);
+/// The most messages one ReceiveMessage call returns, and the most entries
+/// a batch request accepts.
+pub const MAX_BATCH: usize = 10;
+
+/// The queue operations the app needs, independent of where queues live.
+///
+/// `SqsClient` talks to AWS; `memory::InMemoryBackend` keeps everything in
+/// process for tests and `--demo` runs.
+#[async_trait]
+pub trait QueueBackend: Send + Sync {
+    async fn list_queues(&self) -> BackendResult<Vec<QueueInfo>>;
+
+    async fn get_queue_details(&self, url: &str) -> BackendResult<QueueDetails>;
+
+    /// Fails with `BackendError::PurgeInProgress` while the queue is in its purge
+    /// cooldown.
+    async fn purge_queue(&self, url: &str) -> BackendResult<()>;
+
+    /// Who calls are made as (STS GetCallerIdentity plus profile and region).
+    async fn caller_identity(&self) -> BackendResult<CallerIdentity>;
+
+    /// Looks up a queue URL by name. Values that already look like a URL are
+    /// returned unchanged.
+    async fn queue_url(&self, name_or_url: &str) -> BackendResult<String>;
+
+    async fn receive_messages(
+        &self,
+        url: &str,
+        options: ReceiveOptions,
+    ) -> BackendResult<Vec<QueueMessage>>;
+
+    /// Deletes up to ten messages, returning the entries that failed.
+    async fn delete_messages(
+        &self,
+        url: &str,
+        entries: &[DeleteEntry],
+    ) -> BackendResult<Vec<BatchFailure>>;
+
+    /// Changes the visibility timeout of up to ten in-flight messages,
+    /// returning the entries that failed.
+    async fn change_message_visibility(
+        &self,
+        url: &str,
+        entries: &[VisibilityEntry],
+    ) -> BackendResult<Vec<BatchFailure>>;
+
+    /// Sends up to ten messages in one SendMessageBatch call.
+    async fn send_messages(
+        &self,
+        url: &str,
+        messages: &[OutgoingMessage],
+    ) -> BackendResult<SendBatchResult>;
+
+    /// Datapoints of a CloudWatch queue metric, oldest first. Periods with
+    /// no data are left out, so an idle queue may return nothing.
+    async fn queue_metric(&self, url: &str, query: &MetricQuery)
+    -> BackendResult<Vec<MetricPoint>>;
+
+    /// The latest audit entry that could not be written since the last
+    /// call, for the caller to report. Only `audited::AuditedBackend` keeps
+    /// an audit trail.
+    fn take_audit_failure(&self) -> Option<String> {
+        None
+    }
+}
+
+pub fn is_queue_url(value: &str) -> bool {
+    value.starts_with("https://") || value.starts_with("http://")
+}
+
+/// Total messages in a queue according to its Approximate* attributes:
+/// visible, in flight and delayed.
+pub fn approximate_total(details: &QueueDetails) -> Option<i64> {
+    let counts: Vec<i64> = [
+        "ApproximateNumberOfMessages",
+        "ApproximateNumberOfMessagesNotVisible",
+        "ApproximateNumberOfMessagesDelayed",
+    ]
+    .iter()
+    .filter_map(|name| attribute_count(details, name))
+    .collect();
+    (!counts.is_empty()).then(|| counts.iter().sum())
+}
+
+/// A numeric attribute such as `ApproximateNumberOfMessages`.
+pub fn attribute_count(details: &QueueDetails, name: &str) -> Option<i64> {
+    details.attributes.get(name)?.parse().ok()
+}
+
+/// The queue name is the last path segment of its URL.
+pub fn queue_name_from_url(url: &str) -> &str {
+    url.rsplit('/').next().unwrap_or("unknown")
+}
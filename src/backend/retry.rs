@@ -0,0 +1,235 @@
+use super::{BackendError, BackendResult, QueueBackend};
+use crate::types::{
+    BatchFailure, CallerIdentity, DeleteEntry, MetricPoint, MetricQuery, OutgoingMessage,
+    QueueDetails, QueueInfo, QueueMessage, ReceiveOptions, SendBatchResult, VisibilityEntry,
+};
+use async_trait::async_trait;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    /// Attempts in total, including the first.
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 4,
+            base_delay: Duration::from_millis(250),
+            max_delay: Duration::from_secs(8),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// "Full jitter": a random delay up to the exponential backoff for this
+    /// retry, so clients throttled together do not retry together.
+    fn delay(&self, retry: u32) -> Duration {
+        let backoff = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(retry))
+            .min(self.max_delay);
+        let random = RandomState::new().build_hasher().finish();
+        backoff.mul_f64(random as f64 / u64::MAX as f64)
+    }
+}
+
+/// Which failures a call may be repeated after.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Retry {
+    /// Reads and idempotent writes: throttling and transient failures.
+    Transient,
+    /// Sends: only throttling, which AWS rejects before doing anything. A
+    /// timed-out send may have gone through, and repeating it would
+    /// duplicate the message.
+    ThrottlingOnly,
+}
+
+/// Wraps another backend and retries throttled and transient failures with
+/// jittered exponential backoff. The AWS SDK's own retries are switched off
+/// in `SqsClient` so attempts do not multiply.
+pub struct RetryingBackend {
+    inner: Box<dyn QueueBackend>,
+    policy: RetryPolicy,
+}
+
+impl RetryingBackend {
+    pub fn new(inner: Box<dyn QueueBackend>, policy: RetryPolicy) -> Self {
+        Self { inner, policy }
+    }
+
+    async fn call<T, F, Fut>(&self, retry: Retry, op: F) -> BackendResult<T>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = BackendResult<T>>,
+    {
+        let mut attempt = 1;
+        loop {
+            match op().await {
+                Err(e) if attempt < self.policy.max_attempts && retryable(retry, &e) => {
+                    tokio::time::sleep(self.policy.delay(attempt - 1)).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+fn retryable(retry: Retry, error: &BackendError) -> bool {
+    match retry {
+        Retry::Transient => error.is_transient(),
+        Retry::ThrottlingOnly => matches!(error, BackendError::Throttled(_)),
+    }
+}
+
+#[async_trait]
+impl QueueBackend for RetryingBackend {
+    async fn list_queues(&self) -> BackendResult<Vec<QueueInfo>> {
+        self.call(Retry::Transient, || self.inner.list_queues())
+            .await
+    }
+
+    async fn get_queue_details(&self, url: &str) -> BackendResult<QueueDetails> {
+        self.call(Retry::Transient, || self.inner.get_queue_details(url))
+            .await
+    }
+
+    async fn caller_identity(&self) -> BackendResult<CallerIdentity> {
+        self.call(Retry::Transient, || self.inner.caller_identity())
+            .await
+    }
+
+    async fn purge_queue(&self, url: &str) -> BackendResult<()> {
+        // A purge that timed out but landed fails the retry with
+        // PurgeInProgress, which is reported rather than retried.
+        self.call(Retry::Transient, || self.inner.purge_queue(url))
+            .await
+    }
+
+    async fn queue_url(&self, name_or_url: &str) -> BackendResult<String> {
+        self.call(Retry::Transient, || self.inner.queue_url(name_or_url))
+            .await
+    }
+
+    async fn queue_metric(
+        &self,
+        url: &str,
+        query: &MetricQuery,
+    ) -> BackendResult<Vec<MetricPoint>> {
+        self.call(Retry::Transient, || self.inner.queue_metric(url, query))
+            .await
+    }
+
+    async fn receive_messages(
+        &self,
+        url: &str,
+        options: ReceiveOptions,
+    ) -> BackendResult<Vec<QueueMessage>> {
+        self.call(Retry::Transient, || {
+            self.inner.receive_messages(url, options)
+        })
+        .await
+    }
+
+    async fn delete_messages(
+        &self,
+        url: &str,
+        entries: &[DeleteEntry],
+    ) -> BackendResult<Vec<BatchFailure>> {
+        self.call(Retry::Transient, || {
+            self.inner.delete_messages(url, entries)
+        })
+        .await
+    }
+
+    async fn change_message_visibility(
+        &self,
+        url: &str,
+        entries: &[VisibilityEntry],
+    ) -> BackendResult<Vec<BatchFailure>> {
+        self.call(Retry::Transient, || {
+            self.inner.change_message_visibility(url, entries)
+        })
+        .await
+    }
+
+    async fn send_messages(
+        &self,
+        url: &str,
+        messages: &[OutgoingMessage],
+    ) -> BackendResult<SendBatchResult> {
+        self.call(Retry::ThrottlingOnly, || {
+            self.inner.send_messages(url, messages)
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::memory::{FakeQueue, InMemoryBackend, Operation};
+
+    fn retrying(fake: &InMemoryBackend) -> RetryingBackend {
+        RetryingBackend::new(Box::new(fake.clone()), RetryPolicy::default())
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn throttling_is_retried_with_backoff() {
+        let fake = InMemoryBackend::new().with_queue(FakeQueue::new("orders", 1));
+        fake.fail_next(Operation::ListQueues, "ThrottlingException");
+        fake.fail_next(Operation::ListQueues, "ServiceUnavailable");
+        let backend = retrying(&fake);
+
+        let started = tokio::time::Instant::now();
+        assert_eq!(backend.list_queues().await.unwrap().len(), 1);
+        assert_eq!(fake.calls(Operation::ListQueues), 3);
+        // At most 250ms + 500ms of jittered backoff.
+        assert!(started.elapsed() <= Duration::from_millis(750));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn gives_up_after_max_attempts() {
+        let fake = InMemoryBackend::new();
+        for _ in 0..5 {
+            fake.fail_next(Operation::ListQueues, "RequestThrottled");
+        }
+        let backend = retrying(&fake);
+
+        let error = backend.list_queues().await.unwrap_err();
+        assert!(matches!(error, BackendError::Throttled(_)));
+        assert_eq!(fake.calls(Operation::ListQueues), 4);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn permanent_errors_are_not_retried() {
+        let fake = InMemoryBackend::new().with_queue(FakeQueue::new("orders", 1));
+        fake.fail_next(Operation::ListQueues, "AccessDenied");
+        let backend = retrying(&fake);
+
+        let error = backend.list_queues().await.unwrap_err();
+        assert!(matches!(error, BackendError::AccessDenied(_)));
+        assert_eq!(fake.calls(Operation::ListQueues), 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn sends_are_not_retried_after_transient_errors() {
+        let fake = InMemoryBackend::new().with_queue(FakeQueue::new("orders", 0));
+        fake.fail_next(Operation::SendMessages, "InternalError");
+        let backend = retrying(&fake);
+        let url = backend.queue_url("orders").await.unwrap();
+        let message = OutgoingMessage {
+            body: "hello".to_string(),
+            ..OutgoingMessage::default()
+        };
+
+        assert!(backend.send_messages(&url, &[message]).await.is_err());
+        assert_eq!(fake.calls(Operation::SendMessages), 1);
+    }
+}
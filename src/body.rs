@@ -0,0 +1,269 @@
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use flate2::read::GzDecoder;
+use serde_json::Value;
+use std::io::Read;
+
+/// Encoding layers peeled off before giving up, so a body that decodes to
+/// itself cannot loop.
+const MAX_LAYERS: usize = 8;
+/// Cap on a decompressed body; SQS bodies are at most 256 KiB compressed.
+const MAX_DECOMPRESSED_BYTES: u64 = 16 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BodyFormat {
+    Json,
+    Xml,
+    Text,
+}
+
+impl BodyFormat {
+    pub fn label(self) -> &'static str {
+        match self {
+            BodyFormat::Json => "JSON",
+            BodyFormat::Xml => "XML",
+            BodyFormat::Text => "text",
+        }
+    }
+}
+
+/// A message body decoded for display.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodedBody {
+    /// Encodings and envelopes removed, outermost first, e.g. `["SNS",
+    /// "gzip+base64"]`.
+    pub layers: Vec<&'static str>,
+    pub format: BodyFormat,
+    /// Pretty-printed when the format allows it.
+    pub text: String,
+}
+
+/// Detects JSON, XML, base64 and gzip+base64 bodies, decoding until the
+/// payload is reached. With `unwrap_envelopes`, SNS notifications and
+/// EventBridge events are replaced by their `Message` or `detail`.
+pub fn decode(body: &str, unwrap_envelopes: bool) -> DecodedBody {
+    let mut layers = Vec::new();
+    let mut current = body.to_string();
+
+    while layers.len() < MAX_LAYERS {
+        let trimmed = current.trim();
+        if trimmed.starts_with(['{', '['])
+            && let Ok(json) = serde_json::from_str::<Value>(trimmed)
+        {
+            if unwrap_envelopes && let Some((label, inner)) = envelope(&json) {
+                layers.push(label);
+                current = inner;
+                continue;
+            }
+            let text = serde_json::to_string_pretty(&json).unwrap_or_else(|_| current.clone());
+            return DecodedBody {
+                layers,
+                format: BodyFormat::Json,
+                text,
+            };
+        }
+        if trimmed.starts_with('<') && trimmed.ends_with('>') {
+            return DecodedBody {
+                layers,
+                format: BodyFormat::Xml,
+                text: pretty_xml(trimmed),
+            };
+        }
+        match decode_base64(trimmed) {
+            Some((label, inner)) => {
+                layers.push(label);
+                current = inner;
+            }
+            None => break,
+        }
+    }
+
+    DecodedBody {
+        layers,
+        format: BodyFormat::Text,
+        text: current,
+    }
+}
+
+/// The payload inside an SNS notification or EventBridge event.
+fn envelope(json: &Value) -> Option<(&'static str, String)> {
+    if json.get("Type").and_then(Value::as_str) == Some("Notification")
+        && let Some(message) = json.get("Message").and_then(Value::as_str)
+    {
+        return Some(("SNS", message.to_string()));
+    }
+    if json.get("detail-type").is_some() && json.get("source").is_some() {
+        let detail = json.get("detail")?;
+        let inner = match detail {
+            Value::String(s) => s.clone(),
+            other => other.to_string(),
+        };
+        return Some(("EventBridge", inner));
+    }
+    None
+}
+
+/// Decodes standard base64 that holds gzip data or printable UTF-8 text.
+/// Anything else is taken to be text that happens to use the base64
+/// alphabet.
+fn decode_base64(text: &str) -> Option<(&'static str, String)> {
+    if text.len() < 8 || !text.len().is_multiple_of(4) {
+        return None;
+    }
+    let bytes = BASE64.decode(text).ok()?;
+    if bytes.starts_with(&[0x1f, 0x8b]) {
+        let mut inflated = String::new();
+        GzDecoder::new(bytes.as_slice())
+            .take(MAX_DECOMPRESSED_BYTES)
+            .read_to_string(&mut inflated)
+            .ok()?;
+        return Some(("gzip+base64", inflated));
+    }
+    let decoded = String::from_utf8(bytes).ok()?;
+    decoded
+        .chars()
+        .all(|c| !c.is_control() || c.is_whitespace())
+        .then_some(("base64", decoded))
+}
+
+/// Puts each tag on its own line, indented by depth. Elements holding only
+/// text stay on one line.
+fn pretty_xml(xml: &str) -> String {
+    let mut tokens = Vec::new();
+    let mut rest = xml;
+    while !rest.is_empty() {
+        let end = if rest.starts_with('<') {
+            rest.find('>').map_or(rest.len(), |i| i + 1)
+        } else {
+            rest.find('<').unwrap_or(rest.len())
+        };
+        let token = rest[..end].trim();
+        if !token.is_empty() {
+            tokens.push(token);
+        }
+        rest = &rest[end..];
+    }
+
+    let mut lines = Vec::new();
+    let mut depth = 0usize;
+    let mut i = 0;
+    while i < tokens.len() {
+        let token = tokens[i];
+        let indent = "  ".repeat(depth);
+        if token.starts_with("</") {
+            depth = depth.saturating_sub(1);
+            lines.push(format!("{}{}", "  ".repeat(depth), token));
+        } else if !token.starts_with('<')
+            || token.ends_with("/>")
+            || token.starts_with("<?")
+            || token.starts_with("<!")
+        {
+            lines.push(format!("{}{}", indent, token));
+        } else if i + 2 < tokens.len()
+            && !tokens[i + 1].starts_with('<')
+            && tokens[i + 2].starts_with("</")
+        {
+            lines.push(format!(
+                "{}{}{}{}",
+                indent,
+                token,
+                tokens[i + 1],
+                tokens[i + 2]
+            ));
+            i += 2;
+        } else {
+            lines.push(format!("{}{}", indent, token));
+            depth += 1;
+        }
+        i += 1;
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::Compression;
+    use flate2::write::GzEncoder;
+    use std::io::Write;
+
+    #[test]
+    fn pretty_prints_json_and_xml() {
+        let json = decode(r#"{"orderId":"A-1","items":[1,2]}"#, false);
+        assert_eq!(json.format, BodyFormat::Json);
+        assert!(json.layers.is_empty());
+        assert_eq!(
+            json.text,
+            "{\n  \"orderId\": \"A-1\",\n  \"items\": [\n    1,\n    2\n  ]\n}"
+        );
+
+        let xml = decode(
+            r#"<?xml version="1.0"?><order id="1"><item>book</item><empty/></order>"#,
+            false,
+        );
+        assert_eq!(xml.format, BodyFormat::Xml);
+        assert_eq!(
+            xml.text,
+            "<?xml version=\"1.0\"?>\n<order id=\"1\">\n  <item>book</item>\n  <empty/>\n</order>"
+        );
+
+        let text = decode("hello world", false);
+        assert_eq!(text.format, BodyFormat::Text);
+        assert_eq!(text.text, "hello world");
+    }
+
+    #[test]
+    fn keeps_numbers_as_written() {
+        let body = decode(
+            r#"{"id":123456789012345678901234567890,"price":0.1000000000000000055511151231257827}"#,
+            false,
+        );
+        assert_eq!(
+            body.text,
+            "{\n  \"id\": 123456789012345678901234567890,\n  \"price\": 0.1000000000000000055511151231257827\n}"
+        );
+    }
+
+    #[test]
+    fn decodes_base64_and_gzip() {
+        let encoded = BASE64.encode(r#"{"a":1}"#);
+        let body = decode(&encoded, false);
+        assert_eq!(body.layers, ["base64"]);
+        assert_eq!(body.format, BodyFormat::Json);
+
+        let mut gzip = GzEncoder::new(Vec::new(), Compression::default());
+        gzip.write_all(b"<a>1</a>").unwrap();
+        let body = decode(&BASE64.encode(gzip.finish().unwrap()), false);
+        assert_eq!(body.layers, ["gzip+base64"]);
+        assert_eq!(body.text, "<a>1</a>");
+
+        // Words in the base64 alphabet that decode to binary stay text.
+        assert_eq!(decode("testtest", false).format, BodyFormat::Text);
+    }
+
+    #[test]
+    fn unwraps_sns_and_eventbridge_envelopes() {
+        let inner = BASE64.encode(r#"{"orderId":"A-1"}"#);
+        let sns = serde_json::json!({
+            "Type": "Notification",
+            "TopicArn": "arn:aws:sns:us-east-1:000000000000:orders",
+            "Message": inner,
+        })
+        .to_string();
+        let body = decode(&sns, true);
+        assert_eq!(body.layers, ["SNS", "base64"]);
+        assert_eq!(body.text, "{\n  \"orderId\": \"A-1\"\n}");
+        assert!(decode(&sns, false).layers.is_empty());
+
+        let event = serde_json::json!({
+            "detail-type": "OrderPlaced",
+            "source": "shop",
+            "detail": { "orderId": "A-2" },
+        })
+        .to_string();
+        let wrapped_twice = serde_json::json!({ "Type": "Notification", "Message": event });
+        let body = decode(&wrapped_twice.to_string(), true);
+        assert_eq!(body.layers, ["SNS", "EventBridge"]);
+        assert_eq!(body.text, "{\n  \"orderId\": \"A-2\"\n}");
+    }
+}
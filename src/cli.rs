@@ -0,0 +1,244 @@
+use crate::filter::MessageFilter;
+use crate::jsonpath::JsonPath;
+use clap::{Args, Parser, Subcommand};
+use std::path::PathBuf;
+
+#[derive(Parser)]
+#[command(about = "Terminal monitor for Amazon SQS queues")]
+pub struct Cli {
+    /// Run against built-in fake queues instead of AWS
+    #[arg(long, global = true)]
+    pub demo: bool,
+
+    /// Artificial latency for every fake backend call, in milliseconds
+    #[arg(long, global = true, default_value_t = 0, requires = "demo")]
+    pub demo_latency_ms: u64,
+
+    /// Disable purging, deleting, hiding and sending messages
+    #[arg(long, global = true)]
+    pub read_only: bool,
+
+    /// AWS profile to use instead of the default credential chain
+    #[arg(long, global = true, conflicts_with = "demo")]
+    pub profile: Option<String>,
+
+    /// AWS region to use instead of the profile's or environment's
+    #[arg(long, global = true, conflicts_with = "demo")]
+    pub region: Option<String>,
+
+    /// Config file (defaults to <config dir>/sqs-monitor/config.toml)
+    #[arg(long, global = true)]
+    pub config: Option<PathBuf>,
+
+    /// Name of the queue a command changes, required for protected queues
+    /// whose name has to be typed when stdin is not a terminal
+    #[arg(long, global = true, value_name = "QUEUE")]
+    pub confirm: Option<String>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Dump queue messages to a JSONL archive
+    Export(ExportArgs),
+    /// Replay messages from a JSONL archive into a queue
+    Import(ImportArgs),
+    /// Long-poll a queue and print messages as they arrive
+    Tail(TailArgs),
+    /// Run a command for each message, deleting it when the command succeeds
+    Consume(ConsumeArgs),
+    /// Send messages from a JSONL or CSV file, or a body template
+    Send(SendArgs),
+    /// Check queue settings for common misconfigurations, exiting non-zero
+    /// when any are found
+    Lint(LintArgs),
+}
+
+impl Command {
+    /// The queue the command reads from or writes to, if it works on one.
+    pub fn queue(&self) -> Option<&str> {
+        match self {
+            Command::Export(args) => Some(&args.queue),
+            Command::Import(args) => Some(&args.queue),
+            Command::Tail(args) => Some(&args.queue),
+            Command::Consume(args) => Some(&args.queue),
+            Command::Send(args) => Some(&args.queue),
+            Command::Lint(_) => None,
+        }
+    }
+
+    /// Whether the command deletes, sends or hides messages.
+    pub fn mutates(&self) -> bool {
+        match self {
+            Command::Export(args) => args.delete,
+            Command::Import(args) => !args.dry_run,
+            Command::Tail(args) => args.delete || args.hold_repeats,
+            Command::Consume(_) | Command::Send(_) => true,
+            Command::Lint(_) => false,
+        }
+    }
+}
+
+#[derive(Args)]
+pub struct ExportArgs {
+    /// Queue name or URL
+    pub queue: String,
+
+    /// Archive to write (defaults to <queue>-<timestamp>.jsonl)
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+
+    /// Seconds received messages stay hidden while the export runs
+    #[arg(long, default_value_t = 300)]
+    pub visibility_timeout: i32,
+
+    /// Delete each batch once it has been written to the archive
+    #[arg(long)]
+    pub delete: bool,
+
+    /// Stop after this many messages
+    #[arg(long)]
+    pub max: Option<usize>,
+
+    /// Only export (and delete) messages matching this expression, e.g.
+    /// `.status == "FAILED" and receives >= 3`
+    #[arg(long)]
+    pub filter: Option<MessageFilter>,
+}
+
+#[derive(Args)]
+pub struct ImportArgs {
+    /// Queue name or URL to send to
+    pub queue: String,
+
+    /// Archive written by `export`
+    pub archive: PathBuf,
+
+    /// Maximum messages sent per second
+    #[arg(long, value_parser = parse_rate)]
+    pub rate: Option<f64>,
+
+    /// Report what would be sent without sending anything
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Replace each body with the value at this path, e.g. `.Message`
+    #[arg(long)]
+    pub transform: Option<JsonPath>,
+}
+
+#[derive(Args)]
+pub struct TailArgs {
+    /// Queue name or URL
+    pub queue: String,
+
+    /// Delete each message once printed instead of releasing it
+    #[arg(long)]
+    pub delete: bool,
+
+    /// Keep released messages that come round again hidden until the tail
+    /// stops, so each is received at most twice; consumers cannot see them
+    /// meanwhile
+    #[arg(long, conflicts_with = "delete")]
+    pub hold_repeats: bool,
+
+    /// Stop after this many messages
+    #[arg(long)]
+    pub max: Option<usize>,
+}
+
+#[derive(Args)]
+pub struct ConsumeArgs {
+    /// Queue name or URL
+    pub queue: String,
+
+    /// Shell command run once per message with the body on stdin and
+    /// attributes in SQS_* environment variables; its stdout goes to stderr
+    #[arg(long, value_name = "CMD")]
+    pub exec: String,
+
+    /// Commands run at once
+    #[arg(long, default_value_t = 1)]
+    pub concurrency: usize,
+
+    /// Stop after this many messages
+    #[arg(long)]
+    pub max: Option<usize>,
+
+    /// Seconds a message stays hidden while its command runs, renewed until
+    /// the command exits
+    #[arg(long, default_value_t = 60)]
+    pub visibility_timeout: i32,
+
+    /// Seconds before a message whose command failed is delivered again;
+    /// 0 releases it straight away
+    #[arg(long, default_value_t = 0)]
+    pub retry_delay: i32,
+}
+
+#[derive(Args)]
+pub struct SendArgs {
+    /// Queue name or URL
+    pub queue: String,
+
+    /// JSONL file (one body, or one {"body": ...} object, per line) or CSV
+    /// file with a `body` column
+    #[arg(
+        long,
+        required_unless_present = "template",
+        conflicts_with = "template"
+    )]
+    pub file: Option<PathBuf>,
+
+    /// Body to send, with {n} replaced by the message number and {now} by
+    /// the current time
+    #[arg(long)]
+    pub template: Option<String>,
+
+    /// Messages to send; the file repeats until this many are sent
+    /// (defaults to the file's length, or one for a template)
+    #[arg(long)]
+    pub count: Option<usize>,
+
+    /// Maximum messages sent per second
+    #[arg(long, value_parser = parse_rate)]
+    pub rate: Option<f64>,
+}
+
+/// A messages-per-second limit: a positive, finite number. Leave the flag
+/// out for no limit.
+fn parse_rate(value: &str) -> Result<f64, String> {
+    let rate: f64 = value.parse().map_err(|e| format!("{}", e))?;
+    if rate.is_finite() && rate > 0.0 {
+        Ok(rate)
+    } else {
+        Err("must be a positive number of messages per second".to_string())
+    }
+}
+
+#[derive(Args)]
+pub struct LintArgs {
+    /// Queue name patterns to report on, e.g. `prod-*` (defaults to every
+    /// queue)
+    pub queues: Vec<String>,
+
+    /// Seconds consumers may spend on a message, overriding the config
+    /// file's `[lint] consumer_timeout`
+    #[arg(long)]
+    pub consumer_timeout: Option<i64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rates_must_be_positive_and_finite() {
+        assert_eq!(parse_rate("2.5"), Ok(2.5));
+        for rate in ["0", "-1", "NaN", "inf", "fast"] {
+            assert!(parse_rate(rate).is_err(), "{} accepted", rate);
+        }
+    }
+}
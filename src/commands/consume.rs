@@ -0,0 +1,115 @@
+use crate::backend::QueueBackend;
+use crate::cli::ConsumeArgs;
+use crate::consume::{ConsumeOptions, Consumer};
+use anyhow::{Result, bail};
+use std::sync::Arc;
+
+/// Ctrl-C, or SIGTERM from a process supervisor.
+struct StopSignal {
+    #[cfg(unix)]
+    terminate: tokio::signal::unix::Signal,
+}
+
+impl StopSignal {
+    fn new() -> Result<Self> {
+        Ok(StopSignal {
+            #[cfg(unix)]
+            terminate: tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())?,
+        })
+    }
+
+    async fn recv(&mut self) {
+        #[cfg(unix)]
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => {}
+            _ = self.terminate.recv() => {}
+        }
+        #[cfg(not(unix))]
+        let _ = tokio::signal::ctrl_c().await;
+    }
+}
+
+/// Prints one JSON outcome per message on stdout and a summary on stderr.
+/// Ctrl-C, SIGTERM or a failed receive stops receiving and waits for
+/// running commands; a second signal stops them too.
+pub async fn run(backend: Arc<dyn QueueBackend>, args: ConsumeArgs) -> Result<()> {
+    let queue_url = backend.queue_url(&args.queue).await?;
+    let options = ConsumeOptions {
+        command: args.exec,
+        concurrency: args.concurrency,
+        max_messages: args.max,
+        visibility_timeout: args.visibility_timeout,
+        retry_delay: args.retry_delay,
+    };
+
+    let mut stop = StopSignal::new()?;
+    let mut consumer = Consumer::new(backend, &queue_url, options);
+    let mut receive_error = None;
+    // Set once receiving stopped because of a signal or an error, so only
+    // a signal after that kills the running commands
+    let mut stopping = false;
+    while !consumer.is_done() {
+        tokio::select! {
+            started = consumer.receive() => {
+                if let Err(e) = started {
+                    eprintln!(
+                        "Receive failed: {}; waiting for {} running commands (Ctrl-C to kill them)",
+                        e,
+                        consumer.running()
+                    );
+                    receive_error = Some(e);
+                    stopping = true;
+                    break;
+                }
+            }
+            _ = stop.recv() => {
+                eprintln!(
+                    "Stopping; waiting for {} running commands (Ctrl-C again to kill them)",
+                    consumer.running()
+                );
+                stopping = true;
+                break;
+            }
+        }
+        for outcome in consumer.finished() {
+            println!("{}", serde_json::to_string(&outcome)?);
+        }
+    }
+
+    loop {
+        tokio::select! {
+            outcome = consumer.next_finished() => match outcome {
+                Some(outcome) => println!("{}", serde_json::to_string(&outcome)?),
+                None => break,
+            },
+            _ = stop.recv() => {
+                if !stopping {
+                    eprintln!(
+                        "Stopping; waiting for {} running commands (Ctrl-C again to kill them)",
+                        consumer.running()
+                    );
+                    stopping = true;
+                    continue;
+                }
+                // Dropping the consumer kills the commands; their messages
+                // reappear once their visibility timeout runs out
+                bail!("Killed {} running commands", consumer.running());
+            }
+        }
+    }
+
+    eprintln!(
+        "{} messages received: {} deleted, {} released, {} failed",
+        consumer.received, consumer.deleted, consumer.released, consumer.failed
+    );
+    if let Some(e) = receive_error {
+        return Err(e.into());
+    }
+    if consumer.failed > 0 {
+        bail!(
+            "{} messages could not be deleted or released",
+            consumer.failed
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,56 @@
+use crate::archive::{ExportOptions, Exporter, timestamped_path};
+use crate::backend::{QueueBackend, queue_name_from_url};
+use crate::cli::ExportArgs;
+use anyhow::Result;
+use std::io::Write;
+use std::path::Path;
+
+pub async fn run(backend: &dyn QueueBackend, args: ExportArgs) -> Result<()> {
+    let queue_url = backend.queue_url(&args.queue).await?;
+    let path = args
+        .output
+        .unwrap_or_else(|| timestamped_path(Path::new("."), queue_name_from_url(&queue_url)));
+    let options = ExportOptions {
+        visibility_timeout: args.visibility_timeout,
+        delete_after_write: args.delete,
+        max_messages: args.max,
+        filter: args.filter,
+    };
+
+    let mut exporter = Exporter::create(&path, &queue_url, options)?;
+    let mut stderr = std::io::stderr();
+    while !exporter.is_done() {
+        exporter.step(backend).await?;
+        write!(stderr, "\rExported {} messages", exporter.exported)?;
+        if args.delete {
+            write!(stderr, ", deleted {}", exporter.deleted)?;
+        }
+        stderr.flush()?;
+    }
+    if exporter.skipped > 0 {
+        write!(
+            stderr,
+            ", skipped {} that did not match the filter",
+            exporter.skipped
+        )?;
+    }
+    writeln!(stderr)?;
+    if exporter.stopped_on_repeats() {
+        eprintln!(
+            "Stopped early: only already-exported messages were coming back. \
+             The queue may hold more; retry with a longer --visibility-timeout"
+        );
+    }
+
+    if !exporter.delete_failures.is_empty() {
+        eprintln!(
+            "{} messages were written but could not be deleted; they will become visible again",
+            exporter.delete_failures.len()
+        );
+        for failure in &exporter.delete_failures {
+            eprintln!("  {}: {} {}", failure.id, failure.code, failure.message);
+        }
+    }
+    println!("{}", exporter.path().display());
+    Ok(())
+}
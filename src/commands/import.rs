@@ -0,0 +1,40 @@
+use crate::archive::read_archive;
+use crate::backend::QueueBackend;
+use crate::cli::ImportArgs;
+use crate::replay::{ReplayOptions, Replayer};
+use anyhow::{Result, bail};
+
+/// Prints one JSON outcome per message on stdout and a summary on stderr.
+pub async fn run(backend: &dyn QueueBackend, args: ImportArgs) -> Result<()> {
+    let queue_url = backend.queue_url(&args.queue).await?;
+    let records = read_archive(&args.archive)?;
+    let options = ReplayOptions {
+        rate_per_second: args.rate,
+        dry_run: args.dry_run,
+        transform: args.transform,
+    };
+
+    let mut replayer = Replayer::new(&queue_url, records, options);
+    while !replayer.is_done() {
+        for outcome in replayer.step(backend).await? {
+            println!("{}", serde_json::to_string(&outcome)?);
+        }
+    }
+
+    let (count, verb) = if args.dry_run {
+        (replayer.would_send, "would be sent")
+    } else {
+        (replayer.sent, "sent")
+    };
+    eprintln!(
+        "{} of {} messages {}, {} failed",
+        count,
+        replayer.total(),
+        verb,
+        replayer.failed
+    );
+    if replayer.failed > 0 {
+        bail!("{} messages could not be replayed", replayer.failed);
+    }
+    Ok(())
+}
@@ -0,0 +1,65 @@
+use crate::backend::QueueBackend;
+use crate::cli::LintArgs;
+use crate::config::{LintConfig, glob_match};
+use crate::lint::lint_queues;
+use anyhow::{Result, bail};
+
+/// Prints one line per finding on stdout, followed by how to fix it, and
+/// fails when there are any, or when a queue could not be read, so CI jobs
+/// can gate on it.
+pub async fn run(backend: &dyn QueueBackend, args: LintArgs, mut config: LintConfig) -> Result<()> {
+    if args.consumer_timeout.is_some() {
+        config.consumer_timeout = args.consumer_timeout;
+    }
+    // Every queue is read so dead-letter queues are recognised even when
+    // only their sources are reported on.
+    let queues = backend.list_queues().await?;
+    let report = lint_queues(backend, &queues, &config).await;
+    let wanted =
+        |name: &str| args.queues.is_empty() || args.queues.iter().any(|p| glob_match(p, name));
+
+    let mut unreadable = 0;
+    for (name, e) in &report.errors {
+        if wanted(name) {
+            unreadable += 1;
+            eprintln!("{}: not checked: {}", name, e);
+        }
+    }
+
+    let mut checked = 0;
+    let mut found = 0;
+    let mut failing = 0;
+    for (name, findings) in &report.findings {
+        if !wanted(name) {
+            continue;
+        }
+        checked += 1;
+        if !findings.is_empty() {
+            failing += 1;
+        }
+        for finding in findings {
+            found += 1;
+            println!(
+                "{}: {}[{}] {}",
+                name,
+                finding.rule.severity(),
+                finding.rule.id(),
+                finding.message
+            );
+            println!("    {}", finding.rule.advice());
+        }
+    }
+
+    if checked + unreadable == 0 {
+        bail!("No queues match {}", args.queues.join(", "));
+    }
+    if found > 0 || unreadable > 0 {
+        let mut summary = format!("{} findings in {} of {} queues", found, failing, checked);
+        if unreadable > 0 {
+            summary.push_str(&format!(", {} more could not be read", unreadable));
+        }
+        bail!(summary);
+    }
+    eprintln!("{} queues checked, no findings", checked);
+    Ok(())
+}
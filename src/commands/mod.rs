@@ -0,0 +1,102 @@
+pub mod consume;
+pub mod export;
+pub mod import;
+pub mod lint;
+pub mod send;
+pub mod tail;
+
+use crate::config::{Config, Protection};
+use anyhow::{Result, bail};
+use std::io::{BufRead, Write};
+
+/// Checks a command that changes `queue_name` against read-only mode and
+/// the queue's protection. A queue whose name has to be typed needs
+/// `--confirm <name>`, or the name typed at a prompt when `interactive`.
+pub fn check_allowed(
+    config: &Config,
+    read_only: bool,
+    queue_name: &str,
+    confirm: Option<&str>,
+    interactive: bool,
+) -> Result<()> {
+    if read_only {
+        bail!("Refusing to modify '{}' in read-only mode", queue_name);
+    }
+    let Some(protection) = config.protection_for(queue_name) else {
+        return Ok(());
+    };
+    match protection.protection {
+        Protection::Block => bail!("Cannot modify '{}': {}", queue_name, protection.reason()),
+        Protection::TypeName => {
+            let typed = match confirm {
+                Some(name) => name.to_string(),
+                None if interactive => {
+                    eprint!(
+                        "'{}' is a {}. Type its name to continue: ",
+                        queue_name,
+                        protection.reason()
+                    );
+                    std::io::stderr().flush()?;
+                    let mut line = String::new();
+                    std::io::stdin().lock().read_line(&mut line)?;
+                    line.trim().to_string()
+                }
+                None => bail!(
+                    "'{}' is a {}: pass --confirm {} to modify it",
+                    queue_name,
+                    protection.reason(),
+                    queue_name
+                ),
+            };
+            if typed != queue_name {
+                bail!(
+                    "'{}' does not match '{}', nothing changed",
+                    typed,
+                    queue_name
+                );
+            }
+        }
+        Protection::Confirm => {}
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn protected_queues_need_their_name_confirmed() {
+        let config: Config = toml::from_str(
+            r#"
+            [[protected_queues]]
+            pattern = "prod-*"
+
+            [[protected_queues]]
+            pattern = "*-audit"
+            protection = "block"
+            "#,
+        )
+        .unwrap();
+
+        assert!(check_allowed(&config, false, "dev-orders", None, false).is_ok());
+        assert!(check_allowed(&config, true, "dev-orders", None, false).is_err());
+        assert!(check_allowed(&config, false, "prod-orders", Some("prod-orders"), false).is_ok());
+        let refused = check_allowed(&config, false, "prod-orders", None, false).unwrap_err();
+        assert_eq!(
+            refused.to_string(),
+            "'prod-orders' is a protected queue: pass --confirm prod-orders to modify it"
+        );
+        assert!(check_allowed(&config, false, "prod-orders", Some("prod-order"), false).is_err());
+        assert!(
+            check_allowed(
+                &config,
+                false,
+                "billing-audit",
+                Some("billing-audit"),
+                false
+            )
+            .is_err()
+        );
+    }
+}
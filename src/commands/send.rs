@@ -0,0 +1,47 @@
+use crate::backend::QueueBackend;
+use crate::cli::SendArgs;
+use crate::send::{MessageSource, SendOptions, Sender};
+use anyhow::{Result, bail};
+use std::io::Write;
+
+/// Reports progress and throughput on stderr as batches go out, with a line
+/// for each message that failed.
+pub async fn run(backend: &dyn QueueBackend, args: SendArgs) -> Result<()> {
+    let queue_url = backend.queue_url(&args.queue).await?;
+    let source = match (&args.file, args.template) {
+        (Some(path), _) => MessageSource::read(path)?,
+        (None, Some(template)) => MessageSource::Template(template),
+        (None, None) => bail!("Give --file or --template"),
+    };
+    let options = SendOptions {
+        count: args.count,
+        rate_per_second: args.rate,
+    };
+
+    let mut sender = Sender::new(&queue_url, source, options);
+    let mut stderr = std::io::stderr();
+    while !sender.is_done() {
+        for failure in sender.step(backend).await {
+            writeln!(
+                stderr,
+                "\rMessage {} failed: {}: {}",
+                failure.id, failure.code, failure.message
+            )?;
+        }
+        write!(
+            stderr,
+            "\rSent {}/{}, {} failed, {:.1} messages/s",
+            sender.sent,
+            sender.total(),
+            sender.failed,
+            sender.throughput()
+        )?;
+        stderr.flush()?;
+    }
+    writeln!(stderr)?;
+
+    if sender.failed > 0 {
+        bail!("{} messages could not be sent", sender.failed);
+    }
+    Ok(())
+}
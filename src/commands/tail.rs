@@ -0,0 +1,107 @@
+use crate::backend::{QueueBackend, queue_name_from_url};
+use crate::cli::TailArgs;
+use crate::tail::{ArrivalRate, Tail, TailMode, redrive_warning, summary};
+use anyhow::Result;
+use std::io::Write;
+use std::time::Duration;
+use tokio::time::Instant;
+
+const BODY_PREVIEW_CHARS: usize = 100;
+/// How often the arrival rate is printed while messages keep coming.
+const RATE_REPORT_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Prints one summary line per new message on stdout until Ctrl-C, `--max`
+/// or a failed poll, with progress, the arrival rate and failures on
+/// stderr. Held messages are released however it stops.
+pub async fn run(backend: &dyn QueueBackend, args: TailArgs) -> Result<()> {
+    let queue_url = backend.queue_url(&args.queue).await?;
+    let mode = if args.delete {
+        TailMode::Delete
+    } else {
+        TailMode::Release
+    };
+    eprintln!(
+        "Tailing '{}' ({} messages), Ctrl-C to stop",
+        queue_name_from_url(&queue_url),
+        mode
+    );
+    if mode == TailMode::Release
+        && let Ok(details) = backend.get_queue_details(&queue_url).await
+        && let Some(warning) = redrive_warning(&details, args.hold_repeats)
+    {
+        eprintln!("Warning: {}", warning);
+    }
+
+    let mut tail = Tail::new(&queue_url, mode);
+    if args.hold_repeats {
+        tail = tail.holding_repeats();
+    }
+    let started = Instant::now();
+    let mut rate = ArrivalRate::new(started);
+    let mut last_report = started;
+    let mut stdout = std::io::stdout();
+    let mut shown = 0;
+    let mut poll_error = None;
+    loop {
+        let remaining = args.max.map_or(usize::MAX, |max| max - shown);
+        if remaining == 0 {
+            break;
+        }
+        // Stopping mid-poll leaves what was received hidden until its
+        // visibility timeout runs out; nothing is lost.
+        let batch = tokio::select! {
+            batch = tail.poll(backend, remaining) => match batch {
+                Ok(batch) => batch,
+                Err(e) => {
+                    poll_error = Some(e);
+                    break;
+                }
+            },
+            _ = tokio::signal::ctrl_c() => break,
+        };
+        for message in &batch.arrived {
+            writeln!(stdout, "{}", summary(message, BODY_PREVIEW_CHARS))?;
+        }
+        stdout.flush()?;
+        shown += batch.arrived.len();
+        for failure in &batch.failed {
+            eprintln!("  {}: {} {}", failure.id, failure.code, failure.message);
+        }
+
+        let now = Instant::now();
+        rate.record(now, batch.arrived.len());
+        if now.duration_since(last_report) >= RATE_REPORT_INTERVAL {
+            last_report = now;
+            let per_second = rate.per_second(now);
+            eprintln!(
+                "{} messages, {:.1}/s ({:.0}/min)",
+                shown,
+                per_second,
+                per_second * 60.0
+            );
+        }
+
+        if let Some(pause) = tail.backoff(&batch) {
+            tokio::select! {
+                _ = tokio::time::sleep(pause) => {}
+                _ = tokio::signal::ctrl_c() => break,
+            }
+        }
+    }
+
+    let released = tail.release_held(backend).await?;
+    if released > 0 {
+        eprintln!("Released {} messages held while tailing", released);
+    }
+    let per_second = rate.per_second(Instant::now());
+    eprintln!(
+        "{} messages, {:.1}/s ({:.0}/min) over the last minute",
+        shown,
+        per_second,
+        per_second * 60.0
+    );
+    if let Some(e) = poll_error {
+        return Err(e.into());
+    }
+    Ok(())
+}
@@ -0,0 +1,247 @@
+use crate::lint::Rule;
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+/// User settings read from `config.toml`.
+///
+/// ```toml
+/// backup_dir = "/var/backups/sqs"
+/// audit_log = "/var/log/sqs-monitor/audit.jsonl"
+///
+/// [[protected_queues]]
+/// pattern = "prod-*"
+/// protection = "type-name"
+/// reason = "production traffic"
+/// backup_before_purge = true
+///
+/// [[protected_queues]]
+/// pattern = "*-audit"
+/// protection = "block"
+///
+/// [lint]
+/// consumer_timeout = 90
+/// ignore = ["short-polling"]
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Where purge backups are written. Defaults to the platform data
+    /// directory, e.g. `~/.local/share/sqs-monitor/backups`.
+    pub backup_dir: Option<PathBuf>,
+    /// Where purges, deletes and sends are recorded. Defaults to
+    /// `audit.jsonl` in the platform data directory.
+    pub audit_log: Option<PathBuf>,
+    pub protected_queues: Vec<ProtectedQueue>,
+    pub lint: LintConfig,
+}
+
+/// Settings for the queue configuration linter.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LintConfig {
+    /// Seconds consumers may spend on a message; queues whose visibility
+    /// timeout is shorter are flagged. Unchecked when unset.
+    pub consumer_timeout: Option<i64>,
+    /// Rules not to report, by id.
+    pub ignore: Vec<Rule>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProtectedQueue {
+    /// Queue name pattern; `*` matches any run of characters, `?` one.
+    pub pattern: String,
+    #[serde(default)]
+    pub protection: Protection,
+    /// Shown in the status bar when the protection kicks in.
+    #[serde(default)]
+    pub reason: Option<String>,
+    /// Drain matching queues into a local archive before every purge.
+    #[serde(default)]
+    pub backup_before_purge: bool,
+}
+
+/// How destructive actions (purge, export-and-delete) on a protected queue
+/// are confirmed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Protection {
+    /// The usual Y/N prompt.
+    Confirm,
+    /// The queue name has to be typed out.
+    #[default]
+    TypeName,
+    /// Refused outright; replays into the queue are refused too.
+    Block,
+}
+
+impl ProtectedQueue {
+    pub fn reason(&self) -> &str {
+        self.reason.as_deref().unwrap_or("protected queue")
+    }
+}
+
+impl Config {
+    /// `<config dir>/sqs-monitor/config.toml`
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("sqs-monitor").join("config.toml"))
+    }
+
+    /// Reads `path`, or the default location when none is given. A missing
+    /// default file yields the default config; a missing explicit file is an
+    /// error.
+    pub fn load(path: Option<&Path>) -> Result<Self> {
+        let (path, explicit) = match path {
+            Some(path) => (path.to_path_buf(), true),
+            None => match Self::default_path() {
+                Some(path) => (path, false),
+                None => return Ok(Self::default()),
+            },
+        };
+
+        match std::fs::read_to_string(&path) {
+            Ok(content) => toml::from_str(&content)
+                .with_context(|| format!("Invalid config file {}", path.display())),
+            Err(e) if !explicit && e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e).with_context(|| format!("Cannot read config file {}", path.display())),
+        }
+    }
+
+    /// The first protection entry whose pattern matches `queue_name`.
+    pub fn protection_for(&self, queue_name: &str) -> Option<&ProtectedQueue> {
+        self.protected_queues
+            .iter()
+            .find(|p| glob_match(&p.pattern, queue_name))
+    }
+
+    pub fn backup_dir(&self) -> PathBuf {
+        self.backup_dir
+            .clone()
+            .unwrap_or_else(|| data_dir().join("backups"))
+    }
+
+    pub fn audit_log(&self) -> PathBuf {
+        self.audit_log
+            .clone()
+            .unwrap_or_else(|| data_dir().join("audit.jsonl"))
+    }
+}
+
+/// `<data dir>/sqs-monitor`
+fn data_dir() -> PathBuf {
+    dirs::data_local_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("sqs-monitor")
+}
+
+/// Shell-style wildcard match supporting `*` and `?`.
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((star, matched)) => {
+                    p = star + 1;
+                    t = matched + 1;
+                    backtrack = Some((star, matched + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glob_matches_wildcards() {
+        assert!(glob_match("prod-*", "prod-orders"));
+        assert!(glob_match("*-dlq", "orders-dlq"));
+        assert!(glob_match("*prod*", "eu-prod-orders.fifo"));
+        assert!(glob_match("orders-?", "orders-1"));
+        assert!(glob_match("*", ""));
+        assert!(!glob_match("prod-*", "staging-orders"));
+        assert!(!glob_match("orders-?", "orders-10"));
+        assert!(!glob_match("orders", "orders-dlq"));
+    }
+
+    #[test]
+    fn parses_protected_queues() {
+        let config: Config = toml::from_str(
+            r#"
+            backup_dir = "/tmp/backups"
+
+            [[protected_queues]]
+            pattern = "prod-*"
+            backup_before_purge = true
+
+            [[protected_queues]]
+            pattern = "*-audit"
+            protection = "block"
+            reason = "compliance"
+
+            [[protected_queues]]
+            pattern = "*"
+            protection = "confirm"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.backup_dir(), PathBuf::from("/tmp/backups"));
+        assert!(
+            config
+                .protection_for("prod-orders")
+                .unwrap()
+                .backup_before_purge
+        );
+        let prod = config.protection_for("prod-orders").unwrap();
+        assert_eq!(prod.protection, Protection::TypeName);
+        assert_eq!(prod.reason(), "protected queue");
+        let audit = config.protection_for("billing-audit").unwrap();
+        assert_eq!(audit.protection, Protection::Block);
+        assert_eq!(audit.reason(), "compliance");
+        let dev = config.protection_for("dev-orders").unwrap();
+        assert_eq!(dev.protection, Protection::Confirm);
+        assert!(!dev.backup_before_purge);
+    }
+
+    #[test]
+    fn parses_lint_settings() {
+        let config: Config = toml::from_str(
+            r#"
+            [lint]
+            consumer_timeout = 90
+            ignore = ["short-polling", "no-dead-letter-queue"]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.lint.consumer_timeout, Some(90));
+        assert_eq!(
+            config.lint.ignore,
+            [Rule::ShortPolling, Rule::NoDeadLetterQueue]
+        );
+        assert!(toml::from_str::<Config>("[lint]\nignore = [\"typo\"]").is_err());
+    }
+
+    #[test]
+    fn missing_explicit_file_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(Config::load(Some(&dir.path().join("nope.toml"))).is_err());
+    }
+}
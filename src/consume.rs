@@ -0,0 +1,405 @@
+use crate::backend::{BackendResult, MAX_BATCH, QueueBackend};
+use crate::types::{BatchFailure, DeleteEntry, QueueMessage, ReceiveOptions, VisibilityEntry};
+use serde::Serialize;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+/// The longest long poll SQS allows.
+const WAIT_SECS: i32 = 20;
+
+#[derive(Debug, Clone)]
+pub struct ConsumeOptions {
+    /// Run once per message through `sh -c`.
+    pub command: String,
+    /// Commands running at once.
+    pub concurrency: usize,
+    /// Stop receiving after this many messages.
+    pub max_messages: Option<usize>,
+    /// Seconds a message stays hidden while its command runs. The timeout
+    /// is renewed at half-time for as long as the command keeps running.
+    pub visibility_timeout: i32,
+    /// Seconds before a message whose command failed becomes visible
+    /// again; 0 releases it straight away.
+    pub retry_delay: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum ConsumeStatus {
+    /// The command exited 0 and the message was deleted.
+    Deleted,
+    /// The command failed and the message was handed back to the queue.
+    Released { reason: String },
+    /// The message could be neither deleted nor released; it reappears
+    /// once its visibility timeout runs out.
+    Failed { reason: String },
+}
+
+/// What happened to one received message.
+#[derive(Debug, Clone, Serialize)]
+pub struct ConsumeOutcome {
+    pub message_id: String,
+    #[serde(flatten)]
+    pub status: ConsumeStatus,
+}
+
+/// Receives messages and runs a command for each, up to `concurrency` at a
+/// time.
+pub struct Consumer {
+    backend: Arc<dyn QueueBackend>,
+    queue_url: String,
+    options: Arc<ConsumeOptions>,
+    slots: Arc<Semaphore>,
+    running: JoinSet<ConsumeOutcome>,
+    pub received: usize,
+    pub deleted: usize,
+    pub released: usize,
+    pub failed: usize,
+}
+
+impl Consumer {
+    pub fn new(backend: Arc<dyn QueueBackend>, queue_url: &str, options: ConsumeOptions) -> Self {
+        Self {
+            backend,
+            queue_url: queue_url.to_string(),
+            slots: Arc::new(Semaphore::new(options.concurrency.max(1))),
+            options: Arc::new(options),
+            running: JoinSet::new(),
+            received: 0,
+            deleted: 0,
+            released: 0,
+            failed: 0,
+        }
+    }
+
+    /// Whether `max_messages` have been received.
+    pub fn is_done(&self) -> bool {
+        self.options
+            .max_messages
+            .is_some_and(|max| self.received >= max)
+    }
+
+    /// Commands that have started and not yet been collected.
+    pub fn running(&self) -> usize {
+        self.running.len()
+    }
+
+    /// Waits for a free slot, then long-polls for as many messages as there
+    /// are free slots and starts a command for each.
+    ///
+    /// Dropping the future during the long poll loses nothing: messages
+    /// already received stay hidden until their visibility timeout.
+    pub async fn receive(&mut self) -> BackendResult<usize> {
+        let first = self
+            .slots
+            .clone()
+            .acquire_owned()
+            .await
+            .expect("the slot semaphore is never closed");
+        let remaining = self
+            .options
+            .max_messages
+            .map_or(MAX_BATCH, |max| max.saturating_sub(self.received));
+        let mut permits = vec![first];
+        while permits.len() < remaining.min(MAX_BATCH) {
+            match self.slots.clone().try_acquire_owned() {
+                Ok(permit) => permits.push(permit),
+                Err(_) => break,
+            }
+        }
+
+        let messages = self
+            .backend
+            .receive_messages(
+                &self.queue_url,
+                ReceiveOptions {
+                    max_messages: permits.len() as i32,
+                    visibility_timeout: Some(self.options.visibility_timeout),
+                    wait_time_seconds: WAIT_SECS,
+                },
+            )
+            .await?;
+        self.received += messages.len();
+        let started = messages.len();
+        for (message, permit) in messages.into_iter().zip(permits) {
+            let backend = self.backend.clone();
+            let queue_url = self.queue_url.clone();
+            let options = self.options.clone();
+            self.running.spawn(async move {
+                let outcome = process(backend.as_ref(), &queue_url, &options, message).await;
+                drop(permit);
+                outcome
+            });
+        }
+        Ok(started)
+    }
+
+    /// Outcomes of the commands that have finished, without waiting.
+    pub fn finished(&mut self) -> Vec<ConsumeOutcome> {
+        let mut outcomes = Vec::new();
+        while let Some(result) = self.running.try_join_next() {
+            if let Ok(outcome) = result {
+                self.count(&outcome);
+                outcomes.push(outcome);
+            }
+        }
+        outcomes
+    }
+
+    /// Waits for the next command to finish; `None` once none are running.
+    pub async fn next_finished(&mut self) -> Option<ConsumeOutcome> {
+        while let Some(result) = self.running.join_next().await {
+            if let Ok(outcome) = result {
+                self.count(&outcome);
+                return Some(outcome);
+            }
+        }
+        None
+    }
+
+    fn count(&mut self, outcome: &ConsumeOutcome) {
+        match outcome.status {
+            ConsumeStatus::Deleted => self.deleted += 1,
+            ConsumeStatus::Released { .. } => self.released += 1,
+            ConsumeStatus::Failed { .. } => self.failed += 1,
+        }
+    }
+}
+
+/// Runs the command for one message, then deletes or releases it.
+async fn process(
+    backend: &dyn QueueBackend,
+    queue_url: &str,
+    options: &ConsumeOptions,
+    message: QueueMessage,
+) -> ConsumeOutcome {
+    let status = match run_command(backend, queue_url, options, &message).await {
+        Ok(()) => {
+            let entry = DeleteEntry {
+                id: "0".to_string(),
+                receipt_handle: message.receipt_handle.clone(),
+            };
+            match settled(backend.delete_messages(queue_url, &[entry]).await) {
+                Ok(()) => ConsumeStatus::Deleted,
+                Err(e) => ConsumeStatus::Failed {
+                    reason: format!("command succeeded but the message was not deleted: {}", e),
+                },
+            }
+        }
+        Err(reason) => {
+            let entry = VisibilityEntry {
+                id: "0".to_string(),
+                receipt_handle: message.receipt_handle.clone(),
+                visibility_timeout: options.retry_delay,
+            };
+            match settled(backend.change_message_visibility(queue_url, &[entry]).await) {
+                Ok(()) => ConsumeStatus::Released { reason },
+                Err(e) => ConsumeStatus::Failed {
+                    reason: format!("{}; the message was not released: {}", reason, e),
+                },
+            }
+        }
+    };
+    ConsumeOutcome {
+        message_id: message.message_id,
+        status,
+    }
+}
+
+/// Runs the command with the body on stdin, renewing the message's
+/// visibility timeout while it runs. The command's stdout goes to stderr so
+/// that stdout carries only outcomes.
+async fn run_command(
+    backend: &dyn QueueBackend,
+    queue_url: &str,
+    options: &ConsumeOptions,
+    message: &QueueMessage,
+) -> Result<(), String> {
+    let mut command = Command::new("sh");
+    command
+        .arg("-c")
+        .arg(&options.command)
+        .envs(message_env(queue_url, message))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::from(std::io::stderr()))
+        .kill_on_drop(true);
+    // In its own process group, so a Ctrl-C in the terminal reaches only
+    // the consumer, which lets running commands finish
+    #[cfg(unix)]
+    command.process_group(0);
+    let mut child = command
+        .spawn()
+        .map_err(|e| format!("could not start command: {}", e))?;
+
+    // Written from its own task so a command that reads slowly, or not at
+    // all, cannot hold up the visibility renewals
+    if let Some(mut stdin) = child.stdin.take() {
+        let body = message.body.clone();
+        tokio::spawn(async move {
+            let _ = stdin.write_all(body.as_bytes()).await;
+        });
+    }
+
+    let renew_every = Duration::from_secs((options.visibility_timeout / 2).max(1) as u64);
+    let status = loop {
+        tokio::select! {
+            status = child.wait() => {
+                break status.map_err(|e| format!("command failed: {}", e))?;
+            }
+            _ = tokio::time::sleep(renew_every) => {
+                let entry = VisibilityEntry {
+                    id: "0".to_string(),
+                    receipt_handle: message.receipt_handle.clone(),
+                    visibility_timeout: options.visibility_timeout,
+                };
+                // A missed renewal only risks a second delivery
+                let _ = backend.change_message_visibility(queue_url, &[entry]).await;
+            }
+        }
+    };
+    match status.code() {
+        Some(0) => Ok(()),
+        Some(code) => Err(format!("command exited with status {}", code)),
+        None => Err("command was killed by a signal".to_string()),
+    }
+}
+
+/// Folds a single-entry batch result into one error message.
+fn settled(result: BackendResult<Vec<BatchFailure>>) -> Result<(), String> {
+    match result {
+        Ok(failures) => match failures.first() {
+            Some(failure) => Err(format!("{}: {}", failure.code, failure.message)),
+            None => Ok(()),
+        },
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// The environment a message's command runs with: `SQS_QUEUE_URL`,
+/// `SQS_MESSAGE_ID`, `SQS_RECEIPT_HANDLE`, `SQS_<NAME>` for each system
+/// attribute (`ApproximateReceiveCount` becomes
+/// `SQS_APPROXIMATE_RECEIVE_COUNT`) and `SQS_ATTR_<NAME>` for each message
+/// attribute. Binary attributes are passed base64-encoded.
+pub fn message_env(queue_url: &str, message: &QueueMessage) -> Vec<(String, String)> {
+    let mut env = vec![
+        ("SQS_QUEUE_URL".to_string(), queue_url.to_string()),
+        ("SQS_MESSAGE_ID".to_string(), message.message_id.clone()),
+        (
+            "SQS_RECEIPT_HANDLE".to_string(),
+            message.receipt_handle.clone(),
+        ),
+    ];
+    for (name, value) in &message.attributes {
+        env.push((format!("SQS_{}", env_name(name)), value.clone()));
+    }
+    for (name, attribute) in &message.message_attributes {
+        if let Some(value) = attribute
+            .string_value
+            .as_ref()
+            .or(attribute.binary_value.as_ref())
+        {
+            env.push((format!("SQS_ATTR_{}", env_name(name)), value.clone()));
+        }
+    }
+    env
+}
+
+/// `ApproximateReceiveCount` or `event-type` as `APPROXIMATE_RECEIVE_COUNT`
+/// or `EVENT_TYPE`.
+fn env_name(name: &str) -> String {
+    let mut result = String::new();
+    let mut previous_lower = false;
+    for c in name.chars() {
+        if c.is_ascii_uppercase() && previous_lower {
+            result.push('_');
+        }
+        previous_lower = c.is_ascii_lowercase() || c.is_ascii_digit();
+        result.push(if c.is_ascii_alphanumeric() {
+            c.to_ascii_uppercase()
+        } else {
+            '_'
+        });
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::memory::{FakeQueue, InMemoryBackend};
+    use crate::types::MessageAttribute;
+
+    #[test]
+    fn attributes_become_environment_variables() {
+        let mut message = QueueMessage {
+            message_id: "m-1".to_string(),
+            receipt_handle: "h-1".to_string(),
+            body: String::new(),
+            md5_of_body: None,
+            attributes: [("ApproximateReceiveCount".to_string(), "2".to_string())].into(),
+            message_attributes: Default::default(),
+        };
+        message.message_attributes.insert(
+            "event-type".to_string(),
+            MessageAttribute {
+                data_type: "String".to_string(),
+                string_value: Some("OrderPlaced".to_string()),
+                binary_value: None,
+            },
+        );
+
+        let env = message_env("https://queue", &message);
+        let names: Vec<&str> = env.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "SQS_QUEUE_URL",
+                "SQS_MESSAGE_ID",
+                "SQS_RECEIPT_HANDLE",
+                "SQS_APPROXIMATE_RECEIVE_COUNT",
+                "SQS_ATTR_EVENT_TYPE"
+            ]
+        );
+        assert_eq!(env[4].1, "OrderPlaced");
+    }
+
+    #[tokio::test]
+    async fn deletes_on_success_and_releases_on_failure() {
+        let fake = InMemoryBackend::new().with_queue(FakeQueue::new("orders", 4));
+        let url = fake.queue_url("orders").await.unwrap();
+        let options = ConsumeOptions {
+            command: r#"test "$SQS_APPROXIMATE_RECEIVE_COUNT" = 1 && grep -q '"seq":1,'"#
+                .to_string(),
+            concurrency: 2,
+            max_messages: Some(4),
+            visibility_timeout: 30,
+            // Keeps released messages out of the later receives
+            retry_delay: 600,
+        };
+        let mut consumer = Consumer::new(Arc::new(fake.clone()), &url, options);
+        while !consumer.is_done() {
+            consumer.receive().await.unwrap();
+        }
+        let mut outcomes = consumer.finished();
+        while let Some(outcome) = consumer.next_finished().await {
+            outcomes.push(outcome);
+        }
+
+        assert_eq!(outcomes.len(), 4);
+        assert_eq!(
+            (consumer.deleted, consumer.released, consumer.failed),
+            (1, 3, 0)
+        );
+        assert!(outcomes.iter().any(|o| matches!(
+            &o.status,
+            ConsumeStatus::Released { reason } if reason == "command exited with status 1"
+        )));
+        let details = fake.get_queue_details(&url).await.unwrap();
+        assert_eq!(crate::backend::approximate_total(&details), Some(3));
+    }
+}
@@ -0,0 +1,71 @@
+use anyhow::{Context, Result};
+use std::io::Write;
+use std::process::Command;
+
+/// Opens `$VISUAL`, `$EDITOR` or `vi` on a temporary file holding `text` and
+/// returns what was saved, or `None` if the editor failed or the file was
+/// left empty. Blocks until the editor exits, so the terminal must be handed
+/// over first.
+pub fn edit(text: &str, extension: &str) -> Result<Option<String>> {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+    edit_with(&editor, text, extension)
+}
+
+/// File extension for editing `body`, so the editor picks a syntax mode.
+pub fn extension_for(body: &str) -> &'static str {
+    match body.trim_start().chars().next() {
+        Some('{') | Some('[') => ".json",
+        Some('<') => ".xml",
+        _ => ".txt",
+    }
+}
+
+/// `editor` is a shell command line such as `code --wait`; the file path is
+/// appended as its last argument.
+fn edit_with(editor: &str, text: &str, extension: &str) -> Result<Option<String>> {
+    let mut file = tempfile::Builder::new()
+        .prefix("sqs-monitor-")
+        .suffix(extension)
+        .tempfile()
+        .context("Cannot create a temporary file to edit")?;
+    file.write_all(text.as_bytes())?;
+    file.flush()?;
+
+    let status = Command::new("sh")
+        .arg("-c")
+        .arg(format!("{} \"$@\"", editor))
+        .arg("sh")
+        .arg(file.path())
+        .status()
+        .with_context(|| format!("Cannot run editor '{}'", editor))?;
+    if !status.success() {
+        return Ok(None);
+    }
+
+    // Editors may replace the file rather than write to it, so read it back
+    // by path
+    let saved = std::fs::read_to_string(file.path())?;
+    let saved = saved.strip_suffix('\n').unwrap_or(&saved);
+    Ok((!saved.trim().is_empty()).then(|| saved.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn returns_what_the_editor_saved() {
+        let edited = edit_with(
+            r#"sh -c 'printf "{\"retry\":2}\n" > "$1"' sh"#,
+            "{\"retry\":1}\n",
+            ".json",
+        )
+        .unwrap();
+        assert_eq!(edited.as_deref(), Some("{\"retry\":2}"));
+
+        assert_eq!(edit_with("false", "{}", ".json").unwrap(), None);
+        assert_eq!(edit_with("truncate -s 0", "{}", ".json").unwrap(), None);
+    }
+}
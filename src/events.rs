@@ -9,21 +9,78 @@ pub enum AppEvent {
     PreviousQueue,
     ToggleFilter,
     PurgeQueue,
-    ConfirmPurge,
-    CancelPurge,
+    Confirm,
+    ConfirmWithBackup,
+    Cancel,
+    Export,
+    ExportAndDelete,
+    ToggleRawAttributes,
+    ToggleAuditLog,
+    ScrollDetailsDown,
+    ScrollDetailsUp,
+    ToggleHumanReadable,
+    ToggleTimezone,
+    Replay,
+    SwitchProfile,
+    ChangeRegion,
+    TogglePeek,
+    ToggleMessageSelected,
+    DeleteMessages,
+    ChangeMessageVisibility,
+    MoveMessages,
+    ToggleBodyView,
+    FilterMessages,
+    SelectAllMessages,
+    ToggleUnwrapEnvelopes,
+    ToggleReceiveGroups,
+    CycleMetricsWindow,
+    ToggleTail,
+    SendMessages,
+    Compose,
+    TailAndDelete,
+    CloseView,
+    InputChar(char),
+    InputBackspace,
+    InputSubmit,
+    InputCancel,
 }
 
-pub fn poll_event(timeout: Duration) -> anyhow::Result<Option<AppEvent>> {
-    if event::poll(timeout)? {
-        if let Event::Key(key) = event::read()? {
-            if key.kind == KeyEventKind::Press {
-                return Ok(handle_key_event(key));
-            }
-        }
+/// Which key bindings apply, decided by what currently has focus.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyMode {
+    Normal,
+    /// The message view or a message body is open; Esc closes it rather
+    /// than quitting.
+    View,
+    /// A text prompt is open and receives every printable key.
+    TextInput,
+}
+
+pub fn poll_event(timeout: Duration, mode: KeyMode) -> anyhow::Result<Option<AppEvent>> {
+    if event::poll(timeout)?
+        && let Event::Key(key) = event::read()?
+        && key.kind == KeyEventKind::Press
+    {
+        return Ok(match mode {
+            KeyMode::Normal => handle_key_event(key),
+            KeyMode::View if key.code == KeyCode::Esc => Some(AppEvent::CloseView),
+            KeyMode::View => handle_key_event(key),
+            KeyMode::TextInput => handle_text_input_key(key),
+        });
     }
     Ok(None)
 }
 
+fn handle_text_input_key(key: KeyEvent) -> Option<AppEvent> {
+    match key.code {
+        KeyCode::Char(c) => Some(AppEvent::InputChar(c)),
+        KeyCode::Backspace => Some(AppEvent::InputBackspace),
+        KeyCode::Enter => Some(AppEvent::InputSubmit),
+        KeyCode::Esc => Some(AppEvent::InputCancel),
+        _ => None,
+    }
+}
+
 fn handle_key_event(key: KeyEvent) -> Option<AppEvent> {
     match key.code {
         KeyCode::Char('q') | KeyCode::Esc => Some(AppEvent::Quit),
@@ -32,8 +89,35 @@ fn handle_key_event(key: KeyEvent) -> Option<AppEvent> {
         KeyCode::Down | KeyCode::Char('j') => Some(AppEvent::NextQueue),
         KeyCode::Up | KeyCode::Char('k') => Some(AppEvent::PreviousQueue),
         KeyCode::Char('X') => Some(AppEvent::PurgeQueue), // Shift+X
-        KeyCode::Char('y') | KeyCode::Char('Y') => Some(AppEvent::ConfirmPurge),
-        KeyCode::Char('n') | KeyCode::Char('N') => Some(AppEvent::CancelPurge),
+        KeyCode::Char('y') | KeyCode::Char('Y') => Some(AppEvent::Confirm),
+        KeyCode::Char('n') | KeyCode::Char('N') => Some(AppEvent::Cancel),
+        KeyCode::Char('b') | KeyCode::Char('B') => Some(AppEvent::ConfirmWithBackup),
+        KeyCode::Char('e') => Some(AppEvent::Export),
+        KeyCode::Char('E') => Some(AppEvent::ExportAndDelete), // Shift+E
+        KeyCode::Char('i') => Some(AppEvent::Replay),
+        KeyCode::Char('a') => Some(AppEvent::ToggleRawAttributes),
+        KeyCode::Char('l') => Some(AppEvent::ToggleAuditLog),
+        KeyCode::Char('h') => Some(AppEvent::ToggleHumanReadable),
+        KeyCode::Char('z') => Some(AppEvent::ToggleTimezone),
+        KeyCode::Char('p') => Some(AppEvent::SwitchProfile),
+        KeyCode::Char('g') => Some(AppEvent::ChangeRegion),
+        KeyCode::Char('v') => Some(AppEvent::TogglePeek),
+        KeyCode::Char(' ') => Some(AppEvent::ToggleMessageSelected),
+        KeyCode::Char('d') => Some(AppEvent::DeleteMessages),
+        KeyCode::Char('t') => Some(AppEvent::ChangeMessageVisibility),
+        KeyCode::Char('m') => Some(AppEvent::MoveMessages),
+        KeyCode::Enter => Some(AppEvent::ToggleBodyView),
+        KeyCode::Char('/') => Some(AppEvent::FilterMessages),
+        KeyCode::Char('*') => Some(AppEvent::SelectAllMessages),
+        KeyCode::Char('u') => Some(AppEvent::ToggleUnwrapEnvelopes),
+        KeyCode::Char('o') => Some(AppEvent::ToggleReceiveGroups),
+        KeyCode::Char('M') => Some(AppEvent::CycleMetricsWindow), // Shift+M
+        KeyCode::Char('s') => Some(AppEvent::SendMessages),
+        KeyCode::Char('c') => Some(AppEvent::Compose),
+        KeyCode::Char('w') => Some(AppEvent::ToggleTail),
+        KeyCode::Char('W') => Some(AppEvent::TailAndDelete), // Shift+W
+        KeyCode::PageDown => Some(AppEvent::ScrollDetailsDown),
+        KeyCode::PageUp => Some(AppEvent::ScrollDetailsUp),
         _ => None,
     }
 }
@@ -0,0 +1,524 @@
+use crate::body::decode;
+use crate::jsonpath::JsonPath;
+use crate::receives::receive_count;
+use crate::types::QueueMessage;
+use anyhow::{Result, anyhow, bail};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde_json::Value;
+use std::fmt;
+use std::str::FromStr;
+
+/// A predicate over received messages, e.g.
+/// `.detail.status == "FAILED" and receives >= 3 and age > 1h`.
+///
+/// Conditions are `<field> <op> <value>`, combined with `and`, `or`, `not`
+/// and parentheses. Fields:
+///
+/// - `.path`: a JSON path into the body; if the body has no such field, the
+///   payload inside an SNS or EventBridge envelope is tried
+/// - `body`, `id`: the raw body and the message id
+/// - `attr.Name`: a message attribute, or a system attribute such as
+///   `attr.MessageGroupId`
+/// - `receives`: ApproximateReceiveCount
+/// - `sent`: SentTimestamp, compared with a date or RFC 3339 time
+/// - `age`: time since sending, compared with a duration such as `90s`,
+///   `15m`, `2h` or `7d`
+///
+/// Operators are `==`, `!=`, `~` (contains), `<`, `<=`, `>` and `>=`.
+/// Values are quoted strings or bare words; bare words that parse as JSON
+/// (`42`, `true`, `null`) compare as such against JSON fields.
+#[derive(Debug, Clone)]
+pub struct MessageFilter {
+    source: String,
+    expr: Expr,
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Condition(Condition),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Eq,
+    Ne,
+    Contains,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, Clone)]
+enum Condition {
+    Json(JsonPath, Op, Value),
+    Body(Op, String),
+    Id(Op, String),
+    Attribute(String, Op, String),
+    Receives(Op, i64),
+    Sent(Op, DateTime<Utc>),
+    Age(Op, i64),
+}
+
+impl MessageFilter {
+    pub fn matches(&self, message: &QueueMessage, now: DateTime<Utc>) -> bool {
+        let mut body = ParsedBody::new(&message.body);
+        self.expr.eval(message, &mut body, now)
+    }
+}
+
+impl fmt::Display for MessageFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+impl FromStr for MessageFilter {
+    type Err = anyhow::Error;
+
+    fn from_str(source: &str) -> Result<Self> {
+        let tokens = tokenize(source)?;
+        let mut parser = Parser { tokens, pos: 0 };
+        let expr = parser.or_expr()?;
+        if let Some(token) = parser.tokens.get(parser.pos) {
+            bail!("unexpected '{}' in filter", token.text);
+        }
+        Ok(Self {
+            source: source.trim().to_string(),
+            expr,
+        })
+    }
+}
+
+/// The body's JSON, parsed on first use and shared by every condition.
+struct ParsedBody<'a> {
+    raw: &'a str,
+    json: Option<Option<Value>>,
+    payload: Option<Option<Value>>,
+}
+
+impl<'a> ParsedBody<'a> {
+    fn new(raw: &'a str) -> Self {
+        Self {
+            raw,
+            json: None,
+            payload: None,
+        }
+    }
+
+    fn select(&mut self, path: &JsonPath) -> Option<Value> {
+        let raw = self.raw;
+        let json = self
+            .json
+            .get_or_insert_with(|| serde_json::from_str(raw).ok());
+        if let Some(found) = json.as_ref().and_then(|json| path.select(json)) {
+            return Some(found.clone());
+        }
+        let payload = self.payload.get_or_insert_with(|| {
+            let decoded = decode(raw, true);
+            serde_json::from_str(&decoded.text).ok()
+        });
+        payload
+            .as_ref()
+            .and_then(|payload| path.select(payload))
+            .cloned()
+    }
+}
+
+impl Expr {
+    fn eval(&self, message: &QueueMessage, body: &mut ParsedBody, now: DateTime<Utc>) -> bool {
+        match self {
+            Expr::And(a, b) => a.eval(message, body, now) && b.eval(message, body, now),
+            Expr::Or(a, b) => a.eval(message, body, now) || b.eval(message, body, now),
+            Expr::Not(inner) => !inner.eval(message, body, now),
+            Expr::Condition(condition) => condition.eval(message, body, now),
+        }
+    }
+}
+
+impl Condition {
+    fn eval(&self, message: &QueueMessage, body: &mut ParsedBody, now: DateTime<Utc>) -> bool {
+        match self {
+            Condition::Json(path, op, expected) => match body.select(path) {
+                Some(actual) => compare_json(&actual, *op, expected),
+                None => *op == Op::Ne,
+            },
+            Condition::Body(op, expected) => compare_text(&message.body, *op, expected),
+            Condition::Id(op, expected) => compare_text(&message.message_id, *op, expected),
+            Condition::Attribute(name, op, expected) => {
+                let value = match message.message_attributes.get(name) {
+                    Some(attribute) => attribute
+                        .string_value
+                        .as_deref()
+                        .or(attribute.binary_value.as_deref()),
+                    None => message.attributes.get(name).map(String::as_str),
+                };
+                match value {
+                    Some(value) => compare_text(value, *op, expected),
+                    None => *op == Op::Ne,
+                }
+            }
+            Condition::Receives(op, expected) => receive_count(message)
+                .is_some_and(|count| compare_ord(i64::from(count), *op, *expected)),
+            Condition::Sent(op, expected) => message
+                .sent_at()
+                .is_some_and(|at| compare_ord(at, *op, *expected)),
+            Condition::Age(op, expected) => message.sent_at().is_some_and(|at| {
+                compare_ord(now.signed_duration_since(at).num_seconds(), *op, *expected)
+            }),
+        }
+    }
+}
+
+fn compare_ord<T: PartialOrd>(actual: T, op: Op, expected: T) -> bool {
+    match op {
+        Op::Eq => actual == expected,
+        Op::Ne => actual != expected,
+        Op::Lt => actual < expected,
+        Op::Le => actual <= expected,
+        Op::Gt => actual > expected,
+        Op::Ge => actual >= expected,
+        Op::Contains => false,
+    }
+}
+
+/// Text comparison, numeric when both sides are numbers.
+fn compare_text(actual: &str, op: Op, expected: &str) -> bool {
+    if op == Op::Contains {
+        return actual.contains(expected);
+    }
+    match (actual.parse::<f64>(), expected.parse::<f64>()) {
+        (Ok(a), Ok(e)) => compare_ord(a, op, e),
+        _ => compare_ord(actual, op, expected),
+    }
+}
+
+fn compare_json(actual: &Value, op: Op, expected: &Value) -> bool {
+    let text = |value: &Value| match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    };
+    match (op, actual.as_f64(), expected.as_f64()) {
+        (Op::Contains, _, _) => text(actual).contains(&text(expected)),
+        (_, Some(a), Some(e)) => compare_ord(a, op, e),
+        (Op::Eq, _, _) => actual == expected,
+        (Op::Ne, _, _) => actual != expected,
+        _ => compare_ord(text(actual), op, text(expected)),
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    text: String,
+    quoted: bool,
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = source.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == '"' {
+            chars.next();
+            let mut text = String::new();
+            loop {
+                match chars.next() {
+                    Some('"') => break,
+                    Some('\\') => text.extend(chars.next()),
+                    Some(c) => text.push(c),
+                    None => bail!("unterminated string in filter"),
+                }
+            }
+            tokens.push(Token { text, quoted: true });
+        } else if "()".contains(c) {
+            chars.next();
+            tokens.push(Token {
+                text: c.to_string(),
+                quoted: false,
+            });
+        } else if "=!~<>".contains(c) {
+            let mut text = String::new();
+            while let Some(&c) = chars.peek().filter(|c| "=!~<>".contains(**c)) {
+                text.push(c);
+                chars.next();
+            }
+            tokens.push(Token {
+                text,
+                quoted: false,
+            });
+        } else {
+            let mut text = String::new();
+            let mut depth = 0;
+            // Brackets belong to JSON paths such as `.items[0]` or
+            // `.["odd key"]`, quotes and spaces included.
+            while let Some(&c) = chars.peek() {
+                if depth == 0 && (c.is_whitespace() || "()=!~<>".contains(c)) {
+                    break;
+                }
+                match c {
+                    '[' => depth += 1,
+                    ']' => depth -= 1,
+                    _ => {}
+                }
+                text.push(c);
+                chars.next();
+            }
+            tokens.push(Token {
+                text,
+                quoted: false,
+            });
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek_keyword(&self, keyword: &str) -> bool {
+        self.tokens
+            .get(self.pos)
+            .is_some_and(|t| !t.quoted && t.text.eq_ignore_ascii_case(keyword))
+    }
+
+    fn next(&mut self, expected: &str) -> Result<Token> {
+        let token = self
+            .tokens
+            .get(self.pos)
+            .cloned()
+            .ok_or_else(|| anyhow!("filter ends early, expected {}", expected))?;
+        self.pos += 1;
+        Ok(token)
+    }
+
+    fn or_expr(&mut self) -> Result<Expr> {
+        let mut expr = self.and_expr()?;
+        while self.peek_keyword("or") || self.peek_keyword("||") {
+            self.pos += 1;
+            expr = Expr::Or(Box::new(expr), Box::new(self.and_expr()?));
+        }
+        Ok(expr)
+    }
+
+    fn and_expr(&mut self) -> Result<Expr> {
+        let mut expr = self.unary()?;
+        while self.peek_keyword("and") || self.peek_keyword("&&") {
+            self.pos += 1;
+            expr = Expr::And(Box::new(expr), Box::new(self.unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn unary(&mut self) -> Result<Expr> {
+        if self.peek_keyword("not") {
+            self.pos += 1;
+            return Ok(Expr::Not(Box::new(self.unary()?)));
+        }
+        if self.peek_keyword("(") {
+            self.pos += 1;
+            let expr = self.or_expr()?;
+            if !self.peek_keyword(")") {
+                bail!("missing ')' in filter");
+            }
+            self.pos += 1;
+            return Ok(expr);
+        }
+        self.condition().map(Expr::Condition)
+    }
+
+    fn condition(&mut self) -> Result<Condition> {
+        let field = self.next("a field")?;
+        let op = match self.next("an operator")?.text.as_str() {
+            "==" | "=" => Op::Eq,
+            "!=" => Op::Ne,
+            "~" => Op::Contains,
+            "<" => Op::Lt,
+            "<=" => Op::Le,
+            ">" => Op::Gt,
+            ">=" => Op::Ge,
+            other => bail!("unknown operator '{}' after {}", other, field.text),
+        };
+        let value = self.next("a value")?;
+        let name = field.text.as_str();
+        let ordered = || {
+            if op == Op::Contains {
+                bail!("'~' does not apply to {}", name);
+            }
+            Ok(op)
+        };
+
+        Ok(match name {
+            _ if name.starts_with('.') => {
+                let literal = if value.quoted {
+                    Value::String(value.text)
+                } else {
+                    serde_json::from_str(&value.text).unwrap_or(Value::String(value.text))
+                };
+                Condition::Json(name.parse()?, op, literal)
+            }
+            "body" => Condition::Body(op, value.text),
+            "id" => Condition::Id(op, value.text),
+            "receives" => Condition::Receives(
+                ordered()?,
+                value
+                    .text
+                    .parse()
+                    .map_err(|_| anyhow!("receives needs a number, got '{}'", value.text))?,
+            ),
+            "sent" => Condition::Sent(ordered()?, parse_time(&value.text)?),
+            "age" => Condition::Age(ordered()?, parse_duration(&value.text)?),
+            _ => match name.strip_prefix("attr.") {
+                Some(attribute) if !attribute.is_empty() => {
+                    Condition::Attribute(attribute.to_string(), op, value.text)
+                }
+                _ => bail!(
+                    "unknown field '{}'; use .path, body, id, attr.Name, receives, sent or age",
+                    name
+                ),
+            },
+        })
+    }
+}
+
+/// `2024-06-01` (midnight UTC) or an RFC 3339 time.
+fn parse_time(text: &str) -> Result<DateTime<Utc>> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(text) {
+        return Ok(time.with_timezone(&Utc));
+    }
+    NaiveDate::parse_from_str(text, "%Y-%m-%d")
+        .map(|date| date.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc())
+        .map_err(|_| anyhow!("sent needs a date or RFC 3339 time, got '{}'", text))
+}
+
+/// `90s`, `15m`, `2h` or `7d`, in seconds. A bare number is seconds.
+fn parse_duration(text: &str) -> Result<i64> {
+    let (number, unit) = match text.find(|c: char| !c.is_ascii_digit()) {
+        Some(split) => text.split_at(split),
+        None => (text, "s"),
+    };
+    let multiplier = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 3_600,
+        "d" => 86_400,
+        _ => bail!(
+            "age needs a duration such as 90s, 15m, 2h or 7d, got '{}'",
+            text
+        ),
+    };
+    number.parse::<i64>().map(|n| n * multiplier).map_err(|_| {
+        anyhow!(
+            "age needs a duration such as 90s, 15m, 2h or 7d, got '{}'",
+            text
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::MessageAttribute;
+    use chrono::TimeZone;
+    use std::collections::BTreeMap;
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 6, 1, 12, 0, 0).unwrap()
+    }
+
+    fn message(body: &str, receives: u32, sent_minutes_ago: i64) -> QueueMessage {
+        let sent = now() - chrono::Duration::minutes(sent_minutes_ago);
+        let mut attributes = BTreeMap::new();
+        attributes.insert("ApproximateReceiveCount".to_string(), receives.to_string());
+        attributes.insert(
+            "SentTimestamp".to_string(),
+            sent.timestamp_millis().to_string(),
+        );
+        let mut message_attributes = BTreeMap::new();
+        message_attributes.insert(
+            "EventType".to_string(),
+            MessageAttribute {
+                data_type: "String".to_string(),
+                string_value: Some("OrderPlaced".to_string()),
+                binary_value: None,
+            },
+        );
+        QueueMessage {
+            message_id: "m-1".to_string(),
+            receipt_handle: String::new(),
+            body: body.to_string(),
+            md5_of_body: None,
+            attributes,
+            message_attributes,
+        }
+    }
+
+    fn matches(filter: &str, message: &QueueMessage) -> bool {
+        filter
+            .parse::<MessageFilter>()
+            .unwrap()
+            .matches(message, now())
+    }
+
+    #[test]
+    fn matches_body_fields_attributes_and_counts() {
+        let failed = message(
+            r#"{"orderId":"A-1","status":"FAILED","total":12.5,"error":"inventory timeout"}"#,
+            5,
+            90,
+        );
+
+        assert!(matches(r#".status == "FAILED""#, &failed));
+        assert!(matches(".total > 10 and .total <= 12.5", &failed));
+        assert!(matches(r#".error ~ timeout"#, &failed));
+        assert!(matches(".missing != 1", &failed));
+        assert!(!matches(".missing == 1", &failed));
+        assert!(matches(r#"body ~ "A-1""#, &failed));
+        assert!(matches("attr.EventType == OrderPlaced", &failed));
+        assert!(matches("receives >= 5 and age > 1h and age < 2h", &failed));
+        assert!(matches(
+            "sent >= 2024-06-01 and sent < 2024-06-01T11:00:00Z",
+            &failed
+        ));
+        assert!(matches(r#"not (.status == "OK" or receives < 2)"#, &failed));
+        assert!(!matches(r#".status == "OK" or receives < 2"#, &failed));
+    }
+
+    #[test]
+    fn json_paths_fall_back_to_the_envelope_payload() {
+        let inner = r#"{"orderId":"A-2"}"#;
+        let sns = serde_json::json!({ "Type": "Notification", "Message": inner }).to_string();
+        let wrapped = message(&sns, 1, 1);
+        assert!(matches(r#".orderId == "A-2""#, &wrapped));
+        assert!(matches(r#".Type == Notification"#, &wrapped));
+    }
+
+    #[test]
+    fn rejects_malformed_filters() {
+        for filter in [
+            "",
+            ".status",
+            ".status ==",
+            "receives ~ 3",
+            "receives > many",
+            "age > soon",
+            "sent < yesterday",
+            "colour == red",
+            "(.a == 1",
+            ".a == 1 .b == 2",
+            r#"body ~ "open"#,
+        ] {
+            assert!(
+                filter.parse::<MessageFilter>().is_err(),
+                "{} should not parse",
+                filter
+            );
+        }
+    }
+}
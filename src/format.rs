@@ -0,0 +1,201 @@
+use chrono::{DateTime, Local, Utc};
+
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// Renders numbers, durations, sizes and timestamps for display.
+///
+/// `human` switches between readable values ("4d", "256 KiB", "1,024") and the
+/// raw numbers SQS returns; `utc` picks the timezone used for timestamps.
+#[derive(Debug, Clone, Copy)]
+pub struct Formatter {
+    pub human: bool,
+    pub utc: bool,
+}
+
+impl Formatter {
+    pub fn count(&self, n: i64) -> String {
+        if self.human {
+            group_thousands(n)
+        } else {
+            n.to_string()
+        }
+    }
+
+    pub fn seconds(&self, secs: i64) -> String {
+        if self.human {
+            duration(secs)
+        } else {
+            format!("{} seconds", secs)
+        }
+    }
+
+    pub fn bytes(&self, n: i64) -> String {
+        if self.human {
+            bytes(n)
+        } else {
+            format!("{} bytes", n)
+        }
+    }
+
+    pub fn timestamp(&self, dt: DateTime<Utc>) -> String {
+        if self.utc {
+            format!("{} UTC", dt.format(TIMESTAMP_FORMAT))
+        } else {
+            dt.with_timezone(&Local)
+                .format(TIMESTAMP_FORMAT)
+                .to_string()
+        }
+    }
+
+    /// A timestamp followed by how long ago it was, e.g.
+    /// "2024-01-01 10:00:00 (3 months ago)". Raw mode drops the relative part.
+    pub fn timestamp_with_age(&self, dt: DateTime<Utc>, now: DateTime<Utc>) -> String {
+        if self.human {
+            format!("{} ({})", self.timestamp(dt), relative(dt, now))
+        } else {
+            self.timestamp(dt)
+        }
+    }
+
+    /// Formats an epoch-seconds value as reported in queue attributes.
+    pub fn epoch_seconds(&self, secs: i64, now: DateTime<Utc>) -> String {
+        DateTime::from_timestamp(secs, 0)
+            .map(|dt| self.timestamp_with_age(dt, now))
+            .unwrap_or_else(|| "N/A".to_string())
+    }
+}
+
+pub fn group_thousands(n: i64) -> String {
+    let digits = n.unsigned_abs().to_string();
+    let mut grouped = String::with_capacity(digits.len() + digits.len() / 3 + 1);
+    if n < 0 {
+        grouped.push('-');
+    }
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(c);
+    }
+    grouped
+}
+
+/// Compact duration with at most two units: "4d", "1h 30m", "45s".
+pub fn duration(secs: i64) -> String {
+    const UNITS: [(i64, &str); 4] = [(86_400, "d"), (3_600, "h"), (60, "m"), (1, "s")];
+
+    if secs == 0 {
+        return "0s".to_string();
+    }
+
+    let sign = if secs < 0 { "-" } else { "" };
+    let mut remaining = secs.abs();
+    let mut parts = Vec::new();
+    for (size, suffix) in UNITS {
+        if remaining >= size {
+            parts.push(format!("{}{}", remaining / size, suffix));
+            remaining %= size;
+        }
+        if parts.len() == 2 {
+            break;
+        }
+    }
+    format!("{}{}", sign, parts.join(" "))
+}
+
+/// Binary size with one decimal where it matters: "256 KiB", "1.5 MiB".
+pub fn bytes(n: i64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+
+    if n.abs() < 1024 {
+        return format!("{} B", n);
+    }
+    // Round to the displayed precision before settling on a unit, so a value
+    // just under the next unit shows as "1 MiB" rather than "1024.0 KiB".
+    let round = |value: f64| (value * 10.0).round() / 10.0;
+    let mut value = n as f64 / 1024.0;
+    let mut unit = 0;
+    while round(value).abs() >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    let value = round(value);
+    if value.fract() == 0.0 {
+        format!("{} {}", value as i64, UNITS[unit])
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+/// "just now", "5 minutes ago", "in 2 hours", "3 months ago".
+pub fn relative(dt: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    let delta = now.signed_duration_since(dt).num_seconds();
+    let secs = delta.abs();
+    if secs < 45 {
+        return "just now".to_string();
+    }
+
+    let (amount, unit) = match secs {
+        s if s < 3_600 => ((s / 60).max(1), "minute"),
+        s if s < 86_400 => (s / 3_600, "hour"),
+        s if s < 30 * 86_400 => (s / 86_400, "day"),
+        s if s < 365 * 86_400 => (s / (30 * 86_400), "month"),
+        s => (s / (365 * 86_400), "year"),
+    };
+    let plural = if amount == 1 { "" } else { "s" };
+
+    if delta >= 0 {
+        format!("{} {}{} ago", amount, unit, plural)
+    } else {
+        format!("in {} {}{}", amount, unit, plural)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bytes_round_before_picking_a_unit() {
+        assert_eq!(bytes(0), "0 B");
+        assert_eq!(bytes(1023), "1023 B");
+        assert_eq!(bytes(1024), "1 KiB");
+        assert_eq!(bytes(1536), "1.5 KiB");
+        assert_eq!(bytes(262_144), "256 KiB");
+        assert_eq!(bytes(1_048_575), "1 MiB");
+        assert_eq!(bytes(1_048_576), "1 MiB");
+        assert_eq!(bytes(1_073_741_823), "1 GiB");
+        assert_eq!(bytes(-1536), "-1.5 KiB");
+        assert_eq!(bytes(1 << 50), "1024 TiB");
+    }
+
+    #[test]
+    fn durations_keep_the_two_largest_units() {
+        assert_eq!(duration(0), "0s");
+        assert_eq!(duration(59), "59s");
+        assert_eq!(duration(60), "1m");
+        assert_eq!(duration(3_599), "59m 59s");
+        assert_eq!(duration(3_600), "1h");
+        assert_eq!(duration(5_400), "1h 30m");
+        assert_eq!(duration(86_399), "23h 59m");
+        assert_eq!(duration(90_061), "1d 1h");
+        assert_eq!(duration(-90), "-1m 30s");
+    }
+
+    #[test]
+    fn counts_group_thousands_only_when_human() {
+        let human = Formatter {
+            human: true,
+            utc: true,
+        };
+        let raw = Formatter {
+            human: false,
+            utc: true,
+        };
+        assert_eq!(human.count(999), "999");
+        assert_eq!(human.count(1_000), "1,000");
+        assert_eq!(human.count(-1_000), "-1,000");
+        assert_eq!(human.count(1_234_567), "1,234,567");
+        assert_eq!(raw.count(1_234_567), "1234567");
+    }
+}
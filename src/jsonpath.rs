@@ -0,0 +1,124 @@
+use anyhow::{Result, bail};
+use serde_json::Value;
+use std::fmt;
+use std::str::FromStr;
+
+/// A small subset of jq path syntax: `.`, `.field`, `.["odd key"]`, `.[0]`
+/// and chains of those such as `.detail.items[0].id`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JsonPath {
+    source: String,
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+impl JsonPath {
+    pub fn select<'a>(&self, value: &'a Value) -> Option<&'a Value> {
+        self.segments
+            .iter()
+            .try_fold(value, |current, segment| match segment {
+                Segment::Key(key) => current.get(key),
+                Segment::Index(index) => current.get(*index),
+            })
+    }
+}
+
+impl fmt::Display for JsonPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+impl FromStr for JsonPath {
+    type Err = anyhow::Error;
+
+    fn from_str(source: &str) -> Result<Self> {
+        let source = source.trim();
+        let Some(mut rest) = source.strip_prefix('.') else {
+            bail!("path must start with '.': {}", source);
+        };
+
+        let mut segments = Vec::new();
+        let mut after_dot = true;
+        while !rest.is_empty() {
+            if let Some(bracketed) = rest.strip_prefix('[') {
+                let Some(end) = bracketed.find(']') else {
+                    bail!("unclosed '[' in path: {}", source);
+                };
+                let inner = bracketed[..end].trim();
+                segments.push(if let Some(quoted) = inner.strip_prefix('"') {
+                    match quoted.strip_suffix('"') {
+                        Some(key) => Segment::Key(key.to_string()),
+                        None => bail!("unterminated string in path: {}", source),
+                    }
+                } else {
+                    match inner.parse() {
+                        Ok(index) => Segment::Index(index),
+                        Err(_) => bail!("invalid index '{}' in path: {}", inner, source),
+                    }
+                });
+                rest = &bracketed[end + 1..];
+                after_dot = false;
+            } else if let Some(next) = rest.strip_prefix('.') {
+                if after_dot {
+                    bail!("empty segment in path: {}", source);
+                }
+                rest = next;
+                after_dot = true;
+            } else {
+                if !after_dot {
+                    bail!("expected '.' or '[' in path: {}", source);
+                }
+                let end = rest.find(['.', '[']).unwrap_or(rest.len());
+                segments.push(Segment::Key(rest[..end].to_string()));
+                rest = &rest[end..];
+                after_dot = false;
+            }
+        }
+        if after_dot && !segments.is_empty() {
+            bail!("path ends with '.': {}", source);
+        }
+
+        Ok(Self {
+            source: source.to_string(),
+            segments,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn select(path: &str, value: &Value) -> Option<Value> {
+        path.parse::<JsonPath>().unwrap().select(value).cloned()
+    }
+
+    #[test]
+    fn selects_nested_fields_and_indices() {
+        let value = json!({"detail": {"items": [{"id": 7}, {"id": 8}]}, "odd key": true});
+
+        assert_eq!(select(".", &value), Some(value.clone()));
+        assert_eq!(select(".detail.items[1].id", &value), Some(json!(8)));
+        assert_eq!(select(".[\"odd key\"]", &value), Some(json!(true)));
+        assert_eq!(select(".detail.missing", &value), None);
+        assert_eq!(select(".detail.items[5]", &value), None);
+    }
+
+    #[test]
+    fn rejects_malformed_paths() {
+        for path in ["detail", ".a..b", ".a.", ".a[", ".a[x]", ".a[0]b"] {
+            assert!(
+                path.parse::<JsonPath>().is_err(),
+                "{} should not parse",
+                path
+            );
+        }
+    }
+}
@@ -0,0 +1,381 @@
+use crate::backend::QueueBackend;
+use crate::config::LintConfig;
+use crate::format;
+use crate::receives::{dead_letter_target_arn, max_receive_count};
+use crate::types::{QueueDetails, QueueInfo};
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+
+/// A misconfiguration the linter looks for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Rule {
+    NoDeadLetterQueue,
+    DlqRetentionTooShort,
+    MaxReceiveCountOne,
+    VisibilityBelowConsumerTimeout,
+    ShortPolling,
+    EncryptionDisabled,
+    FifoWithStandardDlq,
+}
+
+impl Rule {
+    /// The name used in `[lint] ignore` and printed by `lint`.
+    pub fn id(self) -> &'static str {
+        match self {
+            Rule::NoDeadLetterQueue => "no-dead-letter-queue",
+            Rule::DlqRetentionTooShort => "dlq-retention-too-short",
+            Rule::MaxReceiveCountOne => "max-receive-count-one",
+            Rule::VisibilityBelowConsumerTimeout => "visibility-below-consumer-timeout",
+            Rule::ShortPolling => "short-polling",
+            Rule::EncryptionDisabled => "encryption-disabled",
+            Rule::FifoWithStandardDlq => "fifo-with-standard-dlq",
+        }
+    }
+
+    /// Errors lose or duplicate messages; warnings cost money, retries or
+    /// safety margin.
+    pub fn severity(self) -> Severity {
+        match self {
+            Rule::DlqRetentionTooShort
+            | Rule::VisibilityBelowConsumerTimeout
+            | Rule::FifoWithStandardDlq => Severity::Error,
+            Rule::NoDeadLetterQueue
+            | Rule::MaxReceiveCountOne
+            | Rule::ShortPolling
+            | Rule::EncryptionDisabled => Severity::Warning,
+        }
+    }
+
+    /// What to change to fix it.
+    pub fn advice(self) -> &'static str {
+        match self {
+            Rule::NoDeadLetterQueue => {
+                "Add a RedrivePolicy to set aside messages that keep failing"
+            }
+            Rule::DlqRetentionTooShort => {
+                "Raise the DLQ's MessageRetentionPeriod to at least the source's; \
+                 a message keeps its original age when it is moved"
+            }
+            Rule::MaxReceiveCountOne => {
+                "Raise maxReceiveCount so a single timeout or crash is retried"
+            }
+            Rule::VisibilityBelowConsumerTimeout => {
+                "Raise VisibilityTimeout above the consumer's processing timeout"
+            }
+            Rule::ShortPolling => "Set ReceiveMessageWaitTimeSeconds to 20 to long-poll",
+            Rule::EncryptionDisabled => "Enable SqsManagedSseEnabled or set a KmsMasterKeyId",
+            Rule::FifoWithStandardDlq => "Point the RedrivePolicy at a FIFO dead-letter queue",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        })
+    }
+}
+
+/// One rule a queue breaks, with the values that break it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    pub rule: Rule,
+    pub message: String,
+}
+
+/// The most severe of `findings`.
+pub fn worst(findings: &[Finding]) -> Option<Severity> {
+    findings.iter().map(|f| f.rule.severity()).max()
+}
+
+/// Checks one queue's attributes. `dlq` is the details of its dead-letter
+/// queue when they could be read; `is_dlq` skips the rules that only make
+/// sense for queues consumers read from.
+pub fn lint_queue(
+    details: &QueueDetails,
+    dlq: Option<&QueueDetails>,
+    is_dlq: bool,
+    config: &LintConfig,
+) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let mut flag = |rule: Rule, message: String| {
+        if !config.ignore.contains(&rule) {
+            findings.push(Finding { rule, message });
+        }
+    };
+
+    match dead_letter_target_arn(details) {
+        None if !is_dlq => flag(
+            Rule::NoDeadLetterQueue,
+            "No dead-letter queue: failing messages are retried until they expire".to_string(),
+        ),
+        None => {}
+        Some(target) => {
+            if let (Some(source), Some(dlq)) = (
+                details.message_retention_period,
+                dlq.and_then(|d| d.message_retention_period),
+            ) && dlq < source
+            {
+                flag(
+                    Rule::DlqRetentionTooShort,
+                    format!(
+                        "Dead-letter queue keeps messages for {}, less than this queue's {}",
+                        format::duration(dlq.into()),
+                        format::duration(source.into())
+                    ),
+                );
+            }
+            if details.fifo_queue && !target.ends_with(".fifo") {
+                flag(
+                    Rule::FifoWithStandardDlq,
+                    format!(
+                        "FIFO queue redrives to standard queue '{}'",
+                        target.rsplit(':').next().unwrap_or(&target)
+                    ),
+                );
+            }
+        }
+    }
+
+    if max_receive_count(details) == Some(1) {
+        flag(
+            Rule::MaxReceiveCountOne,
+            "maxReceiveCount is 1: one failed receive moves a message to the DLQ".to_string(),
+        );
+    }
+
+    if let (Some(visibility), Some(consumer)) =
+        (details.visibility_timeout, config.consumer_timeout)
+        && i64::from(visibility) < consumer
+        && !is_dlq
+    {
+        flag(
+            Rule::VisibilityBelowConsumerTimeout,
+            format!(
+                "Visibility timeout {} is shorter than the {} consumer timeout: \
+                 slow messages are delivered twice",
+                format::duration(visibility.into()),
+                format::duration(consumer)
+            ),
+        );
+    }
+
+    let attribute = |name: &str| details.attributes.get(name).map(String::as_str);
+    if attribute("ReceiveMessageWaitTimeSeconds") == Some("0") && !is_dlq {
+        flag(
+            Rule::ShortPolling,
+            "Short polling: empty receives return at once and are billed".to_string(),
+        );
+    }
+    // Only queues that report SSE at all; older endpoints and emulators
+    // leave the attribute out.
+    if attribute("SqsManagedSseEnabled") == Some("false") && attribute("KmsMasterKeyId").is_none() {
+        flag(
+            Rule::EncryptionDisabled,
+            "Messages are stored unencrypted".to_string(),
+        );
+    }
+
+    findings
+}
+
+/// What linting a set of queues found, keyed by queue name.
+#[derive(Debug, Default)]
+pub struct LintReport {
+    pub findings: BTreeMap<String, Vec<Finding>>,
+    /// Queues whose attributes could not be read, with why; they have no
+    /// entry in `findings`.
+    pub errors: BTreeMap<String, String>,
+}
+
+/// Lints every queue in `queues` that can be read. Dead-letter queues are
+/// recognised by name or by another queue's RedrivePolicy pointing at
+/// them.
+pub async fn lint_queues(
+    backend: &dyn QueueBackend,
+    queues: &[QueueInfo],
+    config: &LintConfig,
+) -> LintReport {
+    let mut report = LintReport::default();
+    let mut read = Vec::with_capacity(queues.len());
+    let mut all_details = Vec::with_capacity(queues.len());
+    for queue in queues {
+        match backend.get_queue_details(&queue.url).await {
+            Ok(details) => {
+                read.push(queue);
+                all_details.push(details);
+            }
+            Err(e) => {
+                report.errors.insert(queue.name.clone(), e.to_string());
+            }
+        }
+    }
+
+    let by_arn: HashMap<&str, &QueueDetails> = all_details
+        .iter()
+        .filter_map(|details| Some((details.arn.as_deref()?, details)))
+        .collect();
+    let targets: HashSet<String> = all_details
+        .iter()
+        .filter_map(dead_letter_target_arn)
+        .collect();
+
+    report.findings = read
+        .into_iter()
+        .zip(&all_details)
+        .map(|(queue, details)| {
+            let dlq = dead_letter_target_arn(details)
+                .and_then(|target| by_arn.get(target.as_str()).copied());
+            let is_dlq = queue.is_dlq()
+                || details
+                    .arn
+                    .as_ref()
+                    .is_some_and(|arn| targets.contains(arn));
+            (queue.name.clone(), lint_queue(details, dlq, is_dlq, config))
+        })
+        .collect();
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::memory::{FakeQueue, InMemoryBackend, Operation};
+
+    fn details(name: &str) -> QueueDetails {
+        QueueDetails {
+            arn: Some(format!("arn:aws:sqs:us-east-1:1:{}", name)),
+            message_retention_period: Some(345_600),
+            visibility_timeout: Some(30),
+            fifo_queue: name.ends_with(".fifo"),
+            ..QueueDetails::default()
+        }
+    }
+
+    fn redrive_to(details: &mut QueueDetails, target: &str, max_receive_count: u32) {
+        details.attributes.insert(
+            "RedrivePolicy".to_string(),
+            format!(
+                r#"{{"deadLetterTargetArn":"arn:aws:sqs:us-east-1:1:{}","maxReceiveCount":{}}}"#,
+                target, max_receive_count
+            ),
+        );
+    }
+
+    fn rules(findings: &[Finding]) -> Vec<Rule> {
+        findings.iter().map(|f| f.rule).collect()
+    }
+
+    #[test]
+    fn flags_each_misconfiguration() {
+        let config = LintConfig {
+            consumer_timeout: Some(60),
+            ignore: Vec::new(),
+        };
+        let mut source = details("payments.fifo");
+        redrive_to(&mut source, "payments-dlq", 1);
+        source.message_retention_period = Some(1_209_600);
+        source
+            .attributes
+            .insert("ReceiveMessageWaitTimeSeconds".to_string(), "0".to_string());
+        source
+            .attributes
+            .insert("SqsManagedSseEnabled".to_string(), "false".to_string());
+        let dlq = details("payments-dlq");
+
+        let findings = lint_queue(&source, Some(&dlq), false, &config);
+        assert_eq!(
+            rules(&findings),
+            [
+                Rule::DlqRetentionTooShort,
+                Rule::FifoWithStandardDlq,
+                Rule::MaxReceiveCountOne,
+                Rule::VisibilityBelowConsumerTimeout,
+                Rule::ShortPolling,
+                Rule::EncryptionDisabled,
+            ]
+        );
+        assert_eq!(
+            findings[0].message,
+            "Dead-letter queue keeps messages for 4d, less than this queue's 14d"
+        );
+        assert_eq!(
+            findings[1].message,
+            "FIFO queue redrives to standard queue 'payments-dlq'"
+        );
+        assert_eq!(worst(&findings), Some(Severity::Error));
+
+        let mut healthy = details("orders");
+        redrive_to(&mut healthy, "orders-dlq", 5);
+        healthy.visibility_timeout = Some(120);
+        healthy
+            .attributes
+            .insert("KmsMasterKeyId".to_string(), "alias/aws/sqs".to_string());
+        healthy
+            .attributes
+            .insert("SqsManagedSseEnabled".to_string(), "false".to_string());
+        assert!(lint_queue(&healthy, Some(&details("orders-dlq")), false, &config).is_empty());
+
+        let ignoring = LintConfig {
+            consumer_timeout: None,
+            ignore: vec![Rule::NoDeadLetterQueue],
+        };
+        assert!(lint_queue(&details("orders"), None, false, &ignoring).is_empty());
+        assert_eq!(
+            rules(&lint_queue(&details("orders"), None, false, &config)),
+            [
+                Rule::NoDeadLetterQueue,
+                Rule::VisibilityBelowConsumerTimeout
+            ]
+        );
+        // Nothing consumes from a dead-letter queue in normal operation
+        assert!(lint_queue(&details("orders-dlq"), None, true, &config).is_empty());
+        assert_eq!(worst(&[]), None);
+    }
+
+    #[tokio::test]
+    async fn recognises_dead_letter_queues_by_redrive_policy() {
+        let mut orders = FakeQueue::new("orders", 0);
+        orders
+            .details
+            .attributes
+            .insert("RedrivePolicy".to_string(), r#"{"deadLetterTargetArn":"arn:aws:sqs:us-east-1:000000000000:failed","maxReceiveCount":5}"#.to_string());
+        orders.details.message_retention_period = Some(1_209_600);
+        let backend = InMemoryBackend::new()
+            .with_queue(orders)
+            .with_queue(FakeQueue::new("failed", 0))
+            .with_queue(FakeQueue::new("emails", 0));
+        let queues = backend.list_queues().await.unwrap();
+
+        let report = lint_queues(&backend, &queues, &LintConfig::default()).await;
+        let findings = &report.findings;
+        assert_eq!(rules(&findings["orders"]), [Rule::DlqRetentionTooShort]);
+        assert!(findings["failed"].is_empty());
+        assert_eq!(rules(&findings["emails"]), [Rule::NoDeadLetterQueue]);
+        assert!(report.errors.is_empty());
+    }
+
+    #[tokio::test]
+    async fn skips_queues_that_cannot_be_read() {
+        let backend = InMemoryBackend::new()
+            .with_queue(FakeQueue::new("emails", 0))
+            .with_queue(FakeQueue::new("orders", 0));
+        let queues = backend.list_queues().await.unwrap();
+        backend.fail_next(Operation::GetQueueDetails, "AccessDenied");
+
+        let report = lint_queues(&backend, &queues, &LintConfig::default()).await;
+        assert_eq!(report.errors.keys().collect::<Vec<_>>(), ["emails"]);
+        assert_eq!(report.findings.keys().collect::<Vec<_>>(), ["orders"]);
+        assert_eq!(rules(&report.findings["orders"]), [Rule::NoDeadLetterQueue]);
+    }
+}
@@ -1,117 +1,439 @@
 mod app;
+mod archive;
+mod audit;
 mod aws;
+mod backend;
+mod body;
+mod cli;
+mod commands;
+mod config;
+mod consume;
+mod editor;
 mod events;
+mod filter;
+mod format;
+mod jsonpath;
+mod lint;
+mod metrics;
+mod peek;
+mod receives;
+mod replay;
+mod retention;
+mod send;
+mod tail;
+mod terminal;
 mod types;
 mod ui;
 
 use anyhow::Result;
 use app::App;
-use crossterm::{
-    execute,
-    terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
-};
-use events::{AppEvent, poll_event};
-use ratatui::{Terminal, backend::CrosstermBackend};
-use std::io;
+use audit::AuditLog;
+use aws::profile::ConnectionSettings;
+use aws::sqs::SqsClient;
+use backend::audited::AuditedBackend;
+use backend::memory::InMemoryBackend;
+use backend::retry::{RetryPolicy, RetryingBackend};
+use backend::{QueueBackend, queue_name_from_url};
+use clap::Parser;
+use cli::{Cli, Command};
+use config::Config;
+use events::{AppEvent, KeyMode, poll_event};
+use std::io::IsTerminal;
+use std::path::Path;
+use std::sync::Arc;
 use std::time::{Duration, Instant};
+use tail::TailMode;
+use terminal::TerminalGuard;
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    // Setup terminal
-    enable_raw_mode()?;
-    let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen)?;
-    let backend = CrosstermBackend::new(stdout);
-    let mut terminal = Terminal::new(backend)?;
+    let cli = Cli::parse();
+    let config = Config::load(cli.config.as_deref())?;
+
+    let connection = ConnectionSettings {
+        profile: cli.profile,
+        region: cli.region,
+    };
+    let (queue_backend, region): (Box<dyn QueueBackend>, _) = if cli.demo {
+        let demo = InMemoryBackend::demo().with_latency(Duration::from_millis(cli.demo_latency_ms));
+        (Box::new(demo), None)
+    } else {
+        aws_backend(&connection, &config.audit_log()).await
+    };
+
+    if let Some(command) = cli.command {
+        if command.mutates()
+            && let Some(queue) = command.queue()
+        {
+            commands::check_allowed(
+                &config,
+                cli.read_only,
+                queue_name_from_url(queue),
+                cli.confirm.as_deref(),
+                std::io::stdin().is_terminal(),
+            )?;
+        }
+        let queue_backend: Arc<dyn QueueBackend> = queue_backend.into();
+        let result = match command {
+            Command::Export(args) => commands::export::run(queue_backend.as_ref(), args).await,
+            Command::Import(args) => commands::import::run(queue_backend.as_ref(), args).await,
+            Command::Tail(args) => commands::tail::run(queue_backend.as_ref(), args).await,
+            Command::Consume(args) => commands::consume::run(queue_backend.clone(), args).await,
+            Command::Send(args) => commands::send::run(queue_backend.as_ref(), args).await,
+            Command::Lint(args) => {
+                commands::lint::run(queue_backend.as_ref(), args, config.lint).await
+            }
+        };
+        if let Some(e) = queue_backend.take_audit_failure() {
+            eprintln!("Warning: audit log not written: {}", e);
+        }
+        return result;
+    }
+
+    // Setup terminal; dropping the guard restores it on every exit path
+    let mut terminal = TerminalGuard::enter()?;
 
     // Create app
-    let mut app = App::new().await?;
+    let mut app = App::new(queue_backend);
+    app.config = config;
+    app.read_only = cli.read_only;
+    app.connection = connection;
+    app.region = region;
 
     // Initial refresh
-    app.refresh_queues().await?;
+    if let Err(e) = app.refresh_queues().await {
+        app.report_error(&e);
+    }
 
     // Main loop
     let mut last_auto_refresh = Instant::now();
-    let result = run_app(&mut terminal, &mut app, &mut last_auto_refresh).await;
-
-    // Restore terminal
-    disable_raw_mode()?;
-    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
-    terminal.show_cursor()?;
+    run_app(&mut terminal, &mut app, cli.demo, &mut last_auto_refresh).await
+}
 
-    result
+/// Connects to SQS with the given settings. Credentials are only resolved
+/// on the first call, so this succeeds even when they are missing.
+///
+/// Demo runs touch nothing real, so only this backend is audited.
+async fn aws_backend(
+    settings: &ConnectionSettings,
+    audit_log: &Path,
+) -> (Box<dyn QueueBackend>, Option<String>) {
+    let sqs = SqsClient::connect(settings).await;
+    let region = sqs.region().map(str::to_string);
+    let retrying = RetryingBackend::new(Box::new(sqs), RetryPolicy::default());
+    let audited = AuditedBackend::new(Box::new(retrying), AuditLog::new(audit_log));
+    (Box::new(audited), region)
 }
 
 async fn run_app(
-    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    terminal: &mut TerminalGuard,
     app: &mut App,
+    demo: bool,
     last_auto_refresh: &mut Instant,
 ) -> Result<()> {
+    // Only terminal I/O errors end the loop; everything else is shown in the
+    // status bar.
     loop {
         terminal.draw(|f| ui::draw(f, app))?;
 
         // Check for auto-refresh
         if last_auto_refresh.elapsed() >= app.refresh_interval {
-            app.refresh_queues().await?;
             *last_auto_refresh = Instant::now();
+            if let Err(e) = app.refresh_queues().await {
+                app.report_error(&e);
+            }
         }
 
+        // Export or replay one batch per frame so progress stays visible;
+        // the purge watch polls on its own interval
+        if app.export.is_some() {
+            app.step_export().await;
+        }
+        if app.replay.is_some() {
+            app.step_replay().await;
+        }
+        if app.send.is_some() {
+            app.step_send().await;
+        }
+        if app.purge_watch.is_some() {
+            app.step_purge_watch().await;
+        }
+        app.step_tail();
+        app.step_lint();
+        app.check_audit_log();
+
         // Poll for events with a short timeout
-        if let Some(event) = poll_event(Duration::from_millis(100))? {
-            match event {
-                AppEvent::Quit => {
-                    app.quit();
-                    break;
-                }
-                AppEvent::Refresh => {
-                    app.refresh_queues().await?;
-                    *last_auto_refresh = Instant::now();
-                }
-                AppEvent::NextQueue => {
-                    if !app.awaiting_purge_confirmation {
-                        app.next_queue();
-                        app.refresh_selected_details().await?;
-                    }
-                }
-                AppEvent::PreviousQueue => {
-                    if !app.awaiting_purge_confirmation {
-                        app.previous_queue();
-                        app.refresh_selected_details().await?;
-                    }
-                }
-                AppEvent::ToggleFilter => {
-                    if !app.awaiting_purge_confirmation {
-                        app.toggle_filter();
-                        app.refresh_selected_details().await?;
-                    }
-                }
-                AppEvent::PurgeQueue => {
-                    if !app.awaiting_purge_confirmation {
-                        app.request_purge_confirmation();
-                    }
-                }
-                AppEvent::ConfirmPurge => {
-                    if app.awaiting_purge_confirmation {
-                        if let Some((url, name)) = app.begin_purge() {
-                            // Re-render to show "Purging..." before blocking on API call
-                            terminal.draw(|f| ui::draw(f, app))?;
-                            app.execute_purge(&url, &name).await?;
-                        }
-                        *last_auto_refresh = Instant::now();
-                    }
-                }
-                AppEvent::CancelPurge => {
-                    if app.awaiting_purge_confirmation {
-                        app.cancel_purge();
-                    }
+        let key_mode = if app.input.is_some() {
+            KeyMode::TextInput
+        } else if app.peek.is_some() || app.body_view.is_some() {
+            KeyMode::View
+        } else {
+            KeyMode::Normal
+        };
+        if let Some(event) = poll_event(Duration::from_millis(100), key_mode)?
+            && let Err(e) = handle_event(app, event, last_auto_refresh).await
+        {
+            app.report_error(&e);
+        }
+
+        // Confirmed from the Y/N prompt or a typed queue name
+        if app.purge_confirmed {
+            if let Some((url, name)) = app.begin_purge() {
+                // Re-render to show "Purging..." before blocking on API call
+                terminal.draw(|f| ui::draw(f, app))?;
+                if let Err(e) = app.execute_purge(&url, &name).await {
+                    app.report_error(&e);
                 }
             }
+            *last_auto_refresh = Instant::now();
+        }
+
+        // Confirmed delete, or a submitted visibility timeout or move target
+        if app.message_action.is_some() {
+            app.run_message_action().await;
+        }
+
+        // Compose or edit-and-resend: the editor takes over the terminal
+        // until it exits
+        if let Some(request) = app.edit_request.take() {
+            app.hold_for_edit(&request).await;
+            let extension = editor::extension_for(&request.message.body);
+            let edited = terminal.suspend(|| editor::edit(&request.message.body, extension))?;
+            app.send_edited(request, edited).await;
+        }
+
+        // New profile or region, or a retry from the connection screen; a
+        // fresh client resolves credentials again
+        if app.reconnect_requested {
+            app.reconnect_requested = false;
+            if !demo {
+                let (backend, region) = aws_backend(&app.connection, &app.config.audit_log()).await;
+                app.set_backend(backend, region);
+            }
+            if let Err(e) = app.refresh_queues().await {
+                app.report_error(&e);
+            }
+            *last_auto_refresh = Instant::now();
         }
 
         if app.should_quit {
+            app.finish_tail().await;
             break;
         }
     }
 
     Ok(())
 }
+
+async fn handle_event(
+    app: &mut App,
+    event: AppEvent,
+    last_auto_refresh: &mut Instant,
+) -> Result<()> {
+    match event {
+        AppEvent::Quit => app.quit(),
+        AppEvent::Refresh => {
+            if app.peek.is_some() {
+                app.refetch_peek().await;
+            } else if app.connection_error.is_some() {
+                app.reconnect_requested = true;
+            } else {
+                app.refresh_queues().await?;
+                *last_auto_refresh = Instant::now();
+            }
+        }
+        AppEvent::NextQueue => {
+            if app.peek.is_some() {
+                app.next_message();
+            } else if !app.is_prompting() {
+                app.next_queue();
+                app.refresh_selected_details().await?;
+            }
+        }
+        AppEvent::PreviousQueue => {
+            if app.peek.is_some() {
+                app.previous_message();
+            } else if !app.is_prompting() {
+                app.previous_queue();
+                app.refresh_selected_details().await?;
+            }
+        }
+        AppEvent::ToggleFilter => {
+            if !app.is_prompting() {
+                app.toggle_filter();
+                app.refresh_selected_details().await?;
+            }
+        }
+        AppEvent::PurgeQueue => {
+            if !app.is_prompting() && !app.is_busy() {
+                app.request_purge_confirmation();
+            }
+        }
+        AppEvent::Export => {
+            if app.peek.is_some() {
+                if !app.is_prompting() {
+                    app.export_peeked();
+                }
+            } else if !app.is_prompting() && !app.is_busy() {
+                app.start_export(false);
+            }
+        }
+        AppEvent::ExportAndDelete => {
+            if !app.is_prompting() && !app.is_busy() {
+                app.request_export_delete_confirmation();
+            }
+        }
+        AppEvent::Replay => {
+            if !app.is_prompting() && !app.is_busy() {
+                app.request_replay_path();
+            }
+        }
+        AppEvent::TogglePeek => {
+            if !app.is_prompting() && !app.is_busy() {
+                app.toggle_peek().await;
+            }
+        }
+        AppEvent::ToggleMessageSelected => {
+            if !app.is_prompting() {
+                app.toggle_message_selected();
+            }
+        }
+        AppEvent::DeleteMessages => {
+            if !app.is_prompting() {
+                app.request_message_delete();
+            }
+        }
+        AppEvent::ChangeMessageVisibility => {
+            if !app.is_prompting() {
+                app.request_message_visibility();
+            }
+        }
+        AppEvent::MoveMessages => {
+            if !app.is_prompting() {
+                app.request_message_move();
+            }
+        }
+        AppEvent::FilterMessages => {
+            if !app.is_prompting() {
+                app.request_message_filter();
+            }
+        }
+        AppEvent::SelectAllMessages => {
+            if !app.is_prompting() {
+                app.toggle_all_messages_selected();
+            }
+        }
+        AppEvent::CloseView => {
+            if !app.is_prompting() {
+                app.close_view();
+            }
+        }
+        AppEvent::ToggleBodyView => {
+            if !app.is_prompting() {
+                app.toggle_body_view();
+            }
+        }
+        AppEvent::ToggleReceiveGroups => {
+            if !app.is_prompting() {
+                app.toggle_receive_groups();
+            }
+        }
+        AppEvent::ToggleUnwrapEnvelopes => {
+            if !app.is_prompting() {
+                app.toggle_unwrap_envelopes();
+            }
+        }
+        AppEvent::SendMessages => {
+            if !app.is_prompting() && !app.is_busy() {
+                app.request_send_source();
+            }
+        }
+        AppEvent::Compose => {
+            if !app.is_prompting() && !app.is_busy() {
+                if app.peek.is_some() {
+                    app.request_message_resend();
+                } else {
+                    app.request_compose();
+                }
+            }
+        }
+        AppEvent::ToggleTail => {
+            if app.tail.is_some() || (!app.is_prompting() && !app.is_busy() && app.peek.is_none()) {
+                app.toggle_tail();
+            }
+        }
+        AppEvent::TailAndDelete => {
+            if !app.is_prompting() && !app.is_busy() && app.peek.is_none() {
+                app.request_tail_delete_confirmation();
+            }
+        }
+        AppEvent::InputChar(c) => app.input_char(c),
+        AppEvent::InputBackspace => app.input_backspace(),
+        AppEvent::InputCancel => app.cancel_input(),
+        AppEvent::InputSubmit => app.submit_input(),
+        AppEvent::Confirm => {
+            if app.awaiting_export_confirmation {
+                app.start_export(true);
+            } else if app.awaiting_tail_confirmation {
+                app.start_tail(TailMode::Delete);
+            } else if app.awaiting_purge_confirmation {
+                app.confirm_purge();
+            } else if app.pending_message_action.is_some() {
+                app.confirm_message_action();
+            }
+        }
+        AppEvent::ConfirmWithBackup => {
+            if app.awaiting_purge_confirmation {
+                app.begin_backup_then_purge();
+            }
+        }
+        AppEvent::Cancel => {
+            if app.awaiting_purge_confirmation {
+                app.cancel_purge();
+            } else if app.pending_message_action.is_some() {
+                app.cancel_message_action();
+            } else if app.awaiting_export_confirmation || app.export.is_some() {
+                app.cancel_export();
+            } else if app.replay.is_some() {
+                app.cancel_replay();
+            } else if app.send.is_some() {
+                app.cancel_send();
+            } else if app.purge_watch.is_some() {
+                app.stop_purge_watch();
+            } else if app.awaiting_tail_confirmation || app.tail.is_some() {
+                app.stop_tail();
+            }
+        }
+        AppEvent::ToggleRawAttributes => {
+            if !app.is_prompting() {
+                app.toggle_raw_attributes();
+            }
+        }
+        AppEvent::CycleMetricsWindow => {
+            if !app.is_prompting() {
+                app.cycle_metrics_window().await;
+            }
+        }
+        AppEvent::ToggleAuditLog => {
+            if !app.is_prompting() {
+                app.toggle_audit_log();
+            }
+        }
+        AppEvent::SwitchProfile => {
+            if !app.is_prompting() && !app.is_busy() {
+                app.request_profile();
+            }
+        }
+        AppEvent::ChangeRegion => {
+            if !app.is_prompting() && !app.is_busy() {
+                app.request_region();
+            }
+        }
+        AppEvent::ToggleHumanReadable => app.toggle_human_readable(),
+        AppEvent::ToggleTimezone => app.toggle_timezone(),
+        AppEvent::ScrollDetailsDown => app.scroll_details_down(),
+        AppEvent::ScrollDetailsUp => app.scroll_details_up(),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,185 @@
+use crate::backend::{BackendResult, QueueBackend};
+use crate::types::{MetricPoint, MetricQuery, QueueMetric};
+use chrono::{DateTime, DurationRound, Utc};
+use std::fmt;
+
+/// How much CloudWatch history the metrics panel shows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetricWindow {
+    Hour,
+    SixHours,
+    Day,
+    Week,
+}
+
+impl MetricWindow {
+    /// The next longer window, or `None` after the longest to close the
+    /// panel.
+    pub fn next(self) -> Option<Self> {
+        match self {
+            MetricWindow::Hour => Some(MetricWindow::SixHours),
+            MetricWindow::SixHours => Some(MetricWindow::Day),
+            MetricWindow::Day => Some(MetricWindow::Week),
+            MetricWindow::Week => None,
+        }
+    }
+
+    pub fn duration(self) -> chrono::Duration {
+        match self {
+            MetricWindow::Hour => chrono::Duration::hours(1),
+            MetricWindow::SixHours => chrono::Duration::hours(6),
+            MetricWindow::Day => chrono::Duration::days(1),
+            MetricWindow::Week => chrono::Duration::weeks(1),
+        }
+    }
+
+    /// Seconds per datapoint, keeping each series to under 200 points.
+    pub fn period_secs(self) -> i32 {
+        match self {
+            MetricWindow::Hour => 60,
+            MetricWindow::SixHours => 300,
+            MetricWindow::Day => 900,
+            MetricWindow::Week => 3_600,
+        }
+    }
+}
+
+impl fmt::Display for MetricWindow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            MetricWindow::Hour => "1h",
+            MetricWindow::SixHours => "6h",
+            MetricWindow::Day => "1d",
+            MetricWindow::Week => "7d",
+        })
+    }
+}
+
+/// One metric over the window, with a value for every period; periods
+/// CloudWatch had no datapoint for are zero.
+#[derive(Debug, Clone)]
+pub struct MetricSeries {
+    pub metric: QueueMetric,
+    pub values: Vec<f64>,
+}
+
+impl MetricSeries {
+    pub fn total(&self) -> f64 {
+        self.values.iter().sum()
+    }
+
+    /// The value of the latest period that has one.
+    pub fn latest(&self) -> Option<f64> {
+        self.values.iter().rev().copied().find(|&v| v > 0.0)
+    }
+}
+
+/// The `QueueMetric::ALL` series for one queue.
+#[derive(Debug, Clone)]
+pub struct QueueMetrics {
+    pub queue_name: String,
+    pub window: MetricWindow,
+    pub series: Vec<MetricSeries>,
+}
+
+impl QueueMetrics {
+    /// Fetches every metric for the window ending at `now`, rounded down to
+    /// a whole period so the buckets line up with CloudWatch's.
+    pub async fn fetch(
+        backend: &dyn QueueBackend,
+        queue_name: &str,
+        queue_url: &str,
+        window: MetricWindow,
+        now: DateTime<Utc>,
+    ) -> BackendResult<Self> {
+        let period = chrono::Duration::seconds(window.period_secs().into());
+        let end = now.duration_trunc(period).unwrap_or(now) + period;
+        let start = end - window.duration();
+        let mut series = Vec::new();
+        for metric in QueueMetric::ALL {
+            let query = MetricQuery {
+                metric,
+                start,
+                end,
+                period_secs: window.period_secs(),
+            };
+            let points = backend.queue_metric(queue_url, &query).await?;
+            series.push(MetricSeries {
+                metric,
+                values: dense(&points, &query),
+            });
+        }
+        Ok(Self {
+            queue_name: queue_name.to_string(),
+            window,
+            series,
+        })
+    }
+
+    /// Per-minute rate of a count metric over the window.
+    pub fn per_minute(&self, series: &MetricSeries) -> f64 {
+        series.total() / self.window.duration().num_minutes() as f64
+    }
+}
+
+/// A value per period of the query, oldest first.
+fn dense(points: &[MetricPoint], query: &MetricQuery) -> Vec<f64> {
+    let period = i64::from(query.period_secs.max(1));
+    let buckets = ((query.end - query.start).num_seconds() / period).max(0) as usize;
+    let mut values = vec![0.0; buckets];
+    for point in points {
+        let offset = (point.timestamp - query.start).num_seconds();
+        if offset >= 0
+            && let Some(value) = values.get_mut((offset / period) as usize)
+        {
+            *value = point.value;
+        }
+    }
+    values
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::memory::{FakeQueue, InMemoryBackend};
+
+    #[tokio::test]
+    async fn fills_every_period_of_the_window() {
+        let now = Utc::now();
+        let queue = FakeQueue::new("orders", 2)
+            .with_activity(
+                now - chrono::Duration::minutes(30),
+                QueueMetric::NumberOfMessagesSent,
+                40,
+            )
+            .with_activity(now, QueueMetric::NumberOfMessagesSent, 20)
+            .with_activity(now, QueueMetric::NumberOfMessagesDeleted, 5)
+            .with_activity(
+                now - chrono::Duration::hours(3),
+                QueueMetric::NumberOfMessagesSent,
+                1_000,
+            );
+        let url = queue.url();
+        let backend = InMemoryBackend::new().with_queue(queue);
+
+        let metrics = QueueMetrics::fetch(&backend, "orders", &url, MetricWindow::Hour, now)
+            .await
+            .unwrap();
+        assert_eq!(metrics.series.len(), QueueMetric::ALL.len());
+        let sent = &metrics.series[0];
+        assert_eq!(sent.values.len(), 60);
+        assert_eq!(sent.total(), 60.0);
+        assert_eq!(sent.latest(), Some(20.0));
+        assert_eq!(metrics.per_minute(sent), 1.0);
+        assert_eq!(metrics.series[2].total(), 5.0);
+        assert_eq!(metrics.series[3].latest(), None);
+        let age = &metrics.series[4];
+        assert!(age.latest().is_some_and(|secs| secs >= 120.0));
+
+        let metrics = QueueMetrics::fetch(&backend, "orders", &url, MetricWindow::Day, now)
+            .await
+            .unwrap();
+        assert_eq!(metrics.series[0].values.len(), 96);
+        assert_eq!(metrics.series[0].total(), 1_060.0);
+    }
+}
@@ -0,0 +1,502 @@
+use crate::backend::{BackendResult, MAX_BATCH, QueueBackend};
+use crate::filter::MessageFilter;
+use crate::receives::receive_count;
+use crate::types::{
+    BatchFailure, DeleteEntry, OutgoingMessage, QueueMessage, ReceiveOptions, VisibilityEntry,
+};
+use chrono::Utc;
+use std::collections::HashSet;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Most messages a peek holds, received ten per ReceiveMessage call.
+pub const PEEK_LIMIT: usize = 100;
+/// How long peeked messages stay hidden from other consumers, and so how
+/// long their receipt handles can be used.
+pub const PEEK_VISIBILITY_SECS: i32 = 30;
+/// The longest visibility timeout SQS accepts (12 hours).
+pub const MAX_VISIBILITY_SECS: i32 = 43_200;
+
+/// A received message and what is known about its receipt handle.
+pub struct PeekedMessage {
+    pub message: QueueMessage,
+    /// When the message becomes visible again; SQS stops accepting its
+    /// receipt handle from then on.
+    pub visible_at: Instant,
+    pub selected: bool,
+}
+
+impl PeekedMessage {
+    pub fn handle_expired(&self, now: Instant) -> bool {
+        now >= self.visible_at
+    }
+}
+
+/// Something to do with the selected messages of a peek.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MessageAction {
+    Delete,
+    /// Hide the messages for this many more seconds; 0 releases them.
+    ChangeVisibility(i32),
+    /// Send a copy to another queue, then delete the original.
+    Move {
+        target: String,
+    },
+}
+
+/// What an action did to its target messages.
+#[derive(Debug, Default)]
+pub struct ActionReport {
+    pub done: usize,
+    /// Skipped without calling SQS because their visibility timeout ran out.
+    pub expired: usize,
+    pub failed: Vec<BatchFailure>,
+}
+
+/// Messages received from one queue and held in flight so they can be
+/// inspected and acted on.
+pub struct PeekView {
+    pub queue_name: String,
+    pub queue_url: String,
+    pub messages: Vec<PeekedMessage>,
+    /// Indices into `messages` that pass the filter, in order.
+    pub shown: Vec<usize>,
+    /// Position in `shown`.
+    pub cursor: usize,
+    pub filter: Option<MessageFilter>,
+    /// Shows the most received messages first, with a receive count
+    /// analysis beside the list.
+    pub grouped: bool,
+    /// The queue's redrive `maxReceiveCount`, if it has a dead-letter queue.
+    pub max_receive_count: Option<u32>,
+}
+
+impl PeekView {
+    /// Receives up to `PEEK_LIMIT` messages, hiding them from other
+    /// consumers for `PEEK_VISIBILITY_SECS`.
+    pub async fn fetch(
+        backend: &dyn QueueBackend,
+        queue_name: &str,
+        queue_url: &str,
+        filter: Option<MessageFilter>,
+    ) -> BackendResult<Self> {
+        let mut messages = Vec::new();
+        while messages.len() < PEEK_LIMIT {
+            let received_at = Instant::now();
+            let batch = backend
+                .receive_messages(
+                    queue_url,
+                    ReceiveOptions {
+                        max_messages: (PEEK_LIMIT - messages.len()).min(MAX_BATCH) as i32,
+                        visibility_timeout: Some(PEEK_VISIBILITY_SECS),
+                        wait_time_seconds: 0,
+                    },
+                )
+                .await?;
+            if batch.is_empty() {
+                break;
+            }
+            let visible_at = received_at + Duration::from_secs(PEEK_VISIBILITY_SECS as u64);
+            messages.extend(batch.into_iter().map(|message| PeekedMessage {
+                message,
+                visible_at,
+                selected: false,
+            }));
+        }
+
+        let mut view = Self {
+            queue_name: queue_name.to_string(),
+            queue_url: queue_url.to_string(),
+            messages,
+            shown: Vec::new(),
+            cursor: 0,
+            filter,
+            grouped: false,
+            max_receive_count: None,
+        };
+        view.update_shown();
+        Ok(view)
+    }
+
+    /// The message under the cursor.
+    pub fn current(&self) -> Option<&PeekedMessage> {
+        self.messages.get(*self.shown.get(self.cursor)?)
+    }
+
+    pub fn set_filter(&mut self, filter: Option<MessageFilter>) {
+        self.filter = filter;
+        self.cursor = 0;
+        self.update_shown();
+    }
+
+    pub fn toggle_grouped(&mut self) {
+        self.grouped = !self.grouped;
+        self.cursor = 0;
+        self.update_shown();
+    }
+
+    fn update_shown(&mut self) {
+        let now = Utc::now();
+        self.shown = (0..self.messages.len())
+            .filter(|&i| {
+                self.filter
+                    .as_ref()
+                    .is_none_or(|filter| filter.matches(&self.messages[i].message, now))
+            })
+            .collect();
+        if self.grouped {
+            // Stable, so messages with the same count keep their order
+            self.shown
+                .sort_by_key(|&i| std::cmp::Reverse(receive_count(&self.messages[i].message)));
+        }
+        self.cursor = self.cursor.min(self.shown.len().saturating_sub(1));
+    }
+
+    pub fn next(&mut self) {
+        if !self.shown.is_empty() {
+            self.cursor = (self.cursor + 1) % self.shown.len();
+        }
+    }
+
+    pub fn previous(&mut self) {
+        if !self.shown.is_empty() {
+            self.cursor = self.cursor.checked_sub(1).unwrap_or(self.shown.len() - 1);
+        }
+    }
+
+    pub fn toggle_selected(&mut self) {
+        if let Some(&index) = self.shown.get(self.cursor) {
+            self.messages[index].selected = !self.messages[index].selected;
+        }
+    }
+
+    /// Selects every shown message, or clears the selection if they all
+    /// were selected already. Returns how many are selected afterwards.
+    pub fn toggle_all_shown(&mut self) -> usize {
+        let select = !self.shown.iter().all(|&i| self.messages[i].selected);
+        for &i in &self.shown {
+            self.messages[i].selected = select;
+        }
+        if select { self.shown.len() } else { 0 }
+    }
+
+    /// Indices an action applies to: the shown messages that are selected,
+    /// or the one under the cursor when none are.
+    pub fn targets(&self) -> Vec<usize> {
+        let selected: Vec<usize> = self
+            .shown
+            .iter()
+            .copied()
+            .filter(|&i| self.messages[i].selected)
+            .collect();
+        match self.shown.get(self.cursor) {
+            Some(&current) if selected.is_empty() => vec![current],
+            _ => selected,
+        }
+    }
+
+    /// Runs `action` on the target messages, ten per request. Messages
+    /// whose receipt handle has expired are skipped; deleted and moved
+    /// messages leave the view.
+    pub async fn apply(
+        &mut self,
+        backend: &dyn QueueBackend,
+        action: &MessageAction,
+    ) -> BackendResult<ActionReport> {
+        let now = Instant::now();
+        let (expired, live): (Vec<usize>, Vec<usize>) = self
+            .targets()
+            .into_iter()
+            .partition(|&i| self.messages[i].handle_expired(now));
+        let mut report = ActionReport {
+            expired: expired.len(),
+            ..ActionReport::default()
+        };
+        if live.is_empty() {
+            return Ok(report);
+        }
+
+        let removed = match action {
+            MessageAction::Delete => {
+                report.failed = self.delete(backend, &live).await?;
+                succeeded(&live, &report.failed)
+            }
+            MessageAction::ChangeVisibility(secs) => {
+                for chunk in live.chunks(MAX_BATCH) {
+                    let entries: Vec<VisibilityEntry> = chunk
+                        .iter()
+                        .map(|&i| VisibilityEntry {
+                            id: i.to_string(),
+                            receipt_handle: self.messages[i].message.receipt_handle.clone(),
+                            visibility_timeout: *secs,
+                        })
+                        .collect();
+                    report.failed.extend(
+                        backend
+                            .change_message_visibility(&self.queue_url, &entries)
+                            .await?,
+                    );
+                }
+                let visible_at = now + Duration::from_secs(*secs as u64);
+                for i in succeeded(&live, &report.failed) {
+                    self.messages[i].visible_at = visible_at;
+                }
+                Vec::new()
+            }
+            MessageAction::Move { target } => {
+                let target_url = backend.queue_url(target).await?;
+                let mut copied = Vec::new();
+                for chunk in live.chunks(MAX_BATCH) {
+                    let outgoing: Vec<OutgoingMessage> = chunk
+                        .iter()
+                        .map(|&i| copy_for_queue(i, &self.messages[i].message, &target_url))
+                        .collect();
+                    let sent = backend.send_messages(&target_url, &outgoing).await?;
+                    copied.extend(sent.sent.iter().filter_map(|s| s.id.parse::<usize>().ok()));
+                    report.failed.extend(sent.failed);
+                }
+                let not_deleted = self.delete(backend, &copied).await?;
+                report
+                    .failed
+                    .extend(not_deleted.into_iter().map(|failure| BatchFailure {
+                        message: format!("copied but not deleted: {}", failure.message),
+                        ..failure
+                    }));
+                succeeded(&copied, &report.failed)
+            }
+        };
+
+        report.done = live.len() - report.failed.len();
+        self.remove(&removed);
+        Ok(report)
+    }
+
+    /// Deletes one message by ID and drops it from the view. A message
+    /// whose receipt handle has run out is reported as not in flight
+    /// without calling SQS.
+    pub async fn delete_one(
+        &mut self,
+        backend: &dyn QueueBackend,
+        message_id: &str,
+    ) -> BackendResult<Vec<BatchFailure>> {
+        let Some(index) = self
+            .messages
+            .iter()
+            .position(|m| m.message.message_id == message_id)
+        else {
+            return Ok(Vec::new());
+        };
+        if self.messages[index].handle_expired(Instant::now()) {
+            return Ok(vec![BatchFailure {
+                id: index.to_string(),
+                code: "MessageNotInflight".to_string(),
+                message: "visibility timeout ran out".to_string(),
+            }]);
+        }
+        let failures = self.delete(backend, &[index]).await?;
+        if failures.is_empty() {
+            self.remove(&[index]);
+        }
+        Ok(failures)
+    }
+
+    /// Hides one message by ID for `secs` more seconds, so it stays in
+    /// flight while it is worked on; 0 releases it. A message whose receipt
+    /// handle has run out is reported as not in flight without calling SQS.
+    pub async fn change_visibility_one(
+        &mut self,
+        backend: &dyn QueueBackend,
+        message_id: &str,
+        secs: i32,
+    ) -> BackendResult<Vec<BatchFailure>> {
+        let now = Instant::now();
+        let Some(index) = self
+            .messages
+            .iter()
+            .position(|m| m.message.message_id == message_id)
+        else {
+            return Ok(Vec::new());
+        };
+        if self.messages[index].handle_expired(now) {
+            return Ok(vec![BatchFailure {
+                id: index.to_string(),
+                code: "MessageNotInflight".to_string(),
+                message: "visibility timeout ran out".to_string(),
+            }]);
+        }
+        let entry = VisibilityEntry {
+            id: index.to_string(),
+            receipt_handle: self.messages[index].message.receipt_handle.clone(),
+            visibility_timeout: secs,
+        };
+        let failures = backend
+            .change_message_visibility(&self.queue_url, &[entry])
+            .await?;
+        if failures.is_empty() {
+            self.messages[index].visible_at = now + Duration::from_secs(secs as u64);
+        }
+        Ok(failures)
+    }
+
+    async fn delete(
+        &self,
+        backend: &dyn QueueBackend,
+        indices: &[usize],
+    ) -> BackendResult<Vec<BatchFailure>> {
+        let mut failures = Vec::new();
+        for chunk in indices.chunks(MAX_BATCH) {
+            let entries: Vec<DeleteEntry> = chunk
+                .iter()
+                .map(|&i| DeleteEntry {
+                    id: i.to_string(),
+                    receipt_handle: self.messages[i].message.receipt_handle.clone(),
+                })
+                .collect();
+            failures.extend(backend.delete_messages(&self.queue_url, &entries).await?);
+        }
+        Ok(failures)
+    }
+
+    fn remove(&mut self, indices: &[usize]) {
+        let indices: HashSet<usize> = indices.iter().copied().collect();
+        let mut index = 0;
+        self.messages.retain(|_| {
+            let keep = !indices.contains(&index);
+            index += 1;
+            keep
+        });
+        self.update_shown();
+    }
+}
+
+/// The indices whose batch entry (id = index) is not among `failures`.
+fn succeeded(indices: &[usize], failures: &[BatchFailure]) -> Vec<usize> {
+    indices
+        .iter()
+        .copied()
+        .filter(|i| !failures.iter().any(|f| f.id == i.to_string()))
+        .collect()
+}
+
+fn copy_for_queue(index: usize, message: &QueueMessage, queue_url: &str) -> OutgoingMessage {
+    let mut outgoing = OutgoingMessage {
+        id: index.to_string(),
+        body: message.body.clone(),
+        message_attributes: message.message_attributes.clone(),
+        message_group_id: message.attributes.get("MessageGroupId").cloned(),
+        message_deduplication_id: message.attributes.get("MessageDeduplicationId").cloned(),
+        delay_seconds: None,
+    };
+    outgoing.prepare_for_queue(queue_url, &message.message_id);
+    outgoing
+}
+
+/// A batch failure in words, calling out receipt handles that SQS no longer
+/// accepts.
+pub fn describe_failure(failure: &BatchFailure) -> String {
+    match failure.code.as_str() {
+        "ReceiptHandleIsInvalid" | "MessageNotInflight" => {
+            "receipt handle expired, press R to fetch the message again".to_string()
+        }
+        _ if failure.message.contains("receipt handle") => {
+            format!("receipt handle expired ({})", failure.message)
+        }
+        _ => format!("{}: {}", failure.code, failure.message),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::memory::{FakeQueue, InMemoryBackend};
+
+    async fn peek(fake: &InMemoryBackend, queue: &str) -> PeekView {
+        let url = fake.queue_url(queue).await.unwrap();
+        PeekView::fetch(fake, queue, &url, None).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn deletes_the_selected_messages() {
+        let fake = InMemoryBackend::new().with_queue(FakeQueue::new("orders", 4));
+        let mut view = peek(&fake, "orders").await;
+        assert_eq!(view.messages.len(), 4);
+
+        view.toggle_selected();
+        view.next();
+        view.next();
+        view.toggle_selected();
+        let deleted = [0, 2].map(|i| view.messages[i].message.message_id.clone());
+
+        let report = view.apply(&fake, &MessageAction::Delete).await.unwrap();
+        assert_eq!(report.done, 2);
+        assert!(report.failed.is_empty());
+        assert_eq!(view.messages.len(), 2);
+        assert!(
+            view.messages
+                .iter()
+                .all(|m| !deleted.contains(&m.message.message_id))
+        );
+
+        let details = fake.get_queue_details(&view.queue_url).await.unwrap();
+        assert_eq!(crate::backend::approximate_total(&details), Some(2));
+    }
+
+    #[tokio::test]
+    async fn moves_the_message_under_the_cursor() {
+        let fake = InMemoryBackend::new()
+            .with_queue(FakeQueue::new("orders-dlq", 2))
+            .with_queue(FakeQueue::new("orders", 0));
+        let mut view = peek(&fake, "orders-dlq").await;
+        let body = view.messages[0].message.body.clone();
+
+        let action = MessageAction::Move {
+            target: "orders".to_string(),
+        };
+        let report = view.apply(&fake, &action).await.unwrap();
+        assert_eq!(report.done, 1);
+        assert_eq!(view.messages.len(), 1);
+
+        let target = peek(&fake, "orders").await;
+        assert_eq!(target.messages.len(), 1);
+        assert_eq!(target.messages[0].message.body, body);
+    }
+
+    #[tokio::test]
+    async fn releasing_makes_messages_visible_again() {
+        let fake = InMemoryBackend::new().with_queue(FakeQueue::new("orders", 1));
+        let mut view = peek(&fake, "orders").await;
+        assert!(peek(&fake, "orders").await.messages.is_empty());
+
+        let report = view
+            .apply(&fake, &MessageAction::ChangeVisibility(0))
+            .await
+            .unwrap();
+        assert_eq!(report.done, 1);
+        assert!(view.messages[0].handle_expired(Instant::now()));
+        assert_eq!(peek(&fake, "orders").await.messages.len(), 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn expired_receipt_handles_are_skipped_or_reported() {
+        let fake = InMemoryBackend::new().with_queue(FakeQueue::new("orders", 2));
+        let mut view = peek(&fake, "orders").await;
+
+        // Someone else received the second message after it reappeared, so
+        // the handle held here is stale even though it has not timed out.
+        view.messages[1].message.receipt_handle = "stale".to_string();
+        view.toggle_selected();
+        view.next();
+        view.toggle_selected();
+        let report = view.apply(&fake, &MessageAction::Delete).await.unwrap();
+        assert_eq!(report.done, 1);
+        assert_eq!(report.failed.len(), 1);
+        assert_eq!(
+            describe_failure(&report.failed[0]),
+            "receipt handle expired, press R to fetch the message again"
+        );
+
+        tokio::time::advance(Duration::from_secs(PEEK_VISIBILITY_SECS as u64)).await;
+        let report = view.apply(&fake, &MessageAction::Delete).await.unwrap();
+        assert_eq!((report.done, report.expired), (0, 1));
+    }
+}
@@ -0,0 +1,212 @@
+use crate::types::{QueueDetails, QueueMessage};
+use chrono::{DateTime, Utc};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Share of retried messages above which failures look like a consumer
+/// problem rather than a few bad messages.
+const BROAD_FAILURE_SHARE: f64 = 0.5;
+
+/// Upper bounds (in seconds) of the first-receive age buckets; the last
+/// bucket takes everything older.
+pub const AGE_BUCKETS: [(i64, &str); 5] = [
+    (60, "< 1m"),
+    (300, "< 5m"),
+    (3_600, "< 1h"),
+    (86_400, "< 1d"),
+    (i64::MAX, ">= 1d"),
+];
+
+fn redrive_policy(details: &QueueDetails) -> Option<serde_json::Value> {
+    serde_json::from_str(details.attributes.get("RedrivePolicy")?).ok()
+}
+
+/// The redrive `maxReceiveCount` of a queue, if it has a dead-letter queue.
+pub fn max_receive_count(details: &QueueDetails) -> Option<u32> {
+    let json = redrive_policy(details)?;
+    // SQS has returned the count both as a number and as a string
+    match &json["maxReceiveCount"] {
+        serde_json::Value::Number(n) => n.as_u64().map(|n| n as u32),
+        serde_json::Value::String(s) => s.parse().ok(),
+        _ => None,
+    }
+}
+
+/// The ARN of a queue's dead-letter queue.
+pub fn dead_letter_target_arn(details: &QueueDetails) -> Option<String> {
+    redrive_policy(details)?["deadLetterTargetArn"]
+        .as_str()
+        .map(str::to_string)
+}
+
+pub fn receive_count(message: &QueueMessage) -> Option<u32> {
+    message
+        .attributes
+        .get("ApproximateReceiveCount")?
+        .parse()
+        .ok()
+}
+
+/// Whether a message has at most a fifth of its receives left (and at least
+/// the last one) before SQS moves it to the dead-letter queue.
+pub fn near_redrive(receives: u32, max_receive_count: u32) -> bool {
+    receives + (max_receive_count / 5).max(1) >= max_receive_count
+}
+
+/// How a set of peeked messages has been failing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Diagnosis {
+    /// Every message is on its first receive: the peek's own.
+    NoRetries,
+    /// Only some messages keep coming back.
+    PoisonMessages { retried: usize },
+    /// Most messages have been received and not deleted before.
+    BroadFailure { retried: usize },
+}
+
+impl fmt::Display for Diagnosis {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Diagnosis::NoRetries => f.write_str("No retried messages"),
+            Diagnosis::PoisonMessages { retried } => write!(
+                f,
+                "Likely poison messages: {} keep failing while the rest are new",
+                retried
+            ),
+            Diagnosis::BroadFailure { retried } => write!(
+                f,
+                "Likely a broad consumer failure: {} messages have been retried",
+                retried
+            ),
+        }
+    }
+}
+
+/// Receive counts and first-receive ages of a set of messages.
+///
+/// Receiving a message counts as a receive, so every peeked message has been
+/// received at least once; only counts above one mean a consumer failed.
+#[derive(Debug, Default)]
+pub struct ReceiveAnalysis {
+    pub total: usize,
+    /// Messages per receive count.
+    pub counts: BTreeMap<u32, usize>,
+    /// Messages per `AGE_BUCKETS` entry, by time since first receive.
+    pub ages: [usize; AGE_BUCKETS.len()],
+    /// Messages close to being moved to the dead-letter queue.
+    pub near_redrive: usize,
+    pub max_receive_count: Option<u32>,
+}
+
+impl ReceiveAnalysis {
+    pub fn of<'a>(
+        messages: impl IntoIterator<Item = &'a QueueMessage>,
+        max_receive_count: Option<u32>,
+        now: DateTime<Utc>,
+    ) -> Self {
+        let mut analysis = Self {
+            max_receive_count,
+            ..Self::default()
+        };
+        for message in messages {
+            analysis.total += 1;
+            let receives = receive_count(message).unwrap_or(0);
+            *analysis.counts.entry(receives).or_default() += 1;
+            if max_receive_count.is_some_and(|max| near_redrive(receives, max)) {
+                analysis.near_redrive += 1;
+            }
+            let first_receive = message
+                .attributes
+                .get("ApproximateFirstReceiveTimestamp")
+                .and_then(|ms| ms.parse::<i64>().ok())
+                .and_then(DateTime::from_timestamp_millis);
+            if let Some(first) = first_receive {
+                let age = (now - first).num_seconds();
+                let bucket = AGE_BUCKETS.iter().position(|&(upper, _)| age < upper);
+                analysis.ages[bucket.unwrap_or(AGE_BUCKETS.len() - 1)] += 1;
+            }
+        }
+        analysis
+    }
+
+    pub fn retried(&self) -> usize {
+        self.counts.range(2..).map(|(_, n)| n).sum()
+    }
+
+    pub fn diagnosis(&self) -> Diagnosis {
+        let retried = self.retried();
+        if retried == 0 {
+            Diagnosis::NoRetries
+        } else if retried as f64 > self.total as f64 * BROAD_FAILURE_SHARE {
+            Diagnosis::BroadFailure { retried }
+        } else {
+            Diagnosis::PoisonMessages { retried }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(receives: u32, first_receive_secs_ago: i64, now: DateTime<Utc>) -> QueueMessage {
+        let first = now - chrono::Duration::seconds(first_receive_secs_ago);
+        QueueMessage {
+            message_id: format!("m-{}", receives),
+            receipt_handle: String::new(),
+            body: String::new(),
+            md5_of_body: None,
+            attributes: [
+                ("ApproximateReceiveCount", receives.to_string()),
+                (
+                    "ApproximateFirstReceiveTimestamp",
+                    first.timestamp_millis().to_string(),
+                ),
+            ]
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v))
+            .collect(),
+            message_attributes: BTreeMap::new(),
+        }
+    }
+
+    #[test]
+    fn tells_poison_messages_from_a_broad_failure() {
+        let now = Utc::now();
+        let mut messages: Vec<QueueMessage> = (0..8).map(|_| message(1, 5, now)).collect();
+        messages.push(message(4, 600, now));
+        messages.push(message(5, 90_000, now));
+
+        let analysis = ReceiveAnalysis::of(&messages, Some(5), now);
+        assert_eq!(analysis.counts, BTreeMap::from([(1, 8), (4, 1), (5, 1)]));
+        assert_eq!(analysis.ages, [8, 0, 1, 0, 1]);
+        assert_eq!(analysis.near_redrive, 2);
+        assert_eq!(
+            analysis.diagnosis(),
+            Diagnosis::PoisonMessages { retried: 2 }
+        );
+
+        let retried: Vec<QueueMessage> = (0..3).map(|_| message(2, 30, now)).collect();
+        let analysis = ReceiveAnalysis::of(&retried, None, now);
+        assert_eq!(analysis.near_redrive, 0);
+        assert_eq!(analysis.diagnosis(), Diagnosis::BroadFailure { retried: 3 });
+    }
+
+    #[test]
+    fn reads_max_receive_count_from_the_redrive_policy() {
+        let mut details = QueueDetails::default();
+        assert_eq!(max_receive_count(&details), None);
+        details.attributes.insert(
+            "RedrivePolicy".to_string(),
+            r#"{"deadLetterTargetArn":"arn:aws:sqs:us-east-1:1:dlq","maxReceiveCount":"10"}"#
+                .to_string(),
+        );
+        assert_eq!(max_receive_count(&details), Some(10));
+        assert_eq!(
+            dead_letter_target_arn(&details).as_deref(),
+            Some("arn:aws:sqs:us-east-1:1:dlq")
+        );
+        assert!(near_redrive(8, 10) && !near_redrive(7, 10));
+        assert!(near_redrive(1, 2) && !near_redrive(1, 3));
+    }
+}
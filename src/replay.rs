@@ -0,0 +1,367 @@
+use crate::archive::ArchivedMessage;
+use crate::backend::{MAX_BATCH, QueueBackend};
+use crate::jsonpath::JsonPath;
+use crate::types::OutgoingMessage;
+use anyhow::{Result, anyhow};
+use serde::Serialize;
+use std::time::Duration;
+use tokio::time::Instant;
+
+#[derive(Debug, Clone, Default)]
+pub struct ReplayOptions {
+    /// Upper bound on messages sent per second.
+    pub rate_per_second: Option<f64>,
+    /// Build and report every message without sending anything.
+    pub dry_run: bool,
+    /// Replace each body with the value at this path in the original body.
+    pub transform: Option<JsonPath>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum ReplayStatus {
+    Sent { message_id: String },
+    Failed { reason: String },
+    DryRun { body: String },
+}
+
+/// What happened to one archived message.
+#[derive(Debug, Clone, Serialize)]
+pub struct ReplayOutcome {
+    pub source_message_id: String,
+    #[serde(flatten)]
+    pub status: ReplayStatus,
+}
+
+/// Sends archived messages back into a queue one SendMessageBatch at a time.
+pub struct Replayer {
+    queue_url: String,
+    records: Vec<ArchivedMessage>,
+    options: ReplayOptions,
+    next: usize,
+    next_batch_at: Option<Instant>,
+    pub sent: usize,
+    /// Messages a dry run built without sending.
+    pub would_send: usize,
+    pub failed: usize,
+}
+
+impl Replayer {
+    pub fn new(queue_url: &str, records: Vec<ArchivedMessage>, options: ReplayOptions) -> Self {
+        Self {
+            queue_url: queue_url.to_string(),
+            records,
+            options,
+            next: 0,
+            next_batch_at: None,
+            sent: 0,
+            would_send: 0,
+            failed: 0,
+        }
+    }
+
+    pub fn total(&self) -> usize {
+        self.records.len()
+    }
+
+    pub fn processed(&self) -> usize {
+        self.next
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.next >= self.records.len()
+    }
+
+    fn batch_size(&self) -> usize {
+        match self.options.rate_per_second {
+            Some(rate) => (rate.ceil() as usize).clamp(1, MAX_BATCH),
+            None => MAX_BATCH,
+        }
+    }
+
+    /// Sends the next batch, waiting first if the rate limit requires it, and
+    /// returns the outcome of every message in the batch.
+    pub async fn step(&mut self, backend: &dyn QueueBackend) -> Result<Vec<ReplayOutcome>> {
+        let end = (self.next + self.batch_size()).min(self.records.len());
+        let batch = &self.records[self.next..end];
+        if batch.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        if let Some(at) = self.next_batch_at {
+            tokio::time::sleep_until(at).await;
+        }
+        if let Some(rate) = self.options.rate_per_second.filter(|r| *r > 0.0) {
+            self.next_batch_at =
+                Some(Instant::now() + Duration::from_secs_f64(batch.len() as f64 / rate));
+        }
+
+        let mut outcomes = Vec::with_capacity(batch.len());
+        let mut outgoing = Vec::with_capacity(batch.len());
+        for (index, record) in batch.iter().enumerate() {
+            match self.build_message(index, record) {
+                Ok(message) if self.options.dry_run => outcomes.push(ReplayOutcome {
+                    source_message_id: record.message_id.clone(),
+                    status: ReplayStatus::DryRun { body: message.body },
+                }),
+                Ok(message) => outgoing.push(message),
+                Err(e) => outcomes.push(ReplayOutcome {
+                    source_message_id: record.message_id.clone(),
+                    status: ReplayStatus::Failed {
+                        reason: e.to_string(),
+                    },
+                }),
+            }
+        }
+
+        if !outgoing.is_empty() {
+            let source_id = |id: &str| {
+                id.parse::<usize>()
+                    .ok()
+                    .and_then(|i| batch.get(i))
+                    .map(|r| r.message_id.clone())
+                    .unwrap_or_else(|| id.to_string())
+            };
+            match backend.send_messages(&self.queue_url, &outgoing).await {
+                Ok(result) => {
+                    outcomes.extend(result.sent.into_iter().map(|sent| ReplayOutcome {
+                        source_message_id: source_id(&sent.id),
+                        status: ReplayStatus::Sent {
+                            message_id: sent.message_id,
+                        },
+                    }));
+                    outcomes.extend(result.failed.into_iter().map(|failure| ReplayOutcome {
+                        source_message_id: source_id(&failure.id),
+                        status: ReplayStatus::Failed {
+                            reason: format!("{}: {}", failure.code, failure.message),
+                        },
+                    }));
+                }
+                Err(e) => outcomes.extend(outgoing.iter().map(|message| ReplayOutcome {
+                    source_message_id: source_id(&message.id),
+                    status: ReplayStatus::Failed {
+                        reason: e.to_string(),
+                    },
+                })),
+            }
+        }
+
+        self.next = end;
+        for outcome in &outcomes {
+            match outcome.status {
+                ReplayStatus::Sent { .. } => self.sent += 1,
+                ReplayStatus::DryRun { .. } => self.would_send += 1,
+                ReplayStatus::Failed { .. } => self.failed += 1,
+            }
+        }
+        Ok(outcomes)
+    }
+
+    fn build_message(&self, index: usize, record: &ArchivedMessage) -> Result<OutgoingMessage> {
+        let body = match &self.options.transform {
+            Some(path) => transform_body(path, &record.body)?,
+            None => record.body.clone(),
+        };
+        let mut message = OutgoingMessage {
+            id: index.to_string(),
+            body,
+            message_attributes: record.message_attributes.clone(),
+            message_group_id: record.attributes.get("MessageGroupId").cloned(),
+            message_deduplication_id: record.attributes.get("MessageDeduplicationId").cloned(),
+            delay_seconds: None,
+        };
+        message.prepare_for_queue(&self.queue_url, &record.message_id);
+        Ok(message)
+    }
+}
+
+/// Applies a path filter to a JSON body. Strings are emitted raw, like
+/// `jq -r`, so `.Message` unwraps an SNS envelope; anything else is
+/// re-serialized as compact JSON.
+fn transform_body(path: &JsonPath, body: &str) -> Result<String> {
+    let value: serde_json::Value =
+        serde_json::from_str(body).map_err(|e| anyhow!("body is not JSON: {}", e))?;
+    match path.select(&value) {
+        Some(serde_json::Value::String(s)) => Ok(s.clone()),
+        Some(other) => Ok(other.to_string()),
+        None => Err(anyhow!("path {} not found in body", path)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::memory::{FakeQueue, InMemoryBackend, Operation};
+    use crate::types::{MessageAttribute, ReceiveOptions};
+    use chrono::Utc;
+    use std::collections::BTreeMap;
+
+    fn record(id: &str, body: &str) -> ArchivedMessage {
+        ArchivedMessage {
+            message_id: id.to_string(),
+            queue_url: "https://example.com/000000000000/source".to_string(),
+            exported_at: Utc::now(),
+            body: body.to_string(),
+            md5_of_body: None,
+            attributes: BTreeMap::new(),
+            message_attributes: BTreeMap::new(),
+        }
+    }
+
+    async fn replay(
+        backend: &InMemoryBackend,
+        queue: &str,
+        records: Vec<ArchivedMessage>,
+        options: ReplayOptions,
+    ) -> (Replayer, Vec<ReplayOutcome>) {
+        let url = backend.queue_url(queue).await.unwrap();
+        let mut replayer = Replayer::new(&url, records, options);
+        let mut outcomes = Vec::new();
+        while !replayer.is_done() {
+            outcomes.extend(replayer.step(backend).await.unwrap());
+        }
+        (replayer, outcomes)
+    }
+
+    async fn bodies(backend: &InMemoryBackend, queue: &str) -> Vec<String> {
+        let url = backend.queue_url(queue).await.unwrap();
+        let options = ReceiveOptions {
+            max_messages: 10,
+            visibility_timeout: None,
+            wait_time_seconds: 0,
+        };
+        let messages = backend.receive_messages(&url, options).await.unwrap();
+        messages.into_iter().map(|m| m.body).collect()
+    }
+
+    #[tokio::test]
+    async fn replays_bodies_and_attributes() {
+        let backend = InMemoryBackend::new().with_queue(FakeQueue::new("orders", 0));
+        let mut first = record("a", "one");
+        first.message_attributes.insert(
+            "Type".to_string(),
+            MessageAttribute {
+                data_type: "String".to_string(),
+                string_value: Some("created".to_string()),
+                binary_value: None,
+            },
+        );
+        let (replayer, outcomes) = replay(
+            &backend,
+            "orders",
+            vec![first, record("b", "two")],
+            ReplayOptions::default(),
+        )
+        .await;
+
+        assert_eq!((replayer.sent, replayer.failed), (2, 0));
+        assert!(
+            outcomes
+                .iter()
+                .all(|o| matches!(o.status, ReplayStatus::Sent { .. }))
+        );
+        assert_eq!(outcomes[0].source_message_id, "a");
+        assert_eq!(bodies(&backend, "orders").await, ["one", "two"]);
+    }
+
+    #[tokio::test]
+    async fn dry_run_sends_nothing() {
+        let backend = InMemoryBackend::new().with_queue(FakeQueue::new("orders", 0));
+        let options = ReplayOptions {
+            dry_run: true,
+            ..ReplayOptions::default()
+        };
+        let (replayer, outcomes) =
+            replay(&backend, "orders", vec![record("a", "one")], options).await;
+
+        assert_eq!((replayer.sent, replayer.would_send), (0, 1));
+        assert!(matches!(&outcomes[0].status, ReplayStatus::DryRun { body } if body == "one"));
+        assert_eq!(backend.calls(Operation::SendMessages), 0);
+    }
+
+    #[tokio::test]
+    async fn transform_extracts_path_and_reports_misses() {
+        let backend = InMemoryBackend::new().with_queue(FakeQueue::new("orders", 0));
+        let options = ReplayOptions {
+            transform: Some(".Message".parse().unwrap()),
+            ..ReplayOptions::default()
+        };
+        let records = vec![
+            record("a", r#"{"Type":"Notification","Message":"{\"id\":1}"}"#),
+            record("b", r#"{"detail":{}}"#),
+            record("c", "not json"),
+        ];
+        let (replayer, outcomes) = replay(&backend, "orders", records, options).await;
+
+        assert_eq!((replayer.sent, replayer.failed), (1, 2));
+        assert_eq!(bodies(&backend, "orders").await, [r#"{"id":1}"#]);
+        let failed: Vec<&str> = outcomes
+            .iter()
+            .filter(|o| matches!(o.status, ReplayStatus::Failed { .. }))
+            .map(|o| o.source_message_id.as_str())
+            .collect();
+        assert_eq!(failed, ["b", "c"]);
+    }
+
+    #[tokio::test]
+    async fn fifo_targets_get_group_and_deduplication_ids() {
+        let backend = InMemoryBackend::new().with_queue(FakeQueue::new("orders.fifo", 0));
+        let mut grouped = record("a", "one");
+        grouped
+            .attributes
+            .insert("MessageGroupId".to_string(), "customer-1".to_string());
+        let (replayer, _) = replay(
+            &backend,
+            "orders.fifo",
+            vec![grouped, record("b", "two")],
+            ReplayOptions::default(),
+        )
+        .await;
+        assert_eq!(replayer.failed, 0);
+
+        let url = backend.queue_url("orders.fifo").await.unwrap();
+        let options = ReceiveOptions {
+            max_messages: 10,
+            visibility_timeout: None,
+            wait_time_seconds: 0,
+        };
+        let messages = backend.receive_messages(&url, options).await.unwrap();
+        assert_eq!(messages[0].attributes["MessageGroupId"], "customer-1");
+        assert_eq!(messages[0].attributes["MessageDeduplicationId"], "a");
+        assert_eq!(messages[1].attributes["MessageGroupId"], "sqs-monitor");
+    }
+
+    #[tokio::test]
+    async fn send_errors_fail_the_whole_batch() {
+        let backend = InMemoryBackend::new().with_queue(FakeQueue::new("orders", 0));
+        backend.fail_next(Operation::SendMessages, "throttled");
+        let (replayer, outcomes) = replay(
+            &backend,
+            "orders",
+            vec![record("a", "one"), record("b", "two")],
+            ReplayOptions::default(),
+        )
+        .await;
+
+        assert_eq!(replayer.failed, 2);
+        assert!(
+            matches!(&outcomes[0].status, ReplayStatus::Failed { reason } if reason == "throttled")
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn rate_limit_spaces_out_batches() {
+        let backend = InMemoryBackend::new().with_queue(FakeQueue::new("orders", 0));
+        let records = (0..6).map(|i| record(&i.to_string(), "x")).collect();
+        let options = ReplayOptions {
+            rate_per_second: Some(2.0),
+            ..ReplayOptions::default()
+        };
+        let started = Instant::now();
+        let (replayer, _) = replay(&backend, "orders", records, options).await;
+
+        assert_eq!(replayer.sent, 6);
+        assert_eq!(backend.calls(Operation::SendMessages), 3);
+        assert!(started.elapsed() >= Duration::from_secs(2));
+    }
+}
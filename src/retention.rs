@@ -0,0 +1,149 @@
+use crate::backend::{BackendResult, QueueBackend};
+use crate::types::{MetricQuery, QueueMessage, QueueMetric};
+use chrono::{DateTime, Utc};
+use std::fmt;
+
+/// How far back to look for the latest age datapoint. SQS publishes it every
+/// minute, but CloudWatch can lag a few minutes behind.
+const AGE_LOOKBACK_MINUTES: i64 = 15;
+/// Share of the retention period after which the oldest message is reported
+/// as about to expire.
+const EXPIRY_WARNING_SHARE: f64 = 0.8;
+
+/// Where an oldest-message age came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AgeSource {
+    /// CloudWatch `ApproximateAgeOfOldestMessage`.
+    CloudWatch,
+    /// The oldest `SentTimestamp` among peeked messages. A lower bound: a
+    /// peek sees at most a sample of the queue.
+    Sampled,
+}
+
+impl fmt::Display for AgeSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            AgeSource::CloudWatch => "CloudWatch",
+            AgeSource::Sampled => "peeked messages",
+        })
+    }
+}
+
+/// An estimate of how long the oldest message has been in a queue, as of
+/// `measured_at`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OldestMessageAge {
+    pub secs: i64,
+    pub source: AgeSource,
+    pub measured_at: DateTime<Utc>,
+}
+
+impl OldestMessageAge {
+    /// The age now, assuming the message is still there.
+    pub fn secs_at(&self, now: DateTime<Utc>) -> i64 {
+        self.secs + (now - self.measured_at).num_seconds().max(0)
+    }
+
+    /// Seconds until SQS deletes the oldest message; negative once it is
+    /// overdue.
+    pub fn expires_in(&self, retention_secs: i64, now: DateTime<Utc>) -> i64 {
+        retention_secs - self.secs_at(now)
+    }
+
+    /// Whether the oldest message has used up most of the retention period.
+    pub fn near_expiry(&self, retention_secs: i64, now: DateTime<Utc>) -> bool {
+        self.secs_at(now) as f64 >= retention_secs as f64 * EXPIRY_WARNING_SHARE
+    }
+}
+
+/// The latest `ApproximateAgeOfOldestMessage` datapoint, or `None` when
+/// CloudWatch has nothing recent for the queue.
+pub async fn cloudwatch_age(
+    backend: &dyn QueueBackend,
+    queue_url: &str,
+    now: DateTime<Utc>,
+) -> BackendResult<Option<OldestMessageAge>> {
+    let query = MetricQuery {
+        metric: QueueMetric::ApproximateAgeOfOldestMessage,
+        start: now - chrono::Duration::minutes(AGE_LOOKBACK_MINUTES),
+        end: now,
+        period_secs: 60,
+    };
+    let points = backend.queue_metric(queue_url, &query).await?;
+    Ok(points.last().map(|point| OldestMessageAge {
+        secs: point.value as i64,
+        source: AgeSource::CloudWatch,
+        // The period's maximum, so the age as of the end of the period
+        measured_at: point.timestamp + chrono::Duration::seconds(query.period_secs.into()),
+    }))
+}
+
+/// The oldest `SentTimestamp` among `messages`.
+pub fn oldest_sent<'a>(
+    messages: impl IntoIterator<Item = &'a QueueMessage>,
+) -> Option<DateTime<Utc>> {
+    messages.into_iter().filter_map(QueueMessage::sent_at).min()
+}
+
+/// An age estimate from the oldest message seen in a peek.
+pub fn sampled_age(oldest_sent: DateTime<Utc>, now: DateTime<Utc>) -> OldestMessageAge {
+    OldestMessageAge {
+        secs: (now - oldest_sent).num_seconds().max(0),
+        source: AgeSource::Sampled,
+        measured_at: now,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::memory::{FakeMessage, FakeQueue, InMemoryBackend, Operation};
+
+    #[tokio::test]
+    async fn reads_the_age_from_cloudwatch() {
+        let now = Utc::now();
+        let mut dlq = FakeQueue::new("orders-dlq", 0);
+        let url = dlq.url();
+        let mut old = FakeMessage::new("m-1", "{}");
+        old.sent_timestamp = (now - chrono::Duration::days(3)).timestamp_millis();
+        dlq = dlq.with_message(old);
+        let backend = InMemoryBackend::new().with_queue(dlq);
+
+        let age = cloudwatch_age(&backend, &url, now).await.unwrap().unwrap();
+        assert_eq!(age.source, AgeSource::CloudWatch);
+        assert_eq!(age.secs_at(now), 3 * 86_400);
+        // Four days' retention: the message has used 75% of it
+        assert!(!age.near_expiry(4 * 86_400, now));
+        assert!(age.near_expiry(4 * 86_400, now + chrono::Duration::hours(6)));
+        assert_eq!(age.expires_in(4 * 86_400, now), 86_400);
+
+        backend.fail_next(Operation::QueueMetric, "AccessDenied");
+        assert!(cloudwatch_age(&backend, &url, now).await.is_err());
+    }
+
+    #[test]
+    fn samples_the_oldest_sent_timestamp() {
+        let now = Utc::now();
+        let messages: Vec<QueueMessage> = [30, 90, 10]
+            .into_iter()
+            .map(|minutes| QueueMessage {
+                message_id: String::new(),
+                receipt_handle: String::new(),
+                body: String::new(),
+                md5_of_body: None,
+                attributes: [(
+                    "SentTimestamp".to_string(),
+                    (now - chrono::Duration::minutes(minutes))
+                        .timestamp_millis()
+                        .to_string(),
+                )]
+                .into(),
+                message_attributes: Default::default(),
+            })
+            .collect();
+
+        let oldest = oldest_sent(&messages).unwrap();
+        assert_eq!(sampled_age(oldest, now).secs, 90 * 60);
+        assert_eq!(oldest_sent(&[]), None);
+    }
+}
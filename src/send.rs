@@ -0,0 +1,462 @@
+use crate::backend::{MAX_BATCH, QueueBackend};
+use crate::types::{BatchFailure, MessageAttribute, OutgoingMessage};
+use anyhow::{Context, Result, bail};
+use chrono::{SecondsFormat, Utc};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// CSV columns that set message fields; every other column becomes a
+/// String message attribute.
+const CSV_FIELDS: [&str; 4] = ["body", "group_id", "deduplication_id", "delay_seconds"];
+
+/// Where the bodies of sent messages come from.
+#[derive(Debug, Clone)]
+pub enum MessageSource {
+    /// Messages read from a file, sent in order and repeated from the top
+    /// until the count is reached.
+    File(Vec<OutgoingMessage>),
+    /// A body in which `{n}` becomes the message's 1-based number and
+    /// `{now}` the time it is sent.
+    Template(String),
+}
+
+/// A JSONL line that spells out more than the body. Message attributes are
+/// plain strings or full attributes as `export` writes them.
+#[derive(Deserialize)]
+struct JsonlMessage {
+    body: serde_json::Value,
+    #[serde(default)]
+    message_attributes: BTreeMap<String, JsonlAttribute>,
+    group_id: Option<String>,
+    deduplication_id: Option<String>,
+    delay_seconds: Option<i32>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum JsonlAttribute {
+    Text(String),
+    Full(MessageAttribute),
+}
+
+/// Whether a send source typed into the UI was meant as a file path rather
+/// than a body template: a single word with a directory separator or a
+/// `.jsonl`/`.csv` extension that does not start like a JSON or XML body.
+pub fn looks_like_path(value: &str) -> bool {
+    let lower = value.to_ascii_lowercase();
+    !value.contains(char::is_whitespace)
+        && !value.starts_with(['{', '[', '<', '"'])
+        && (value.contains('/') || lower.ends_with(".jsonl") || lower.ends_with(".csv"))
+}
+
+impl MessageSource {
+    /// Reads a `.csv` file with a `body` column, or a JSONL file with one
+    /// message per line. A JSONL line is either an object with a `body`
+    /// field (and optionally `message_attributes`, `group_id`,
+    /// `deduplication_id` and `delay_seconds`) or the body itself.
+    pub fn read(path: &Path) -> Result<Self> {
+        let is_csv = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("csv"));
+        let messages = if is_csv {
+            read_csv(path)?
+        } else {
+            read_jsonl(path)?
+        };
+        if messages.is_empty() {
+            bail!("{} holds no messages", path.display());
+        }
+        Ok(MessageSource::File(messages))
+    }
+
+    /// Messages in the file; a template has no natural length.
+    pub fn len(&self) -> Option<usize> {
+        match self {
+            MessageSource::File(messages) => Some(messages.len()),
+            MessageSource::Template(_) => None,
+        }
+    }
+
+    /// The message sent in position `index` (0-based).
+    fn message(&self, index: usize) -> OutgoingMessage {
+        match self {
+            MessageSource::File(messages) => messages[index % messages.len()].clone(),
+            MessageSource::Template(template) => OutgoingMessage {
+                body: template.replace("{n}", &(index + 1).to_string()).replace(
+                    "{now}",
+                    &Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+                ),
+                ..OutgoingMessage::default()
+            },
+        }
+    }
+}
+
+fn read_jsonl(path: &Path) -> Result<Vec<OutgoingMessage>> {
+    let content =
+        std::fs::read_to_string(path).with_context(|| format!("Cannot read {}", path.display()))?;
+    let mut messages = Vec::new();
+    for (index, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let described = serde_json::from_str::<serde_json::Value>(line)
+            .ok()
+            .filter(|value| value.get("body").is_some())
+            .map(serde_json::from_value::<JsonlMessage>)
+            .transpose()
+            .with_context(|| format!("{}:{}: invalid message", path.display(), index + 1))?;
+        messages.push(match described {
+            Some(message) => OutgoingMessage {
+                body: match message.body {
+                    serde_json::Value::String(body) => body,
+                    other => other.to_string(),
+                },
+                message_attributes: message
+                    .message_attributes
+                    .into_iter()
+                    .map(|(name, attribute)| (name, attribute.into()))
+                    .collect(),
+                message_group_id: message.group_id,
+                message_deduplication_id: message.deduplication_id,
+                delay_seconds: message.delay_seconds,
+                ..OutgoingMessage::default()
+            },
+            None => OutgoingMessage {
+                body: line.to_string(),
+                ..OutgoingMessage::default()
+            },
+        });
+    }
+    Ok(messages)
+}
+
+fn read_csv(path: &Path) -> Result<Vec<OutgoingMessage>> {
+    let mut reader =
+        csv::Reader::from_path(path).with_context(|| format!("Cannot read {}", path.display()))?;
+    let headers = reader.headers()?.clone();
+    let Some(body_column) = headers.iter().position(|h| h == "body") else {
+        bail!("{} has no 'body' column", path.display());
+    };
+    let column = |name: &str| headers.iter().position(|h| h == name);
+    let (group_column, deduplication_column, delay_column) = (
+        column("group_id"),
+        column("deduplication_id"),
+        column("delay_seconds"),
+    );
+
+    let mut messages = Vec::new();
+    for (index, record) in reader.records().enumerate() {
+        let record =
+            record.with_context(|| format!("{}: invalid row {}", path.display(), index + 2))?;
+        let field = |column: Option<usize>| {
+            column
+                .and_then(|c| record.get(c))
+                .filter(|value| !value.is_empty())
+                .map(str::to_string)
+        };
+        let delay_seconds = field(delay_column)
+            .map(|delay| delay.parse::<i32>())
+            .transpose()
+            .with_context(|| {
+                format!(
+                    "{}: invalid delay_seconds on row {}",
+                    path.display(),
+                    index + 2
+                )
+            })?;
+        let message_attributes = headers
+            .iter()
+            .zip(record.iter())
+            .filter(|(name, value)| !CSV_FIELDS.contains(name) && !value.is_empty())
+            .map(|(name, value)| {
+                (
+                    name.to_string(),
+                    JsonlAttribute::Text(value.to_string()).into(),
+                )
+            })
+            .collect();
+        messages.push(OutgoingMessage {
+            body: record.get(body_column).unwrap_or_default().to_string(),
+            message_attributes,
+            message_group_id: field(group_column),
+            message_deduplication_id: field(deduplication_column),
+            delay_seconds,
+            ..OutgoingMessage::default()
+        });
+    }
+    Ok(messages)
+}
+
+impl From<JsonlAttribute> for MessageAttribute {
+    fn from(attribute: JsonlAttribute) -> Self {
+        match attribute {
+            JsonlAttribute::Text(value) => MessageAttribute {
+                data_type: "String".to_string(),
+                string_value: Some(value),
+                binary_value: None,
+            },
+            JsonlAttribute::Full(attribute) => attribute,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SendOptions {
+    /// Messages to send; defaults to the file's length, or one for a
+    /// template.
+    pub count: Option<usize>,
+    /// Upper bound on messages sent per second.
+    pub rate_per_second: Option<f64>,
+}
+
+/// Sends messages from a source with SendMessageBatch, one batch per step.
+pub struct Sender {
+    queue_url: String,
+    source: MessageSource,
+    options: SendOptions,
+    /// Makes deduplication ids unique across runs for FIFO queues.
+    run_id: i64,
+    next: usize,
+    next_batch_at: Option<Instant>,
+    started: Instant,
+    pub sent: usize,
+    pub failed: usize,
+}
+
+impl Sender {
+    pub fn new(queue_url: &str, source: MessageSource, options: SendOptions) -> Self {
+        Self {
+            queue_url: queue_url.to_string(),
+            source,
+            options,
+            run_id: Utc::now().timestamp_millis(),
+            next: 0,
+            next_batch_at: None,
+            started: Instant::now(),
+            sent: 0,
+            failed: 0,
+        }
+    }
+
+    pub fn total(&self) -> usize {
+        self.options.count.or(self.source.len()).unwrap_or(1)
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.next >= self.total()
+    }
+
+    /// Messages per second since the first batch.
+    pub fn throughput(&self) -> f64 {
+        let elapsed = self.started.elapsed().as_secs_f64();
+        if elapsed > 0.0 {
+            self.sent as f64 / elapsed
+        } else {
+            0.0
+        }
+    }
+
+    fn batch_size(&self) -> usize {
+        match self.options.rate_per_second {
+            Some(rate) => (rate.ceil() as usize).clamp(1, MAX_BATCH),
+            None => MAX_BATCH,
+        }
+    }
+
+    /// Sends the next batch, waiting first if the rate limit requires it,
+    /// and returns the messages that failed. A failed request fails every
+    /// message in the batch.
+    pub async fn step(&mut self, backend: &dyn QueueBackend) -> Vec<BatchFailure> {
+        let end = (self.next + self.batch_size()).min(self.total());
+        if self.next >= end {
+            return Vec::new();
+        }
+        if self.next == 0 {
+            self.started = Instant::now();
+        }
+        if let Some(at) = self.next_batch_at {
+            tokio::time::sleep_until(at).await;
+        }
+        if let Some(rate) = self.options.rate_per_second.filter(|r| *r > 0.0) {
+            self.next_batch_at =
+                Some(Instant::now() + Duration::from_secs_f64((end - self.next) as f64 / rate));
+        }
+
+        let outgoing: Vec<OutgoingMessage> = (self.next..end)
+            .map(|index| {
+                let mut message = self.source.message(index);
+                message.id = index.to_string();
+                message.prepare_for_queue(&self.queue_url, &format!("{}-{}", self.run_id, index));
+                message
+            })
+            .collect();
+        let failures = match backend.send_messages(&self.queue_url, &outgoing).await {
+            Ok(result) => result.failed,
+            Err(e) => outgoing
+                .iter()
+                .map(|message| BatchFailure {
+                    id: message.id.clone(),
+                    code: "RequestFailed".to_string(),
+                    message: e.to_string(),
+                })
+                .collect(),
+        };
+
+        self.failed += failures.len();
+        self.sent += outgoing.len() - failures.len();
+        self.next = end;
+        failures
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::memory::{FakeQueue, InMemoryBackend, Operation};
+    use crate::types::ReceiveOptions;
+    use std::io::Write;
+
+    fn file(extension: &str, content: &str) -> tempfile::NamedTempFile {
+        let mut file = tempfile::Builder::new()
+            .suffix(extension)
+            .tempfile()
+            .unwrap();
+        file.write_all(content.as_bytes()).unwrap();
+        file
+    }
+
+    async fn send(
+        backend: &InMemoryBackend,
+        source: MessageSource,
+        options: SendOptions,
+    ) -> Sender {
+        let url = backend.queue_url("orders").await.unwrap();
+        let mut sender = Sender::new(&url, source, options);
+        while !sender.is_done() {
+            sender.step(backend).await;
+        }
+        sender
+    }
+
+    async fn received(backend: &InMemoryBackend) -> Vec<crate::types::QueueMessage> {
+        let url = backend.queue_url("orders").await.unwrap();
+        let options = ReceiveOptions {
+            max_messages: 10,
+            visibility_timeout: None,
+            wait_time_seconds: 0,
+        };
+        backend.receive_messages(&url, options).await.unwrap()
+    }
+
+    #[test]
+    fn tells_paths_from_templates() {
+        assert!(looks_like_path("ordrs.jsonl"));
+        assert!(looks_like_path("EXPORT.CSV"));
+        assert!(looks_like_path("./fixtures/orders"));
+        assert!(!looks_like_path(r#"{"url":"https://example.com/{n}"}"#));
+        assert!(!looks_like_path("order {n} from a/b"));
+        assert!(!looks_like_path("hello"));
+    }
+
+    #[test]
+    fn reads_jsonl_and_csv() {
+        let jsonl = file(
+            ".jsonl",
+            "{\"orderId\":1}\n\n{\"body\":{\"orderId\":2},\"message_attributes\":{\"Type\":\"created\"},\"delay_seconds\":5}\nplain text\n",
+        );
+        let MessageSource::File(messages) = MessageSource::read(jsonl.path()).unwrap() else {
+            panic!("expected file messages");
+        };
+        let bodies: Vec<&str> = messages.iter().map(|m| m.body.as_str()).collect();
+        assert_eq!(
+            bodies,
+            [r#"{"orderId":1}"#, r#"{"orderId":2}"#, "plain text"]
+        );
+        assert_eq!(
+            messages[1].message_attributes["Type"]
+                .string_value
+                .as_deref(),
+            Some("created")
+        );
+        assert_eq!(messages[1].delay_seconds, Some(5));
+
+        let csv = file(
+            ".csv",
+            "body,group_id,Type\n\"{\"\"orderId\"\":1}\",g-1,created\nhello,,\n",
+        );
+        let MessageSource::File(messages) = MessageSource::read(csv.path()).unwrap() else {
+            panic!("expected file messages");
+        };
+        assert_eq!(messages[0].body, r#"{"orderId":1}"#);
+        assert_eq!(messages[0].message_group_id.as_deref(), Some("g-1"));
+        assert_eq!(
+            messages[0].message_attributes["Type"]
+                .string_value
+                .as_deref(),
+            Some("created")
+        );
+        assert!(messages[1].message_attributes.is_empty());
+
+        let no_body = file(".csv", "payload\nx\n");
+        assert!(MessageSource::read(no_body.path()).is_err());
+    }
+
+    #[tokio::test]
+    async fn templates_count_up_and_files_repeat() {
+        let backend = InMemoryBackend::new().with_queue(FakeQueue::new("orders", 0));
+        let source = MessageSource::Template(r#"{"seq":{n}}"#.to_string());
+        let options = SendOptions {
+            count: Some(3),
+            ..SendOptions::default()
+        };
+        let sender = send(&backend, source, options).await;
+        assert_eq!((sender.sent, sender.failed), (3, 0));
+
+        let source = MessageSource::File(vec![OutgoingMessage {
+            body: "again".to_string(),
+            ..OutgoingMessage::default()
+        }]);
+        let options = SendOptions {
+            count: Some(2),
+            ..SendOptions::default()
+        };
+        send(&backend, source, options).await;
+
+        let bodies: Vec<String> = received(&backend)
+            .await
+            .into_iter()
+            .map(|m| m.body)
+            .collect();
+        assert_eq!(
+            bodies,
+            [
+                r#"{"seq":1}"#,
+                r#"{"seq":2}"#,
+                r#"{"seq":3}"#,
+                "again",
+                "again"
+            ]
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn rate_limits_and_counts_failed_batches() {
+        let backend = InMemoryBackend::new().with_queue(FakeQueue::new("orders", 0));
+        backend.fail_next(Operation::SendMessages, "ThrottlingException");
+        let options = SendOptions {
+            count: Some(8),
+            rate_per_second: Some(4.0),
+        };
+        let started = Instant::now();
+        let sender = send(&backend, MessageSource::Template("x".to_string()), options).await;
+
+        assert_eq!((sender.sent, sender.failed), (4, 4));
+        assert_eq!(backend.calls(Operation::SendMessages), 2);
+        assert!(started.elapsed() >= Duration::from_secs(1));
+    }
+}
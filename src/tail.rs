@@ -0,0 +1,551 @@
+use crate::backend::{BackendResult, MAX_BATCH, QueueBackend};
+use crate::receives::{max_receive_count, receive_count};
+use crate::types::{
+    BatchFailure, DeleteEntry, QueueDetails, QueueMessage, ReceiveOptions, VisibilityEntry,
+};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+
+/// The longest long poll SQS allows.
+pub const TAIL_WAIT_SECS: i32 = 20;
+/// Messages the tail pane keeps, newest first.
+pub const TAIL_HISTORY: usize = 200;
+/// How far back the arrival rate looks.
+const RATE_WINDOW: Duration = Duration::from_secs(60);
+/// Wait before polling again after a poll that only brought back messages
+/// already shown, so the tail does not spin.
+const REPEAT_BACKOFF: Duration = Duration::from_secs(5);
+/// How long a message that comes round again is kept hidden when the tail
+/// holds repeats; held messages are released when the tail stops, and this
+/// bounds how long they stay hidden if it never gets the chance.
+const REPEAT_HOLD_SECS: i32 = 600;
+/// Wait after a failed poll, on top of the retries the backend already made.
+const ERROR_BACKOFF: Duration = Duration::from_secs(5);
+/// Message IDs remembered for telling new arrivals from released messages
+/// coming round again.
+const SEEN_LIMIT: usize = 10_000;
+
+/// What the tail does with a message once it has been shown.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TailMode {
+    /// Make it visible again straight away for the queue's consumers.
+    /// Every receive counts towards the redrive `maxReceiveCount`, and a
+    /// backlog nobody consumes comes round again every `REPEAT_BACKOFF`
+    /// unless the tail holds repeats.
+    #[default]
+    Release,
+    /// Consume it: delete it from the queue.
+    Delete,
+}
+
+impl fmt::Display for TailMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            TailMode::Release => "releasing",
+            TailMode::Delete => "deleting",
+        })
+    }
+}
+
+/// What one long poll brought in.
+#[derive(Debug, Default)]
+pub struct TailBatch {
+    /// Messages this tail had not seen before, in the order received.
+    pub arrived: Vec<QueueMessage>,
+    /// Released messages received again.
+    pub repeats: usize,
+    /// Messages that could not be released or deleted, by message ID.
+    pub failed: Vec<BatchFailure>,
+}
+
+/// Long-polls one queue, releasing or deleting what it receives.
+pub struct Tail {
+    queue_url: String,
+    mode: TailMode,
+    seen: HashSet<String>,
+    seen_order: VecDeque<String>,
+    hold_repeats: bool,
+    /// Receipt handles of repeats kept hidden, by message ID.
+    held: HashMap<String, String>,
+}
+
+impl Tail {
+    pub fn new(queue_url: &str, mode: TailMode) -> Self {
+        Self {
+            queue_url: queue_url.to_string(),
+            mode,
+            seen: HashSet::new(),
+            seen_order: VecDeque::new(),
+            hold_repeats: false,
+            held: HashMap::new(),
+        }
+    }
+
+    /// In release mode, keeps messages that come round again hidden until
+    /// the tail stops, so each is received at most twice (plus once per
+    /// `REPEAT_HOLD_SECS`). They are out of their consumers' reach
+    /// meanwhile.
+    pub fn holding_repeats(mut self) -> Self {
+        self.hold_repeats = self.mode == TailMode::Release;
+        self
+    }
+
+    pub fn mode(&self) -> TailMode {
+        self.mode
+    }
+
+    /// Waits up to `TAIL_WAIT_SECS` for at most `max_messages` (capped at
+    /// ten), then releases or deletes everything received. Messages seen
+    /// before are held hidden instead if the tail holds repeats.
+    pub async fn poll(
+        &mut self,
+        backend: &dyn QueueBackend,
+        max_messages: usize,
+    ) -> BackendResult<TailBatch> {
+        let messages = backend
+            .receive_messages(
+                &self.queue_url,
+                ReceiveOptions {
+                    max_messages: max_messages.clamp(1, MAX_BATCH) as i32,
+                    visibility_timeout: None,
+                    wait_time_seconds: TAIL_WAIT_SECS,
+                },
+            )
+            .await?;
+        let mut batch = TailBatch::default();
+        if messages.is_empty() {
+            return Ok(batch);
+        }
+
+        let failed = match self.mode {
+            TailMode::Release => {
+                let entries: Vec<VisibilityEntry> = messages
+                    .iter()
+                    .enumerate()
+                    .map(|(i, message)| VisibilityEntry {
+                        id: i.to_string(),
+                        receipt_handle: message.receipt_handle.clone(),
+                        visibility_timeout: if self.hold_repeats
+                            && self.seen.contains(&message.message_id)
+                        {
+                            REPEAT_HOLD_SECS
+                        } else {
+                            0
+                        },
+                    })
+                    .collect();
+                backend
+                    .change_message_visibility(&self.queue_url, &entries)
+                    .await?
+            }
+            TailMode::Delete => {
+                let entries: Vec<DeleteEntry> = messages
+                    .iter()
+                    .enumerate()
+                    .map(|(i, message)| DeleteEntry {
+                        id: i.to_string(),
+                        receipt_handle: message.receipt_handle.clone(),
+                    })
+                    .collect();
+                backend.delete_messages(&self.queue_url, &entries).await?
+            }
+        };
+        // Entry ids are positions in the batch; report message IDs instead
+        batch.failed = failed
+            .into_iter()
+            .map(|failure| BatchFailure {
+                id: failure
+                    .id
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|i| messages.get(i))
+                    .map_or(failure.id.clone(), |m| m.message_id.clone()),
+                ..failure
+            })
+            .collect();
+
+        for message in messages {
+            if self.remember(&message.message_id) {
+                batch.arrived.push(message);
+            } else {
+                batch.repeats += 1;
+                if self.hold_repeats {
+                    self.held
+                        .insert(message.message_id.clone(), message.receipt_handle);
+                }
+            }
+        }
+        Ok(batch)
+    }
+
+    /// Makes the messages held hidden visible again, returning how many
+    /// were released. Holds that already ran out fail harmlessly.
+    pub async fn release_held(&mut self, backend: &dyn QueueBackend) -> BackendResult<usize> {
+        let handles: Vec<String> = self.held.drain().map(|(_, handle)| handle).collect();
+        let mut released = 0;
+        for chunk in handles.chunks(MAX_BATCH) {
+            let entries: Vec<VisibilityEntry> = chunk
+                .iter()
+                .enumerate()
+                .map(|(i, handle)| VisibilityEntry {
+                    id: i.to_string(),
+                    receipt_handle: handle.clone(),
+                    visibility_timeout: 0,
+                })
+                .collect();
+            let failed = backend
+                .change_message_visibility(&self.queue_url, &entries)
+                .await?;
+            released += entries.len() - failed.len();
+        }
+        Ok(released)
+    }
+
+    /// How long to wait before polling again after `batch`.
+    pub fn backoff(&self, batch: &TailBatch) -> Option<Duration> {
+        (batch.arrived.is_empty() && batch.repeats > 0).then_some(REPEAT_BACKOFF)
+    }
+
+    /// Records a message ID, returning false if it was seen before.
+    fn remember(&mut self, message_id: &str) -> bool {
+        if !self.seen.insert(message_id.to_string()) {
+            return false;
+        }
+        self.seen_order.push_back(message_id.to_string());
+        if self.seen_order.len() > SEEN_LIMIT
+            && let Some(oldest) = self.seen_order.pop_front()
+        {
+            self.seen.remove(&oldest);
+            self.held.remove(&oldest);
+        }
+        true
+    }
+}
+
+/// Arrivals over the last minute.
+pub struct ArrivalRate {
+    started: Instant,
+    arrivals: VecDeque<(Instant, usize)>,
+}
+
+impl ArrivalRate {
+    pub fn new(started: Instant) -> Self {
+        Self {
+            started,
+            arrivals: VecDeque::new(),
+        }
+    }
+
+    pub fn record(&mut self, now: Instant, count: usize) {
+        if count > 0 {
+            self.arrivals.push_back((now, count));
+        }
+        while self
+            .arrivals
+            .front()
+            .is_some_and(|&(at, _)| now.duration_since(at) > RATE_WINDOW)
+        {
+            self.arrivals.pop_front();
+        }
+    }
+
+    /// Messages per second, averaged over the last minute or over the time
+    /// since the tail started if that is shorter.
+    pub fn per_second(&self, now: Instant) -> f64 {
+        let count: usize = self
+            .arrivals
+            .iter()
+            .filter(|&&(at, _)| now.duration_since(at) <= RATE_WINDOW)
+            .map(|&(_, count)| count)
+            .sum();
+        let window = now
+            .duration_since(self.started)
+            .clamp(Duration::from_secs(1), RATE_WINDOW);
+        count as f64 / window.as_secs_f64()
+    }
+}
+
+/// A tail running in a background task while the UI shows what arrives.
+pub struct TailView {
+    pub queue_name: String,
+    pub mode: TailMode,
+    /// Newest first, at most `TAIL_HISTORY`.
+    pub messages: VecDeque<QueueMessage>,
+    /// New messages received since the tail started.
+    pub received: usize,
+    /// Messages that could not be released or deleted.
+    pub failed: usize,
+    /// What went wrong with the latest poll, if anything.
+    pub last_error: Option<String>,
+    pub rate: ArrivalRate,
+    updates: mpsc::UnboundedReceiver<BackendResult<TailBatch>>,
+    /// Dropped with the view, which tells the task to release held
+    /// messages and finish.
+    stop: oneshot::Sender<()>,
+    task: JoinHandle<()>,
+}
+
+impl TailView {
+    /// Starts polling in the background; dropping the view stops it once
+    /// held messages are released, provided the runtime keeps running.
+    pub fn start(backend: Arc<dyn QueueBackend>, queue_name: &str, mut tail: Tail) -> Self {
+        let (sender, updates) = mpsc::unbounded_channel();
+        let (stop, mut stopped) = oneshot::channel::<()>();
+        let mode = tail.mode();
+        let task = tokio::spawn(async move {
+            loop {
+                let result = tokio::select! {
+                    result = tail.poll(backend.as_ref(), MAX_BATCH) => result,
+                    _ = &mut stopped => break,
+                };
+                let pause = match &result {
+                    Ok(batch) => tail.backoff(batch),
+                    Err(_) => Some(ERROR_BACKOFF),
+                };
+                if sender.send(result).is_err() {
+                    break;
+                }
+                if let Some(pause) = pause {
+                    tokio::select! {
+                        _ = tokio::time::sleep(pause) => {}
+                        _ = &mut stopped => break,
+                    }
+                }
+            }
+            let _ = tail.release_held(backend.as_ref()).await;
+        });
+
+        Self {
+            queue_name: queue_name.to_string(),
+            mode,
+            messages: VecDeque::new(),
+            received: 0,
+            failed: 0,
+            last_error: None,
+            rate: ArrivalRate::new(Instant::now()),
+            updates,
+            stop,
+            task,
+        }
+    }
+
+    /// Stops polling and waits until held messages are released, for when
+    /// the program is about to exit.
+    pub async fn stop(self) {
+        drop(self.stop);
+        let _ = self.task.await;
+    }
+
+    /// Takes in whatever the background poll has delivered since the last
+    /// call, without waiting.
+    pub fn drain(&mut self) {
+        let now = Instant::now();
+        while let Ok(result) = self.updates.try_recv() {
+            match result {
+                Ok(batch) => {
+                    self.rate.record(now, batch.arrived.len());
+                    self.received += batch.arrived.len();
+                    for message in batch.arrived {
+                        self.messages.push_front(message);
+                    }
+                    self.messages.truncate(TAIL_HISTORY);
+                    self.failed += batch.failed.len();
+                    self.last_error = batch
+                        .failed
+                        .last()
+                        .map(|failure| format!("{}: {}", failure.code, failure.message));
+                }
+                Err(e) => self.last_error = Some(e.to_string()),
+            }
+        }
+        self.rate.record(now, 0);
+    }
+}
+
+/// Why releasing tail on `details`' queue still moves messages towards its
+/// dead-letter queue, or `None` when it has none.
+pub fn redrive_warning(details: &QueueDetails, hold_repeats: bool) -> Option<String> {
+    let max = max_receive_count(details)?;
+    let receives = if hold_repeats {
+        "up to twice".to_string()
+    } else {
+        format!(
+            "again every {}s while nothing new arrives",
+            REPEAT_BACKOFF.as_secs()
+        )
+    };
+    Some(format!(
+        "this queue moves messages to its DLQ after {} receives, and tailing receives each one {}",
+        max, receives
+    ))
+}
+
+/// One line per message for the `tail` command: sent time, ID, receive
+/// count and the start of the body.
+pub fn summary(message: &QueueMessage, preview_chars: usize) -> String {
+    let sent = message
+        .sent_at()
+        .map(|dt| dt.format("%Y-%m-%dT%H:%M:%SZ").to_string())
+        .unwrap_or_else(|| "?".to_string());
+    let receives = receive_count(message).map_or_else(|| "?".to_string(), |n| n.to_string());
+    format!(
+        "{}  {}  {:>3}x  {}",
+        sent,
+        message.message_id,
+        receives,
+        body_preview(&message.body, preview_chars)
+    )
+}
+
+/// The start of a body on one line, with control characters blanked.
+pub fn body_preview(body: &str, chars: usize) -> String {
+    body.chars()
+        .map(|c| if c.is_control() { ' ' } else { c })
+        .take(chars)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::memory::{FakeQueue, InMemoryBackend};
+    use crate::types::{OutgoingMessage, ReceiveOptions};
+
+    #[tokio::test(start_paused = true)]
+    async fn release_leaves_messages_and_reports_each_once() {
+        let fake = InMemoryBackend::new().with_queue(FakeQueue::new("orders", 3));
+        let url = fake.queue_url("orders").await.unwrap();
+        let mut tail = Tail::new(&url, TailMode::default());
+
+        let batch = tail.poll(&fake, 10).await.unwrap();
+        assert_eq!((batch.arrived.len(), batch.repeats), (3, 0));
+        assert_eq!(tail.backoff(&batch), None);
+
+        // Released messages come straight back and are not shown again
+        for _ in 0..2 {
+            let batch = tail.poll(&fake, 10).await.unwrap();
+            assert_eq!((batch.arrived.len(), batch.repeats), (0, 3));
+            assert_eq!(tail.backoff(&batch), Some(REPEAT_BACKOFF));
+        }
+        assert_eq!(tail.release_held(&fake).await.unwrap(), 0);
+        let info = &fake.list_queues().await.unwrap()[0];
+        assert_eq!(info.approximate_messages, 3);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn holding_repeats_bounds_receives() {
+        let fake = InMemoryBackend::new().with_queue(FakeQueue::new("orders", 3));
+        let url = fake.queue_url("orders").await.unwrap();
+        let mut tail = Tail::new(&url, TailMode::default()).holding_repeats();
+        assert_eq!(tail.poll(&fake, 10).await.unwrap().arrived.len(), 3);
+
+        // Held once they come round, so they are not received over and over
+        let batch = tail.poll(&fake, 10).await.unwrap();
+        assert_eq!((batch.arrived.len(), batch.repeats), (0, 3));
+        assert_eq!(tail.backoff(&batch), Some(REPEAT_BACKOFF));
+        let batch = tail.poll(&fake, 10).await.unwrap();
+        assert_eq!((batch.arrived.len(), batch.repeats), (0, 0));
+
+        assert_eq!(tail.release_held(&fake).await.unwrap(), 3);
+        let info = &fake.list_queues().await.unwrap()[0];
+        assert_eq!(
+            (
+                info.approximate_messages,
+                info.approximate_messages_not_visible
+            ),
+            (3, 0)
+        );
+        let receives: Vec<String> = fake
+            .receive_messages(
+                &url,
+                ReceiveOptions {
+                    max_messages: 10,
+                    visibility_timeout: None,
+                    wait_time_seconds: 0,
+                },
+            )
+            .await
+            .unwrap()
+            .iter()
+            .map(|m| m.attributes["ApproximateReceiveCount"].clone())
+            .collect();
+        assert_eq!(receives, ["3", "3", "3"]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn stopping_a_view_waits_for_held_messages_to_be_released() {
+        let fake = InMemoryBackend::new().with_queue(FakeQueue::new("orders", 3));
+        let url = fake.queue_url("orders").await.unwrap();
+        let tail = Tail::new(&url, TailMode::Release).holding_repeats();
+        let mut view = TailView::start(Arc::new(fake.clone()), "orders", tail);
+
+        tokio::time::sleep(Duration::from_secs(30)).await;
+        view.drain();
+        assert_eq!(view.received, 3);
+        let info = &fake.list_queues().await.unwrap()[0];
+        assert_eq!(info.approximate_messages_not_visible, 3);
+
+        view.stop().await;
+        let info = &fake.list_queues().await.unwrap()[0];
+        assert_eq!(info.approximate_messages, 3);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn delete_mode_consumes_and_long_polls_for_new_messages() {
+        let fake = InMemoryBackend::new().with_queue(FakeQueue::new("orders", 2));
+        let url = fake.queue_url("orders").await.unwrap();
+        let mut tail = Tail::new(&url, TailMode::Delete);
+        assert_eq!(tail.poll(&fake, 10).await.unwrap().arrived.len(), 2);
+
+        let started = Instant::now();
+        let batch = tail.poll(&fake, 10).await.unwrap();
+        assert!(batch.arrived.is_empty());
+        assert_eq!(
+            started.elapsed(),
+            Duration::from_secs(TAIL_WAIT_SECS as u64)
+        );
+
+        let producer = fake.clone();
+        let sender_url = url.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_secs(3)).await;
+            producer
+                .send_messages(
+                    &sender_url,
+                    &[OutgoingMessage {
+                        id: "0".to_string(),
+                        body: "late".to_string(),
+                        ..OutgoingMessage::default()
+                    }],
+                )
+                .await
+                .unwrap();
+        });
+        let batch = tail.poll(&fake, 10).await.unwrap();
+        assert_eq!(batch.arrived[0].body, "late");
+        let details = fake.get_queue_details(&url).await.unwrap();
+        assert_eq!(crate::backend::approximate_total(&details), Some(0));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn rate_covers_the_last_minute() {
+        let started = Instant::now();
+        let mut rate = ArrivalRate::new(started);
+        rate.record(started, 10);
+        tokio::time::advance(Duration::from_secs(10)).await;
+        assert_eq!(rate.per_second(Instant::now()), 1.0);
+
+        tokio::time::advance(Duration::from_secs(50)).await;
+        rate.record(Instant::now(), 20);
+        assert_eq!(rate.per_second(Instant::now()), 0.5);
+
+        tokio::time::advance(Duration::from_secs(30)).await;
+        rate.record(Instant::now(), 0);
+        assert_eq!(rate.per_second(Instant::now()), 20.0 / 60.0);
+    }
+}
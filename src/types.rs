@@ -1,4 +1,6 @@
 use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 #[derive(Debug, Clone)]
 pub struct QueueInfo {
@@ -11,7 +13,28 @@ pub struct QueueInfo {
     pub last_updated: DateTime<Utc>,
 }
 
-#[derive(Debug, Clone)]
+impl QueueInfo {
+    /// FIFO queue names are required by SQS to end in `.fifo`.
+    pub fn is_fifo(&self) -> bool {
+        self.name.ends_with(".fifo")
+    }
+
+    pub fn is_dlq(&self) -> bool {
+        let base = self.name.trim_end_matches(".fifo");
+        base.ends_with("-dlq") || base.ends_with("_dlq")
+    }
+}
+
+/// The principal and AWS settings a backend acts with.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CallerIdentity {
+    pub account: Option<String>,
+    pub arn: Option<String>,
+    pub profile: Option<String>,
+    pub region: Option<String>,
+}
+
+#[derive(Debug, Clone, Default)]
 pub struct QueueDetails {
     pub arn: Option<String>,
     pub created_timestamp: Option<i64>,
@@ -21,18 +44,177 @@ pub struct QueueDetails {
     pub visibility_timeout: Option<i32>,
     pub maximum_message_size: Option<i32>,
     pub delay_seconds: Option<i32>,
+    pub fifo_queue: bool,
+    pub content_based_deduplication: bool,
+    pub deduplication_scope: Option<String>,
+    pub fifo_throughput_limit: Option<String>,
+    /// Every attribute returned by GetQueueAttributes, keyed by attribute name.
+    pub attributes: BTreeMap<String, String>,
+}
+
+/// A user-defined message attribute. Binary values are kept base64-encoded so
+/// messages round-trip through JSON archives unchanged.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MessageAttribute {
+    pub data_type: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub string_value: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub binary_value: Option<String>,
+}
+
+/// A message as returned by ReceiveMessage.
+#[derive(Debug, Clone)]
+pub struct QueueMessage {
+    pub message_id: String,
+    pub receipt_handle: String,
+    pub body: String,
+    pub md5_of_body: Option<String>,
+    /// System attributes such as SentTimestamp, ApproximateReceiveCount,
+    /// MessageGroupId and AWSTraceHeader.
+    pub attributes: BTreeMap<String, String>,
+    pub message_attributes: BTreeMap<String, MessageAttribute>,
+}
+
+impl QueueMessage {
+    /// When the message was sent, from its SentTimestamp attribute.
+    pub fn sent_at(&self) -> Option<DateTime<Utc>> {
+        self.attributes
+            .get("SentTimestamp")?
+            .parse::<i64>()
+            .ok()
+            .and_then(DateTime::from_timestamp_millis)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ReceiveOptions {
+    /// 1 to 10, the most SQS returns per call.
+    pub max_messages: i32,
+    /// Overrides the queue's visibility timeout for the received messages.
+    pub visibility_timeout: Option<i32>,
+    /// Long-poll duration; 0 means short polling.
+    pub wait_time_seconds: i32,
+}
+
+#[derive(Debug, Clone)]
+pub struct DeleteEntry {
+    /// Batch-unique entry id, echoed back in failures.
+    pub id: String,
+    pub receipt_handle: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct VisibilityEntry {
+    /// Batch-unique entry id, echoed back in failures.
+    pub id: String,
+    pub receipt_handle: String,
+    /// Seconds from now until the message becomes visible again.
+    pub visibility_timeout: i32,
 }
 
-impl Default for QueueDetails {
-    fn default() -> Self {
-        Self {
-            arn: None,
-            created_timestamp: None,
-            last_modified_timestamp: None,
-            message_retention_period: None,
-            visibility_timeout: None,
-            maximum_message_size: None,
-            delay_seconds: None,
+/// A single failed entry from a batch API call.
+#[derive(Debug, Clone)]
+pub struct BatchFailure {
+    pub id: String,
+    pub code: String,
+    pub message: String,
+}
+
+/// One entry of a SendMessageBatch call.
+#[derive(Debug, Clone, Default)]
+pub struct OutgoingMessage {
+    /// Batch-unique entry id, echoed back in results.
+    pub id: String,
+    pub body: String,
+    pub message_attributes: BTreeMap<String, MessageAttribute>,
+    pub message_group_id: Option<String>,
+    pub message_deduplication_id: Option<String>,
+    pub delay_seconds: Option<i32>,
+}
+
+impl OutgoingMessage {
+    /// Makes the message acceptable to the target queue: FIFO queues need a
+    /// group id and, unless content-based deduplication is on, a
+    /// deduplication id; standard queues reject both.
+    pub fn prepare_for_queue(&mut self, queue_url: &str, fallback_deduplication_id: &str) {
+        if queue_url.ends_with(".fifo") {
+            self.message_group_id
+                .get_or_insert_with(|| DEFAULT_MESSAGE_GROUP_ID.to_string());
+            self.message_deduplication_id
+                .get_or_insert_with(|| fallback_deduplication_id.to_string());
+            // Per-message delays are not supported on FIFO queues.
+            self.delay_seconds = None;
+        } else {
+            self.message_group_id = None;
+            self.message_deduplication_id = None;
         }
     }
 }
+
+/// Group used for FIFO sends when the source message did not carry one.
+pub const DEFAULT_MESSAGE_GROUP_ID: &str = "sqs-monitor";
+
+#[derive(Debug, Clone)]
+pub struct SentMessage {
+    pub id: String,
+    pub message_id: String,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SendBatchResult {
+    pub sent: Vec<SentMessage>,
+    pub failed: Vec<BatchFailure>,
+}
+
+/// A CloudWatch metric SQS publishes per queue in the `AWS/SQS` namespace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueueMetric {
+    NumberOfMessagesSent,
+    NumberOfMessagesReceived,
+    NumberOfMessagesDeleted,
+    NumberOfEmptyReceives,
+    ApproximateAgeOfOldestMessage,
+}
+
+impl QueueMetric {
+    pub const ALL: [QueueMetric; 5] = [
+        QueueMetric::NumberOfMessagesSent,
+        QueueMetric::NumberOfMessagesReceived,
+        QueueMetric::NumberOfMessagesDeleted,
+        QueueMetric::NumberOfEmptyReceives,
+        QueueMetric::ApproximateAgeOfOldestMessage,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            QueueMetric::NumberOfMessagesSent => "NumberOfMessagesSent",
+            QueueMetric::NumberOfMessagesReceived => "NumberOfMessagesReceived",
+            QueueMetric::NumberOfMessagesDeleted => "NumberOfMessagesDeleted",
+            QueueMetric::NumberOfEmptyReceives => "NumberOfEmptyReceives",
+            QueueMetric::ApproximateAgeOfOldestMessage => "ApproximateAgeOfOldestMessage",
+        }
+    }
+
+    /// Counts are summed over a period; the age is a gauge, so its
+    /// maximum is what matters.
+    pub fn is_count(self) -> bool {
+        self != QueueMetric::ApproximateAgeOfOldestMessage
+    }
+}
+
+/// One metric over a time window, aggregated into `period_secs` buckets.
+#[derive(Debug, Clone, Copy)]
+pub struct MetricQuery {
+    pub metric: QueueMetric,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub period_secs: i32,
+}
+
+/// The aggregate of a metric over the period starting at `timestamp`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MetricPoint {
+    pub timestamp: DateTime<Utc>,
+    pub value: f64,
+}
@@ -1,14 +1,35 @@
 use crate::app::App;
-use chrono::{DateTime, Local};
+use crate::audit::{AuditOutcome, RecentEntries};
+use crate::backend::{BackendError, queue_name_from_url};
+use crate::body::DecodedBody;
+use crate::format::{Formatter, duration, relative};
+use crate::lint::{Finding, Severity, worst};
+use crate::metrics::MetricWindow;
+use crate::peek::PeekView;
+use crate::receives::{AGE_BUCKETS, ReceiveAnalysis, near_redrive, receive_count};
+use crate::retention::AgeSource;
+use crate::tail::{TailView, body_preview};
+use crate::types::QueueMetric;
+use chrono::{DateTime, Utc};
 use ratatui::{
     Frame,
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Sparkline, Wrap},
 };
 
+#[cfg(test)]
+mod snapshot_tests;
+
+mod highlight;
+
 pub fn draw(frame: &mut Frame, app: &App) {
+    draw_at(frame, app, Utc::now());
+}
+
+/// Draws the UI with relative times computed against `now`.
+pub fn draw_at(frame: &mut Frame, app: &App, now: DateTime<Utc>) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
@@ -18,44 +39,85 @@ pub fn draw(frame: &mut Frame, app: &App) {
         ])
         .split(frame.area());
 
-    draw_header(frame, chunks[0]);
-    draw_main_content(frame, app, chunks[1]);
+    draw_header(frame, app, chunks[0]);
+    draw_main_content(frame, app, chunks[1], now);
     draw_status_bar(frame, app, chunks[2]);
 }
 
-fn draw_header(frame: &mut Frame, area: Rect) {
-    let header = Paragraph::new("SQS Queue Monitor")
-        .style(
-            Style::default()
-                .fg(Color::Cyan)
-                .add_modifier(Modifier::BOLD),
-        )
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .title(" SQS Monitor ")
-                .title_style(Style::default().fg(Color::Yellow)),
-        );
+fn draw_header(frame: &mut Frame, app: &App, area: Rect) {
+    let mut title = vec![Span::styled(
+        "SQS Queue Monitor",
+        Style::default()
+            .fg(Color::Cyan)
+            .add_modifier(Modifier::BOLD),
+    )];
+    if app.read_only {
+        title.push(Span::styled(
+            "  [READ-ONLY]",
+            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+        ));
+    }
+    let header = Paragraph::new(Line::from(title)).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(" SQS Monitor ")
+            .title_style(Style::default().fg(Color::Yellow)),
+    );
     frame.render_widget(header, area);
 }
 
-fn draw_main_content(frame: &mut Frame, app: &App, area: Rect) {
+fn draw_main_content(frame: &mut Frame, app: &App, area: Rect, now: DateTime<Utc>) {
+    if let Some(error) = &app.connection_error {
+        draw_connection_status(frame, app, error, area);
+        return;
+    }
+    if let Some(recent) = &app.audit_entries {
+        draw_audit_log(frame, app, recent, area);
+        return;
+    }
+    if let Some(peek) = &app.peek {
+        match &app.body_view {
+            Some(body) => draw_body_view(frame, app, peek, body, area),
+            None => draw_peek(frame, peek, area, now),
+        }
+        return;
+    }
+    if let Some(tail) = &app.tail {
+        draw_tail(frame, tail, area, now);
+        return;
+    }
+
     let chunks = Layout::default()
         .direction(Direction::Horizontal)
         .constraints([Constraint::Percentage(40), Constraint::Percentage(60)])
         .split(area);
 
     draw_queue_list(frame, app, chunks[0]);
-    draw_queue_details(frame, app, chunks[1]);
+    match app.metrics_window {
+        Some(window) => {
+            let details = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([
+                    Constraint::Min(6),
+                    Constraint::Length(QueueMetric::ALL.len() as u16 + 2),
+                ])
+                .split(chunks[1]);
+            draw_queue_details(frame, app, details[0], now);
+            draw_metrics(frame, app, window, details[1]);
+        }
+        None => draw_queue_details(frame, app, chunks[1], now),
+    }
 }
 
 fn draw_queue_list(frame: &mut Frame, app: &App, area: Rect) {
+    let fmt = app.formatter();
+
     let items: Vec<ListItem> = app
         .queues
         .iter()
         .enumerate()
         .map(|(idx, queue)| {
-            let is_dlq = queue.name.ends_with("-dlq") || queue.name.ends_with("_dlq");
+            let is_dlq = queue.is_dlq();
             let msg_count = queue.approximate_messages;
 
             let msg_color = match msg_count {
@@ -84,8 +146,16 @@ fn draw_queue_list(frame: &mut Frame, app: &App, area: Rect) {
                     },
                     style,
                 ),
-                Span::styled(format!("{:<30}", queue.name), style),
-                Span::styled(format!("{:>6}", msg_count), Style::default().fg(msg_color)),
+                Span::styled(format!("{:<26}", queue.name), style),
+                lint_badge(app.findings_for(&queue.name)),
+                Span::styled(
+                    if queue.is_fifo() { " FIFO " } else { "      " },
+                    Style::default().fg(Color::Blue),
+                ),
+                Span::styled(
+                    format!("{:>8}", fmt.count(msg_count)),
+                    Style::default().fg(msg_color),
+                ),
             ])];
 
             ListItem::new(content).style(style)
@@ -112,7 +182,34 @@ fn draw_queue_list(frame: &mut Frame, app: &App, area: Rect) {
     frame.render_stateful_widget(list, area, &mut list_state);
 }
 
-fn draw_queue_details(frame: &mut Frame, app: &App, area: Rect) {
+/// The number of configuration findings, colored by the worst of them.
+fn lint_badge(findings: &[Finding]) -> Span<'static> {
+    match worst(findings) {
+        Some(severity) => Span::styled(
+            format!(" ⚠{:<2}", findings.len()),
+            Style::default()
+                .fg(severity_color(severity))
+                .add_modifier(Modifier::BOLD),
+        ),
+        None => Span::raw("    "),
+    }
+}
+
+fn severity_color(severity: Severity) -> Color {
+    match severity {
+        Severity::Warning => Color::Yellow,
+        Severity::Error => Color::Red,
+    }
+}
+
+fn draw_queue_details(frame: &mut Frame, app: &App, area: Rect, now: DateTime<Utc>) {
+    if app.show_raw_attributes {
+        draw_raw_attributes(frame, app, area, now);
+        return;
+    }
+
+    let fmt = app.formatter();
+
     let content = if let Some(queue) = app.selected_queue() {
         let mut lines = vec![
             Line::from(vec![
@@ -125,19 +222,30 @@ fn draw_queue_details(frame: &mut Frame, app: &App, area: Rect) {
             Line::from(""),
             Line::from(vec![
                 Span::styled("Messages:              ", Style::default().fg(Color::Cyan)),
-                Span::raw(queue.approximate_messages.to_string()),
+                Span::raw(fmt.count(queue.approximate_messages)),
             ]),
             Line::from(vec![
                 Span::styled("Messages In Flight:    ", Style::default().fg(Color::Cyan)),
-                Span::raw(queue.approximate_messages_not_visible.to_string()),
+                Span::raw(fmt.count(queue.approximate_messages_not_visible)),
             ]),
             Line::from(vec![
                 Span::styled("Messages Delayed:      ", Style::default().fg(Color::Cyan)),
-                Span::raw(queue.approximate_messages_delayed.to_string()),
+                Span::raw(fmt.count(queue.approximate_messages_delayed)),
             ]),
             Line::from(""),
         ];
 
+        if let Some(left) = app.purge_cooldown(&queue.url) {
+            lines.push(Line::from(vec![
+                Span::styled("Next Purge Allowed In: ", Style::default().fg(Color::Cyan)),
+                Span::styled(
+                    fmt.seconds(left.as_secs_f64().ceil() as i64),
+                    Style::default().fg(Color::Yellow),
+                ),
+            ]));
+            lines.push(Line::from(""));
+        }
+
         if let Some(details) = &app.selected_details {
             if let Some(arn) = &details.arn {
                 lines.push(Line::from(vec![Span::styled(
@@ -151,42 +259,152 @@ fn draw_queue_details(frame: &mut Frame, app: &App, area: Rect) {
             if let Some(retention) = details.message_retention_period {
                 lines.push(Line::from(vec![
                     Span::styled("Retention Period:      ", Style::default().fg(Color::Cyan)),
-                    Span::raw(format!("{} seconds", retention)),
+                    Span::raw(fmt.seconds(retention.into())),
                 ]));
             }
 
+            let queued = queue.approximate_messages + queue.approximate_messages_not_visible;
+            match app.oldest_message_age(now) {
+                Some(age) => {
+                    let source = match (age.source, &app.cloudwatch_error) {
+                        (AgeSource::Sampled, Some(_)) => {
+                            format!("{}, CloudWatch unavailable", age.source)
+                        }
+                        _ => age.source.to_string(),
+                    };
+                    lines.push(Line::from(vec![
+                        Span::styled("Oldest Message Age:    ", Style::default().fg(Color::Cyan)),
+                        Span::raw(format!("{} ({})", fmt.seconds(age.secs_at(now)), source)),
+                    ]));
+                    if let Some(retention) = details.message_retention_period.map(i64::from)
+                        && age.near_expiry(retention, now)
+                    {
+                        let left = age.expires_in(retention, now);
+                        let warning = if left > 0 {
+                            format!(
+                                "Oldest messages are deleted by retention in {}",
+                                fmt.seconds(left)
+                            )
+                        } else {
+                            "Messages are reaching the retention period and being deleted"
+                                .to_string()
+                        };
+                        lines.push(Line::from(Span::styled(
+                            format!("⚠ {}", warning),
+                            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+                        )));
+                    }
+                }
+                None if queued > 0 => lines.push(Line::from(vec![
+                    Span::styled("Oldest Message Age:    ", Style::default().fg(Color::Cyan)),
+                    Span::styled(
+                        "unknown ([V] peek to sample)",
+                        Style::default().fg(Color::DarkGray),
+                    ),
+                ])),
+                None => {}
+            }
+
             if let Some(timeout) = details.visibility_timeout {
                 lines.push(Line::from(vec![
                     Span::styled("Visibility Timeout:    ", Style::default().fg(Color::Cyan)),
-                    Span::raw(format!("{} seconds", timeout)),
+                    Span::raw(fmt.seconds(timeout.into())),
                 ]));
             }
 
             if let Some(max_size) = details.maximum_message_size {
                 lines.push(Line::from(vec![
                     Span::styled("Max Message Size:      ", Style::default().fg(Color::Cyan)),
-                    Span::raw(format!("{} bytes", max_size)),
+                    Span::raw(fmt.bytes(max_size.into())),
                 ]));
             }
 
             if let Some(delay) = details.delay_seconds {
                 lines.push(Line::from(vec![
-                    Span::styled("Delay Seconds:         ", Style::default().fg(Color::Cyan)),
-                    Span::raw(delay.to_string()),
+                    Span::styled("Delivery Delay:        ", Style::default().fg(Color::Cyan)),
+                    Span::raw(fmt.seconds(delay.into())),
+                ]));
+            }
+
+            lines.push(Line::from(vec![
+                Span::styled("Queue Type:            ", Style::default().fg(Color::Cyan)),
+                Span::raw(if details.fifo_queue {
+                    "FIFO"
+                } else {
+                    "Standard"
+                }),
+            ]));
+
+            if details.fifo_queue {
+                lines.push(Line::from(vec![
+                    Span::styled("Content Deduplication: ", Style::default().fg(Color::Cyan)),
+                    Span::raw(if details.content_based_deduplication {
+                        "Enabled"
+                    } else {
+                        "Disabled"
+                    }),
                 ]));
+
+                if let Some(scope) = &details.deduplication_scope {
+                    lines.push(Line::from(vec![
+                        Span::styled("Deduplication Scope:   ", Style::default().fg(Color::Cyan)),
+                        Span::raw(scope),
+                    ]));
+                }
+
+                if let Some(limit) = &details.fifo_throughput_limit {
+                    lines.push(Line::from(vec![
+                        Span::styled("Throughput Limit:      ", Style::default().fg(Color::Cyan)),
+                        Span::raw(limit),
+                    ]));
+                }
             }
 
             if let Some(created) = details.created_timestamp {
-                let dt = DateTime::from_timestamp(created, 0)
-                    .map(|dt| dt.with_timezone(&Local))
-                    .map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string())
-                    .unwrap_or_else(|| "N/A".to_string());
                 lines.push(Line::from(""));
                 lines.push(Line::from(vec![
                     Span::styled("Created:               ", Style::default().fg(Color::Cyan)),
-                    Span::raw(dt),
+                    Span::raw(fmt.epoch_seconds(created, now)),
                 ]));
             }
+
+            if let Some(modified) = details.last_modified_timestamp {
+                lines.push(Line::from(vec![
+                    Span::styled("Last Modified:         ", Style::default().fg(Color::Cyan)),
+                    Span::raw(fmt.epoch_seconds(modified, now)),
+                ]));
+            }
+        }
+
+        let findings = app.findings_for(&queue.name);
+        if !findings.is_empty() {
+            lines.push(Line::from(""));
+            lines.push(Line::from(Span::styled(
+                "Configuration Findings:",
+                Style::default().add_modifier(Modifier::BOLD),
+            )));
+            for finding in findings {
+                lines.push(Line::from(Span::styled(
+                    format!("⚠ {}", finding.message),
+                    Style::default().fg(severity_color(finding.rule.severity())),
+                )));
+                lines.push(Line::from(Span::styled(
+                    format!("  {}", finding.rule.advice()),
+                    Style::default().fg(Color::DarkGray),
+                )));
+            }
+        } else if let Some(e) = app.lint_errors.get(&queue.name) {
+            lines.push(Line::from(""));
+            lines.push(Line::from(Span::styled(
+                format!("Configuration not checked: {}", e),
+                Style::default().fg(Color::DarkGray),
+            )));
+        } else if app.is_linting() && !app.lint_findings.contains_key(&queue.name) {
+            lines.push(Line::from(""));
+            lines.push(Line::from(Span::styled(
+                "Checking configuration...",
+                Style::default().fg(Color::DarkGray),
+            )));
         }
 
         lines
@@ -194,40 +412,560 @@ fn draw_queue_details(frame: &mut Frame, app: &App, area: Rect) {
         vec![Line::from("No queue selected")]
     };
 
-    let details = Paragraph::new(content).block(
+    let details = Paragraph::new(content)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(" Queue Details ([A] all attributes) ")
+                .title_style(Style::default().fg(Color::Yellow)),
+        )
+        .scroll((app.details_scroll, 0));
+
+    frame.render_widget(details, area);
+}
+
+/// One sparkline per CloudWatch metric, each with its total or latest value.
+fn draw_metrics(frame: &mut Frame, app: &App, window: MetricWindow, area: Rect) {
+    let fmt = app.formatter();
+    let next = window
+        .next()
+        .map_or("close".to_string(), |next| next.to_string());
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!(
+            " CloudWatch, last {} ([Shift+M] {}) ",
+            window, next
+        ))
+        .title_style(Style::default().fg(Color::Yellow));
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let Some((metrics, _)) = &app.metrics else {
+        let text = match &app.metrics_error {
+            Some(e) => Span::styled(e.to_string(), Style::default().fg(Color::Red)),
+            None => Span::styled("Loading...", Style::default().fg(Color::DarkGray)),
+        };
+        frame.render_widget(Paragraph::new(text).wrap(Wrap { trim: false }), inner);
+        return;
+    };
+
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(metrics.series.iter().map(|_| Constraint::Length(1)))
+        .split(inner);
+    for (series, row) in metrics.series.iter().zip(rows.iter()) {
+        let (name, color) = match series.metric {
+            QueueMetric::NumberOfMessagesSent => ("Sent", Color::Cyan),
+            QueueMetric::NumberOfMessagesReceived => ("Received", Color::Blue),
+            QueueMetric::NumberOfMessagesDeleted => ("Deleted", Color::Green),
+            QueueMetric::NumberOfEmptyReceives => ("Empty receives", Color::DarkGray),
+            QueueMetric::ApproximateAgeOfOldestMessage => ("Oldest age", Color::Magenta),
+        };
+        let value = if series.metric.is_count() {
+            format!(
+                "{:>8} {:>7}/m",
+                fmt.count(series.total() as i64),
+                format!("{:.1}", metrics.per_minute(series))
+            )
+        } else {
+            series
+                .latest()
+                .map_or(format!("{:>18}", "no data"), |secs| {
+                    format!("{:>14} now", fmt.seconds(secs as i64))
+                })
+        };
+        let cells = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Length(METRIC_LABEL_WIDTH), Constraint::Min(0)])
+            .split(*row);
+        frame.render_widget(
+            Paragraph::new(Line::from(vec![
+                Span::styled(format!("{:<15}", name), Style::default().fg(color)),
+                Span::raw(value),
+            ])),
+            cells[0],
+        );
+        // The most recent periods that fit
+        let shown = series.values.len().saturating_sub(cells[1].width as usize);
+        let data: Vec<u64> = series.values[shown..]
+            .iter()
+            .map(|v| v.round() as u64)
+            .collect();
+        frame.render_widget(
+            Sparkline::default()
+                .data(&data)
+                .style(Style::default().fg(color)),
+            cells[1],
+        );
+    }
+}
+
+fn draw_raw_attributes(frame: &mut Frame, app: &App, area: Rect, now: DateTime<Utc>) {
+    let fmt = app.formatter();
+
+    let content = match &app.selected_details {
+        Some(details) if !details.attributes.is_empty() => {
+            let mut lines = Vec::new();
+            for (name, value) in &details.attributes {
+                lines.push(Line::from(Span::styled(
+                    format!("{}:", name),
+                    Style::default()
+                        .fg(Color::Cyan)
+                        .add_modifier(Modifier::BOLD),
+                )));
+                for value_line in format_attribute_value(&fmt, now, name, value) {
+                    lines.push(Line::from(format!("  {}", value_line)));
+                }
+            }
+            lines
+        }
+        Some(_) => vec![Line::from("No attributes returned")],
+        None => vec![Line::from("No queue selected")],
+    };
+
+    let attributes = Paragraph::new(content)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(" All Attributes ([A] summary, PgUp/PgDn scroll) ")
+                .title_style(Style::default().fg(Color::Yellow)),
+        )
+        .wrap(Wrap { trim: false })
+        .scroll((app.details_scroll, 0));
+
+    frame.render_widget(attributes, area);
+}
+
+fn draw_audit_log(frame: &mut Frame, app: &App, recent: &RecentEntries, area: Rect) {
+    let fmt = app.formatter();
+    let lines: Vec<Line> = if recent.entries.is_empty() {
+        vec![Line::from("No audited actions yet")]
+    } else {
+        recent
+            .entries
+            .iter()
+            .map(|entry| {
+                let before = entry
+                    .messages_before
+                    .map(|n| fmt.count(n))
+                    .unwrap_or_else(|| "?".to_string());
+                let (outcome, color) = match &entry.outcome {
+                    AuditOutcome::Ok => ("ok".to_string(), Color::Green),
+                    AuditOutcome::Partial { failed } => {
+                        (format!("{} failed", failed), Color::Yellow)
+                    }
+                    AuditOutcome::Error { error } => (format!("error: {}", error), Color::Red),
+                };
+                Line::from(vec![
+                    Span::raw(format!(
+                        "{:<24} {:<12} {:<7} {:<32} {:>9} before  ",
+                        fmt.timestamp(entry.timestamp),
+                        entry.os_user.as_deref().unwrap_or("?"),
+                        entry.action,
+                        queue_name_from_url(&entry.queue_url),
+                        before,
+                    )),
+                    Span::styled(outcome, Style::default().fg(color)),
+                    Span::styled(
+                        entry
+                            .identity
+                            .arn
+                            .as_deref()
+                            .map(|arn| format!("  {}", arn))
+                            .unwrap_or_default(),
+                        Style::default().fg(Color::DarkGray),
+                    ),
+                ])
+            })
+            .collect()
+    };
+
+    let log = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(match recent.unreadable {
+                    0 => " Audit Log, newest first ([L] close, PgUp/PgDn scroll) ".to_string(),
+                    skipped => format!(
+                        " Audit Log, newest first, {} unreadable lines skipped ([L] close, PgUp/PgDn scroll) ",
+                        skipped
+                    ),
+                })
+                .title_style(Style::default().fg(Color::Yellow)),
+        )
+        .scroll((app.details_scroll, 0));
+
+    frame.render_widget(log, area);
+}
+
+const PEEK_BODY_PREVIEW: usize = 60;
+/// Width of the receive analysis beside a grouped message view.
+const RECEIVE_PANEL_WIDTH: u16 = 38;
+/// Longest histogram bar in that panel.
+const RECEIVE_BAR_WIDTH: usize = 20;
+/// Metric name and value to the left of each sparkline.
+const METRIC_LABEL_WIDTH: u16 = 34;
+
+fn draw_peek(frame: &mut Frame, peek: &PeekView, area: Rect, now: DateTime<Utc>) {
+    let area = if peek.grouped {
+        let chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Min(40), Constraint::Length(RECEIVE_PANEL_WIDTH)])
+            .split(area);
+        draw_receive_analysis(frame, peek, chunks[1], now);
+        chunks[0]
+    } else {
+        area
+    };
+    let instant_now = tokio::time::Instant::now();
+    let items: Vec<ListItem> = peek
+        .shown
+        .iter()
+        .enumerate()
+        .map(|(position, &index)| {
+            let peeked = &peek.messages[index];
+            let message = &peeked.message;
+            let sent = message
+                .sent_at()
+                .map(|dt| relative(dt, now))
+                .unwrap_or_else(|| "?".to_string());
+            let receives = receive_count(message);
+            // Flagged when the next few failures send it to the DLQ
+            let receives_style = match (receives, peek.max_receive_count) {
+                (Some(receives), Some(max)) if near_redrive(receives, max) => {
+                    Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)
+                }
+                _ => Style::default(),
+            };
+            let receives = receives.map_or("?".to_string(), |n| n.to_string());
+            let (handle, handle_color) = if peeked.handle_expired(instant_now) {
+                ("expired".to_string(), Color::Red)
+            } else {
+                let left = peeked.visible_at.duration_since(instant_now);
+                (
+                    format!("held {}", duration(left.as_secs_f64().ceil() as i64)),
+                    Color::Green,
+                )
+            };
+            let preview = body_preview(&message.body, PEEK_BODY_PREVIEW);
+
+            let style = if position == peek.cursor {
+                Style::default()
+                    .fg(Color::Black)
+                    .bg(Color::Cyan)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+            ListItem::new(Line::from(vec![
+                Span::styled(if peeked.selected { "[x] " } else { "[ ] " }, style),
+                Span::styled(format!("{:<36}  ", message.message_id), style),
+                Span::raw(format!("{:<16} ", sent)),
+                Span::styled(format!("{:>3}x  ", receives), receives_style),
+                Span::styled(
+                    format!("{:<10} ", handle),
+                    Style::default().fg(handle_color),
+                ),
+                Span::styled(preview, Style::default().fg(Color::DarkGray)),
+            ]))
+            .style(style)
+        })
+        .collect();
+
+    let title = match &peek.filter {
+        _ if peek.messages.is_empty() => format!(
+            " No visible messages in '{}' ([R] retry [V] close) ",
+            peek.queue_name
+        ),
+        // The analysis panel takes the room of the usual key hints
+        _ if peek.grouped => format!(
+            " {} of {} messages in '{}' by receive count ([Space] select [D]elete [M]ove [V] close) ",
+            peek.shown.len(),
+            peek.messages.len(),
+            peek.queue_name
+        ),
+        Some(filter) => format!(
+            " {} of {} messages in '{}' match {} ([/] filter [*] select all [Enter] view [Space] select [D]elete [T] visibility [M]ove [E]xport [R] re-fetch [V] close) ",
+            peek.shown.len(),
+            peek.messages.len(),
+            peek.queue_name,
+            filter
+        ),
+        None => format!(
+            " {} messages in '{}' ([/] filter [Enter] view [Space] select [D]elete [T] visibility [M]ove [R] re-fetch [V] close) ",
+            peek.messages.len(),
+            peek.queue_name
+        ),
+    };
+    let list = List::new(items).block(
         Block::default()
             .borders(Borders::ALL)
-            .title(" Queue Details ")
+            .title(title)
             .title_style(Style::default().fg(Color::Yellow)),
     );
 
-    frame.render_widget(details, area);
+    let mut list_state = ListState::default();
+    list_state.select((!peek.shown.is_empty()).then_some(peek.cursor));
+    frame.render_stateful_widget(list, area, &mut list_state);
+}
+
+/// Histograms of receive counts and first-receive ages for the shown
+/// messages, with a guess at what the spread means.
+fn draw_receive_analysis(frame: &mut Frame, peek: &PeekView, area: Rect, now: DateTime<Utc>) {
+    let analysis = ReceiveAnalysis::of(
+        peek.shown.iter().map(|&i| &peek.messages[i].message),
+        peek.max_receive_count,
+        now,
+    );
+    let label = |text: &str| {
+        Span::styled(
+            text.to_string(),
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        )
+    };
+    let largest = analysis
+        .counts
+        .values()
+        .chain(&analysis.ages)
+        .copied()
+        .max()
+        .unwrap_or(0);
+    let bar = |n: usize| {
+        let width = if largest == 0 {
+            0
+        } else {
+            (n * RECEIVE_BAR_WIDTH).div_ceil(largest)
+        };
+        format!("{:<w$} {}", "█".repeat(width), n, w = RECEIVE_BAR_WIDTH)
+    };
+
+    let mut lines = vec![Line::from(label("Receive count"))];
+    for (&receives, &n) in &analysis.counts {
+        let style = match analysis.max_receive_count {
+            Some(max) if near_redrive(receives, max) => Style::default().fg(Color::Red),
+            _ => Style::default(),
+        };
+        lines.push(Line::from(vec![
+            Span::raw(format!("{:>6}x ", receives)),
+            Span::styled(bar(n), style),
+        ]));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(label("First received")));
+    for ((_, name), &n) in AGE_BUCKETS.iter().zip(&analysis.ages) {
+        lines.push(Line::from(format!("{:>7} {}", name, bar(n))));
+    }
+    lines.push(Line::from(""));
+    if let Some(max) = analysis.max_receive_count {
+        lines.push(Line::from(vec![
+            label("Redrive:  "),
+            Span::raw(format!(
+                "{} near maxReceiveCount {}",
+                analysis.near_redrive, max
+            )),
+        ]));
+    }
+    lines.push(Line::from(analysis.diagnosis().to_string()));
+
+    let panel = Paragraph::new(lines).wrap(Wrap { trim: false }).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(" Receives ([O] ungroup) ")
+            .title_style(Style::default().fg(Color::Yellow)),
+    );
+    frame.render_widget(panel, area);
+}
+
+fn draw_tail(frame: &mut Frame, tail: &TailView, area: Rect, now: DateTime<Utc>) {
+    let items: Vec<ListItem> = tail
+        .messages
+        .iter()
+        .map(|message| {
+            let sent = message
+                .sent_at()
+                .map(|dt| relative(dt, now))
+                .unwrap_or_else(|| "?".to_string());
+            let receives =
+                receive_count(message).map_or_else(|| "?".to_string(), |n| n.to_string());
+            ListItem::new(Line::from(vec![
+                Span::raw(format!("{:<36}  ", message.message_id)),
+                Span::raw(format!("{:<16} {:>3}x  ", sent, receives)),
+                Span::styled(
+                    body_preview(&message.body, PEEK_BODY_PREVIEW),
+                    Style::default().fg(Color::DarkGray),
+                ),
+            ]))
+        })
+        .collect();
+
+    let rate = tail.rate.per_second(tokio::time::Instant::now());
+    let failed = if tail.failed > 0 {
+        format!(" · {} failed", tail.failed)
+    } else {
+        String::new()
+    };
+    let title = format!(
+        " Tailing '{}', {} · {} received{} · {:.1}/s ({:.0}/min) ([W] stop) ",
+        tail.queue_name,
+        tail.mode,
+        tail.received,
+        failed,
+        rate,
+        rate * 60.0
+    );
+    let mut block = Block::default()
+        .borders(Borders::ALL)
+        .title(title)
+        .title_style(Style::default().fg(Color::Yellow));
+    if let Some(error) = &tail.last_error {
+        block = block.title_bottom(Line::styled(
+            format!(" {} ", error),
+            Style::default().fg(Color::Red),
+        ));
+    }
+    frame.render_widget(List::new(items).block(block), area);
+}
+
+fn draw_body_view(frame: &mut Frame, app: &App, peek: &PeekView, body: &DecodedBody, area: Rect) {
+    let message_id = peek.current().map_or("", |m| m.message.message_id.as_str());
+    let mut layers = body.layers.clone();
+    layers.push(body.format.label());
+    let title = format!(
+        " {} · {} ([Enter] back [↑/↓] message [U] {} envelopes, PgUp/PgDn scroll) ",
+        message_id,
+        layers.join(" › "),
+        if app.unwrap_envelopes {
+            "keep"
+        } else {
+            "unwrap"
+        }
+    );
+
+    let viewer = Paragraph::new(highlight::body_lines(body))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .title_style(Style::default().fg(Color::Yellow)),
+        )
+        .wrap(Wrap { trim: false })
+        .scroll((app.details_scroll, 0));
+    frame.render_widget(viewer, area);
+}
+
+/// Shown instead of the queues while nothing can be listed, so a missing or
+/// expired login reads as a problem to fix rather than an empty account.
+fn draw_connection_status(frame: &mut Frame, app: &App, error: &BackendError, area: Rect) {
+    let label = |text: &'static str| {
+        Span::styled(
+            text,
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        )
+    };
+    let region = app
+        .connection
+        .region
+        .as_deref()
+        .or(app.region.as_deref())
+        .unwrap_or("not configured");
+
+    let lines = vec![
+        Line::from(Span::styled(
+            "Could not list queues",
+            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+        Line::from(vec![
+            label("Credentials: "),
+            Span::raw(app.connection.credential_source()),
+        ]),
+        Line::from(vec![label("Region:      "), Span::raw(region)]),
+        Line::from(vec![
+            label("Error:       "),
+            Span::styled(error.to_string(), Style::default().fg(Color::Red)),
+        ]),
+        Line::from(""),
+        Line::from("[R] Retry   [P] Switch profile   [G] Change region   [Q] Quit"),
+    ];
+
+    let status = Paragraph::new(lines).wrap(Wrap { trim: false }).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(" Connection ")
+            .title_style(Style::default().fg(Color::Yellow)),
+    );
+    frame.render_widget(status, area);
+}
+
+/// Render a raw attribute value for display, one entry per output line.
+fn format_attribute_value(
+    fmt: &Formatter,
+    now: DateTime<Utc>,
+    name: &str,
+    value: &str,
+) -> Vec<String> {
+    match name {
+        "Policy" | "RedrivePolicy" | "RedriveAllowPolicy" => {
+            match serde_json::from_str::<serde_json::Value>(value)
+                .and_then(|json| serde_json::to_string_pretty(&json))
+            {
+                Ok(pretty) => pretty.lines().map(str::to_string).collect(),
+                Err(_) => vec![value.to_string()],
+            }
+        }
+        "CreatedTimestamp" | "LastModifiedTimestamp" => match value.parse::<i64>() {
+            Ok(secs) => vec![fmt.epoch_seconds(secs, now)],
+            Err(_) => vec![value.to_string()],
+        },
+        "MessageRetentionPeriod"
+        | "VisibilityTimeout"
+        | "DelaySeconds"
+        | "ReceiveMessageWaitTimeSeconds"
+        | "KmsDataKeyReusePeriodSeconds" => match value.parse::<i64>() {
+            Ok(secs) => vec![fmt.seconds(secs)],
+            Err(_) => vec![value.to_string()],
+        },
+        "MaximumMessageSize" => match value.parse::<i64>() {
+            Ok(size) => vec![fmt.bytes(size)],
+            Err(_) => vec![value.to_string()],
+        },
+        "ApproximateNumberOfMessages"
+        | "ApproximateNumberOfMessagesNotVisible"
+        | "ApproximateNumberOfMessagesDelayed" => match value.parse::<i64>() {
+            Ok(count) => vec![fmt.count(count)],
+            Err(_) => vec![value.to_string()],
+        },
+        _ => vec![value.to_string()],
+    }
 }
 
 fn draw_status_bar(frame: &mut Frame, app: &App, area: Rect) {
+    let fmt = app.formatter();
     let last_refresh = app
         .last_refresh
-        .map(|dt| {
-            dt.with_timezone(&Local)
-                .format("%Y-%m-%d %H:%M:%S")
-                .to_string()
-        })
+        .map(|dt| fmt.timestamp(dt))
         .unwrap_or_else(|| "Never".to_string());
 
     let filter_status = if app.filter_non_empty { "ON" } else { "OFF" };
 
-    let status_text = if app.awaiting_purge_confirmation || app.purge_in_progress {
+    let status_text = if let Some(input) = &app.input {
+        format!("{}{}_", input.prompt, input.value)
+    } else if app.is_prompting() || app.purge_in_progress || app.is_busy() {
         // Show confirmation prompt or purge-in-progress message
         app.status_message.clone()
     } else {
         // Normal status
         format!(
-            "{} | Last Refresh: {} | Filter: {} | [Q]uit [R]efresh [F]ilter [Shift+X]Purge [↑/↓]Navigate",
+            "{} | Last Refresh: {} | Filter: {} | [Q]uit [R]efresh [F]ilter [A]ttributes [H]uman/raw [Z]one [E]xport [I]mport [S]end [C]ompose [V]iew messages [Shift+M]etrics [W]tail [L]og [P]rofile [G]region [Shift+X]Purge [↑/↓]Navigate",
             app.status_message, last_refresh, filter_status
         )
     };
 
-    let status_style = if app.awaiting_purge_confirmation || app.purge_in_progress {
+    let status_style = if app.is_prompting() || app.purge_in_progress || app.is_busy() {
         Style::default()
             .fg(Color::Yellow)
             .add_modifier(Modifier::BOLD)
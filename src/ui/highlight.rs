@@ -0,0 +1,159 @@
+//! Syntax colouring for decoded message bodies.
+
+use crate::body::{BodyFormat, DecodedBody};
+use ratatui::{
+    style::{Color, Style},
+    text::{Line, Span},
+};
+
+const KEY: Color = Color::Cyan;
+const STRING: Color = Color::Green;
+const NUMBER: Color = Color::Yellow;
+const KEYWORD: Color = Color::Magenta;
+const TAG: Color = Color::Blue;
+
+pub fn body_lines(body: &DecodedBody) -> Vec<Line<'static>> {
+    body.text
+        .lines()
+        .map(|line| match body.format {
+            BodyFormat::Json => json_line(line),
+            BodyFormat::Xml => xml_line(line),
+            BodyFormat::Text => Line::from(line.to_string()),
+        })
+        .collect()
+}
+
+/// Colours one line of pretty-printed JSON. Strings followed by `:` are
+/// object keys.
+fn json_line(line: &str) -> Line<'static> {
+    let chars: Vec<char> = line.chars().collect();
+    let mut spans = Vec::new();
+    let mut plain = String::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let start = i;
+        let color = if c == '"' {
+            i += 1;
+            while i < chars.len() && chars[i] != '"' {
+                i += if chars[i] == '\\' { 2 } else { 1 };
+            }
+            i = (i + 1).min(chars.len());
+            let is_key = chars[i..]
+                .iter()
+                .find(|c| !c.is_whitespace())
+                .is_some_and(|&c| c == ':');
+            if is_key { KEY } else { STRING }
+        } else if c == '-' || c.is_ascii_digit() {
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || "+-.".contains(chars[i]))
+            {
+                i += 1;
+            }
+            NUMBER
+        } else if c.is_ascii_alphabetic() {
+            while i < chars.len() && chars[i].is_ascii_alphabetic() {
+                i += 1;
+            }
+            KEYWORD
+        } else {
+            plain.push(c);
+            i += 1;
+            continue;
+        };
+
+        if !plain.is_empty() {
+            spans.push(Span::raw(std::mem::take(&mut plain)));
+        }
+        let token: String = chars[start..i].iter().collect();
+        spans.push(Span::styled(token, Style::default().fg(color)));
+    }
+    if !plain.is_empty() {
+        spans.push(Span::raw(plain));
+    }
+    Line::from(spans)
+}
+
+/// Colours tags, with quoted attribute values set apart; text between
+/// tags is left plain.
+fn xml_line(line: &str) -> Line<'static> {
+    let mut spans = Vec::new();
+    let mut rest = line;
+    while !rest.is_empty() {
+        if rest.starts_with('<') {
+            let end = rest.find('>').map_or(rest.len(), |i| i + 1);
+            let mut tag = &rest[..end];
+            while let Some(open) = tag.find('"') {
+                let close = tag[open + 1..]
+                    .find('"')
+                    .map_or(tag.len(), |i| open + i + 2);
+                spans.push(Span::styled(
+                    tag[..open].to_string(),
+                    Style::default().fg(TAG),
+                ));
+                spans.push(Span::styled(
+                    tag[open..close].to_string(),
+                    Style::default().fg(STRING),
+                ));
+                tag = &tag[close..];
+            }
+            spans.push(Span::styled(tag.to_string(), Style::default().fg(TAG)));
+            rest = &rest[end..];
+        } else {
+            let end = rest.find('<').unwrap_or(rest.len());
+            spans.push(Span::raw(rest[..end].to_string()));
+            rest = &rest[end..];
+        }
+    }
+    Line::from(spans)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn colored(line: &Line) -> Vec<(String, Option<Color>)> {
+        line.spans
+            .iter()
+            .map(|span| (span.content.to_string(), span.style.fg))
+            .collect()
+    }
+
+    #[test]
+    fn colours_json_tokens() {
+        let line = json_line(r#"  "total": -12.5e3, "ok": true, "note": "a \"b\":""#);
+        assert_eq!(
+            colored(&line),
+            [
+                ("  ".to_string(), None),
+                ("\"total\"".to_string(), Some(KEY)),
+                (": ".to_string(), None),
+                ("-12.5e3".to_string(), Some(NUMBER)),
+                (", ".to_string(), None),
+                ("\"ok\"".to_string(), Some(KEY)),
+                (": ".to_string(), None),
+                ("true".to_string(), Some(KEYWORD)),
+                (", ".to_string(), None),
+                ("\"note\"".to_string(), Some(KEY)),
+                (": ".to_string(), None),
+                ("\"a \\\"b\\\":\"".to_string(), Some(STRING)),
+            ]
+        );
+    }
+
+    #[test]
+    fn colours_xml_tags_and_attributes() {
+        let line = xml_line(r#"  <item id="1">book</item>"#);
+        assert_eq!(
+            colored(&line),
+            [
+                ("  ".to_string(), None),
+                ("<item id=".to_string(), Some(TAG)),
+                ("\"1\"".to_string(), Some(STRING)),
+                (">".to_string(), Some(TAG)),
+                ("book".to_string(), None),
+                ("</item>".to_string(), Some(TAG)),
+            ]
+        );
+    }
+}
@@ -0,0 +1,336 @@
+//! Rendered-buffer snapshots of the whole UI.
+//!
+//! Snapshots live in `src/ui/snapshots/`. After an intentional layout change,
+//! regenerate them with `INSTA_UPDATE=always cargo test` (or review them one
+//! by one with `cargo insta review`) and commit the updated `.snap` files.
+
+use super::draw_at;
+use crate::app::App;
+use crate::audit::{AuditEntry, AuditLog, AuditOutcome};
+use crate::backend::memory::{FakeMessage, FakeQueue, InMemoryBackend, Operation};
+use crate::types::QueueMetric;
+use chrono::{DateTime, TimeZone, Utc};
+use ratatui::{Terminal, backend::TestBackend};
+
+fn now() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 6, 1, 12, 0, 0).unwrap()
+}
+
+fn queue(name: &str, visible: i64) -> FakeQueue {
+    let mut queue = FakeQueue::new(name, visible);
+    let created = Utc
+        .with_ymd_and_hms(2024, 3, 1, 9, 30, 0)
+        .unwrap()
+        .timestamp();
+    queue.details.created_timestamp = Some(created);
+    queue.details.last_modified_timestamp = Some(created);
+    queue
+}
+
+async fn app_with(backend: InMemoryBackend) -> App {
+    let mut app = App::new(Box::new(backend));
+    app.use_utc = true;
+    app.refresh_queues().await.unwrap();
+    while app.is_linting() {
+        tokio::task::yield_now().await;
+        app.step_lint();
+    }
+    app.last_refresh = Some(now());
+    app
+}
+
+fn render(app: &App, width: u16, height: u16) -> Terminal<TestBackend> {
+    let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+    terminal.draw(|f| draw_at(f, app, now())).unwrap();
+    terminal
+}
+
+#[tokio::test]
+async fn empty() {
+    let app = app_with(InMemoryBackend::new()).await;
+    insta::assert_snapshot!(render(&app, 120, 24).backend());
+}
+
+#[tokio::test]
+async fn many_queues() {
+    let backend = (0..30).fold(InMemoryBackend::new(), |backend, i| {
+        backend.with_queue(queue(&format!("service-{:02}", i), i * 37))
+    });
+    let mut app = app_with(backend).await;
+    for _ in 0..25 {
+        app.next_queue();
+    }
+    app.refresh_selected_details().await.unwrap();
+    insta::assert_snapshot!(render(&app, 120, 30).backend());
+}
+
+#[tokio::test]
+async fn dead_letter_queues() {
+    let backend = InMemoryBackend::new()
+        .with_queue(queue("orders", 12))
+        .with_queue(queue("orders-dlq", 250))
+        .with_queue(queue("payments_dlq", 0))
+        .with_queue(queue("billing-dlq.fifo", 3));
+    let app = app_with(backend).await;
+    insta::assert_snapshot!(render(&app, 120, 24).backend());
+}
+
+#[tokio::test]
+async fn lint_findings() {
+    let mut payments = queue("payments.fifo", 40);
+    payments.details.attributes.insert(
+        "RedrivePolicy".to_string(),
+        r#"{"deadLetterTargetArn":"arn:aws:sqs:us-east-1:000000000000:payments-dlq","maxReceiveCount":1}"#
+            .to_string(),
+    );
+    payments
+        .details
+        .attributes
+        .insert("ReceiveMessageWaitTimeSeconds".to_string(), "0".to_string());
+    let backend = InMemoryBackend::new()
+        .with_queue(payments)
+        .with_queue(queue("payments-dlq", 2))
+        .with_queue(queue("emails", 0));
+    let app = app_with(backend).await;
+    insta::assert_snapshot!(render(&app, 120, 38).backend());
+}
+
+#[tokio::test]
+async fn purge_prompt() {
+    let backend = InMemoryBackend::new()
+        .with_queue(queue("orders", 1_500))
+        .with_queue(queue("orders-dlq", 4));
+    let mut app = app_with(backend).await;
+    app.request_purge_confirmation();
+    insta::assert_snapshot!(render(&app, 120, 24).backend());
+}
+
+#[tokio::test]
+async fn error_status() {
+    let backend = InMemoryBackend::new().with_queue(queue("orders", 1));
+    backend.fail_next(
+        Operation::ListQueues,
+        "dispatch failure: connection refused",
+    );
+    let app = app_with(backend).await;
+    insta::assert_snapshot!(render(&app, 120, 24).backend());
+}
+
+#[tokio::test]
+async fn connection_screen() {
+    let backend = InMemoryBackend::new().with_queue(queue("orders", 1));
+    backend.fail_next(Operation::ListQueues, "ExpiredToken");
+    let mut app = App::new(Box::new(backend));
+    app.connection.profile = Some("dev".to_string());
+    app.connection.region = Some("eu-west-1".to_string());
+    app.use_utc = true;
+    app.refresh_queues().await.unwrap();
+    insta::assert_snapshot!(render(&app, 120, 24).backend());
+}
+
+#[tokio::test(start_paused = true)]
+async fn peek_view() {
+    let mut orders = queue("orders", 0);
+    for (i, sent_minutes_ago) in [(1, 90), (2, 30), (3, 2)] {
+        let id = orders.next_message_id();
+        let mut message = FakeMessage::new(&id, &format!("{{\"orderId\":\"A-{}\"}}", i));
+        message.sent_timestamp =
+            (now() - chrono::Duration::minutes(sent_minutes_ago)).timestamp_millis();
+        orders = orders.with_message(message);
+    }
+    let mut app = app_with(InMemoryBackend::new().with_queue(orders)).await;
+    app.toggle_peek().await;
+    app.toggle_message_selected();
+    tokio::time::advance(std::time::Duration::from_secs(12)).await;
+    insta::assert_snapshot!(render(&app, 120, 16).backend());
+}
+
+#[tokio::test(start_paused = true)]
+async fn peek_grouped_by_receives() {
+    let mut orders = queue("orders", 0);
+    orders.details.attributes.insert(
+        "RedrivePolicy".to_string(),
+        r#"{"deadLetterTargetArn":"arn:aws:sqs:us-east-1:000000000000:orders-dlq","maxReceiveCount":5}"#
+            .to_string(),
+    );
+    for (i, receives, first_received_minutes_ago) in [(1, 0, 0), (2, 4, 40), (3, 0, 0), (4, 1, 3)] {
+        let id = orders.next_message_id();
+        let mut message = FakeMessage::new(&id, &format!("{{\"orderId\":\"C-{}\"}}", i));
+        message.sent_timestamp = (now() - chrono::Duration::hours(1)).timestamp_millis();
+        message.receive_count = receives;
+        if receives > 0 {
+            message.first_receive_timestamp = Some(
+                (now() - chrono::Duration::minutes(first_received_minutes_ago)).timestamp_millis(),
+            );
+        }
+        orders = orders.with_message(message);
+    }
+    let mut app = app_with(InMemoryBackend::new().with_queue(orders)).await;
+    app.toggle_peek().await;
+    app.toggle_receive_groups();
+    insta::assert_snapshot!(render(&app, 140, 24).backend());
+}
+
+#[tokio::test]
+async fn retention_warning() {
+    let mut dlq = queue("orders-dlq", 0);
+    let mut old = FakeMessage::new("m-1", "{}");
+    // Four days' retention and 90 hours old, so gone in six hours
+    old.sent_timestamp = (Utc::now() - chrono::Duration::hours(90)).timestamp_millis();
+    dlq = dlq.with_message(old);
+    let app = app_with(InMemoryBackend::new().with_queue(dlq)).await;
+    insta::assert_snapshot!(render(&app, 120, 24).backend());
+}
+
+#[tokio::test]
+async fn metrics_panel() {
+    let real_now = Utc::now();
+    let mut orders = queue("orders", 0);
+    let mut old = FakeMessage::new("m-1", "{}");
+    old.sent_timestamp = (real_now - chrono::Duration::hours(82)).timestamp_millis();
+    orders = orders.with_message(old);
+    for minutes_ago in 0..60 {
+        let at = real_now - chrono::Duration::minutes(minutes_ago);
+        orders = orders
+            .with_activity(
+                at,
+                QueueMetric::NumberOfMessagesSent,
+                30 + minutes_ago as u64 % 20,
+            )
+            .with_activity(at, QueueMetric::NumberOfMessagesReceived, 30)
+            .with_activity(at, QueueMetric::NumberOfMessagesDeleted, 28);
+    }
+    let mut app = app_with(InMemoryBackend::new().with_queue(orders)).await;
+    app.cycle_metrics_window().await;
+    insta::assert_snapshot!(render(&app, 140, 30).backend());
+}
+
+#[tokio::test(start_paused = true)]
+async fn tail_view() {
+    let mut orders = queue("orders", 0);
+    for (i, sent_seconds_ago) in [(1, 40), (2, 5)] {
+        let id = orders.next_message_id();
+        let mut message = FakeMessage::new(&id, &format!("{{\"orderId\":\"B-{}\"}}", i));
+        message.sent_timestamp =
+            (now() - chrono::Duration::seconds(sent_seconds_ago)).timestamp_millis();
+        orders = orders.with_message(message);
+    }
+    let mut app = app_with(InMemoryBackend::new().with_queue(orders)).await;
+    app.toggle_tail();
+    // Paused time runs the background long polls forward to here
+    tokio::time::sleep(std::time::Duration::from_secs(10)).await;
+    app.step_tail();
+    insta::assert_snapshot!(render(&app, 120, 16).backend());
+}
+
+#[tokio::test]
+async fn body_viewer() {
+    let mut orders = queue("orders", 0);
+    let id = orders.next_message_id();
+    let event = r#"{"detail-type":"OrderPlaced","source":"shop","detail":{"orderId":"A-1","total":12.5,"gift":false,"lines":[{"sku":"B-7","qty":2}]}}"#;
+    let sns = serde_json::json!({ "Type": "Notification", "Message": event }).to_string();
+    let orders = orders.with_message(FakeMessage::new(&id, &sns));
+    let mut app = app_with(InMemoryBackend::new().with_queue(orders)).await;
+    app.toggle_peek().await;
+    app.toggle_body_view();
+    app.toggle_unwrap_envelopes();
+    insta::assert_snapshot!(render(&app, 120, 20).backend());
+}
+
+#[tokio::test]
+async fn raw_attributes() {
+    let mut fifo = queue("payments.fifo", 9);
+    fifo.details.attributes.insert(
+        "RedrivePolicy".to_string(),
+        r#"{"deadLetterTargetArn":"arn:aws:sqs:us-east-1:000000000000:payments-dlq.fifo","maxReceiveCount":3}"#
+            .to_string(),
+    );
+    let mut app = app_with(InMemoryBackend::new().with_queue(fifo)).await;
+    app.toggle_raw_attributes();
+    insta::assert_snapshot!(render(&app, 120, 40).backend());
+}
+
+#[tokio::test]
+async fn tiny_terminal() {
+    let backend = InMemoryBackend::new()
+        .with_queue(queue("orders", 12))
+        .with_queue(queue("orders-dlq", 250));
+    let app = app_with(backend).await;
+    insta::assert_snapshot!(render(&app, 40, 10).backend());
+}
+
+#[tokio::test]
+async fn read_only_refuses_purge() {
+    let backend = InMemoryBackend::new().with_queue(queue("orders", 1_500));
+    let mut app = app_with(backend).await;
+    app.read_only = true;
+    app.request_purge_confirmation();
+    insta::assert_snapshot!(render(&app, 120, 24).backend());
+}
+
+#[tokio::test]
+async fn protected_purge_prompt() {
+    let backend = InMemoryBackend::new().with_queue(queue("prod-orders", 1_500));
+    let mut app = app_with(backend).await;
+    app.config = toml::from_str(
+        r#"
+        [[protected_queues]]
+        pattern = "prod-*"
+        reason = "production traffic"
+        "#,
+    )
+    .unwrap();
+    app.request_purge_confirmation();
+    app.input_char('p');
+    insta::assert_snapshot!(render(&app, 120, 24).backend());
+}
+
+#[tokio::test]
+async fn audit_log() {
+    let dir = tempfile::tempdir().unwrap();
+    let log = AuditLog::new(dir.path().join("audit.jsonl"));
+    let url = "https://sqs.us-east-1.amazonaws.com/000000000000/orders";
+    let entry = |minute, action: &str, before, outcome| AuditEntry {
+        timestamp: Utc.with_ymd_and_hms(2024, 6, 1, 11, minute, 0).unwrap(),
+        os_user: Some("alice".to_string()),
+        identity: Default::default(),
+        queue_url: url.to_string(),
+        action: action.to_string(),
+        parameters: serde_json::json!({}),
+        messages_before: before,
+        outcome,
+    };
+    log.append(&entry(10, "send", Some(1_499), AuditOutcome::Ok))
+        .unwrap();
+    // Left cut short by a crash mid-append
+    let mut file = std::fs::OpenOptions::new()
+        .append(true)
+        .open(log.path())
+        .unwrap();
+    std::io::Write::write_all(&mut file, b"{\"timestamp\":\"2024-06-01T11:1\n").unwrap();
+    log.append(&entry(
+        20,
+        "delete",
+        Some(1_500),
+        AuditOutcome::Partial { failed: 2 },
+    ))
+    .unwrap();
+    log.append(&entry(
+        30,
+        "purge",
+        None,
+        AuditOutcome::Error {
+            error: "AccessDenied".to_string(),
+        },
+    ))
+    .unwrap();
+
+    let mut app = app_with(InMemoryBackend::new().with_queue(queue("orders", 1_500))).await;
+    app.config.audit_log = Some(log.path().to_path_buf());
+    app.toggle_audit_log();
+    // The default message names the temporary log path.
+    assert!(app.status_message.starts_with("3 audit entries from "));
+    assert!(app.status_message.ends_with(", 1 unreadable lines skipped"));
+    app.status_message = "Audit log".to_string();
+    insta::assert_snapshot!(render(&app, 120, 16).backend());
+}